@@ -1,5 +1,13 @@
 use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
-use serde_toon2::{DecoderOptions, Delimiter, EncoderOptions, KeyFolding, PathExpansion};
+use serde_toon2::diff::{self, ChangeKind, DiffOptions};
+use serde_toon2::error::ErrorKind;
+use serde_toon2::extract::{self, BlockKind};
+use serde_toon2::query::Query;
+use serde_toon2::repair::{self, RepairKind};
+use serde_toon2::truncate::{self, ElisionKind, TruncateOptions};
+use serde_toon2::{
+    DecoderOptions, Delimiter, EncoderOptions, Indent, KeyFolding, LineEnding, PathExpansion, stats,
+};
 use std::path::Path;
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -34,6 +42,21 @@ impl From<KeyFoldingArg> for KeyFolding {
     }
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum LineEndingArg {
+    Lf,
+    Crlf,
+}
+
+impl From<LineEndingArg> for LineEnding {
+    fn from(arg: LineEndingArg) -> Self {
+        match arg {
+            LineEndingArg::Lf => LineEnding::Lf,
+            LineEndingArg::Crlf => LineEnding::CrLf,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum PathExpansionArg {
     Off,
@@ -46,6 +69,18 @@ enum OutputTypeArg {
     Yaml,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum QueryOutputArg {
+    Toon,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum DiffFormatArg {
+    Text,
+    JsonPatch,
+}
+
 impl From<PathExpansionArg> for PathExpansion {
     fn from(arg: PathExpansionArg) -> Self {
         match arg {
@@ -55,6 +90,26 @@ impl From<PathExpansionArg> for PathExpansion {
     }
 }
 
+fn parse_indent(s: &str) -> Result<Indent, String> {
+    match s {
+        "tab" | "tabs" => Ok(Indent::Tabs),
+        "auto" => Ok(Indent::Auto),
+        _ => s
+            .parse()
+            .map(Indent::Spaces)
+            .map_err(|_| format!("expected a number of spaces, `tab` or `auto`, got `{}`", s)),
+    }
+}
+
+/// Like [`parse_indent`], but rejects `auto`, which only means something when decoding.
+fn parse_encode_indent(s: &str) -> Result<Indent, String> {
+    match parse_indent(s) {
+        Ok(Indent::Auto) => Err("`auto` only applies when decoding".to_string()),
+        Ok(indent) => Ok(indent),
+        Err(_) => Err(format!("expected a number of spaces or `tab`, got `{}`", s)),
+    }
+}
+
 #[derive(Debug, Parser)]
 #[command(name = "toon")]
 #[command(about = "Convert between JSON/YAML and TOON formats", long_about = None)]
@@ -78,7 +133,28 @@ impl From<PathExpansionArg> for PathExpansion {
     \x1b[1mtoon encode\x1b[0m --delimiter pipe data.json
 
   Enable strict mode when parsing TOON:
-    \x1b[1mtoon decode\x1b[0m --strict data.toon")]
+    \x1b[1mtoon decode\x1b[0m --strict data.toon
+
+  Decode TOON indented with tabs or any number of spaces:
+    \x1b[1mtoon decode\x1b[0m --indent auto data.toon
+
+  Fix common mistakes in LLM-written TOON while parsing:
+    \x1b[1mtoon decode\x1b[0m --repair reply.toon
+
+  Decode the first TOON block in a Markdown reply:
+    \x1b[1mtoon decode\x1b[0m --extract reply.md
+
+  Fit the output into roughly 2000 tokens:
+    \x1b[1mtoon encode\x1b[0m --token-budget 2000 data.json
+
+  Compare TOON and JSON sizes for a file:
+    \x1b[1mtoon stats\x1b[0m data.json
+
+  Select the names of items costing more than 10:
+    \x1b[1mtoon query\x1b[0m 'items[?price>10].name' data.toon
+
+  Compare two TOON files, matching table rows by their `id` field:
+    \x1b[1mtoon diff\x1b[0m --key id old.toon new.toon")]
 struct Cli {
     #[command(subcommand)]
     command: Command,
@@ -101,16 +177,37 @@ enum Command {
 
         #[arg(
             long,
-            help = "Number of spaces per indentation level",
+            value_parser = parse_encode_indent,
+            help = "Indentation per level: a number of spaces or `tab`",
             default_value = "2"
         )]
-        indent: usize,
+        indent: Indent,
 
         #[arg(long, value_enum, help = "Key folding mode", default_value = "off")]
         key_folding: KeyFoldingArg,
 
         #[arg(long, help = "Maximum depth for inlining nested structures")]
         flatten_depth: Option<usize>,
+
+        #[arg(long, help = "Keep at most this many elements in each array")]
+        max_rows: Option<usize>,
+
+        #[arg(long, help = "Shorten strings longer than this many characters")]
+        max_string_len: Option<usize>,
+
+        #[arg(long, help = "Shrink the output until its estimated token count fits")]
+        token_budget: Option<usize>,
+
+        #[arg(
+            long,
+            value_enum,
+            help = "Line break between lines",
+            default_value = "lf"
+        )]
+        line_ending: LineEndingArg,
+
+        #[arg(long, help = "End the output with a line break")]
+        trailing_newline: bool,
     },
 
     #[command(alias = "d", about = "Decode TOON to JSON or YAML format")]
@@ -120,20 +217,102 @@ enum Command {
 
         #[arg(
             long,
-            help = "Number of spaces per indentation level",
+            value_parser = parse_indent,
+            help = "Indentation per level: a number of spaces, `tab` or `auto`",
             default_value = "2"
         )]
-        indent: usize,
+        indent: Indent,
 
         #[arg(long, help = "Enable strict validation mode")]
         strict: bool,
 
+        #[arg(long, help = "Fix common mistakes instead of rejecting the input")]
+        repair: bool,
+
+        #[arg(long, help = "Decode the first TOON block found in surrounding text")]
+        extract: bool,
+
         #[arg(long, value_enum, help = "Path expansion mode", default_value = "off")]
         expand_paths: PathExpansionArg,
 
         #[arg(short, long, value_enum, help = "Output type", default_value = "json")]
         output_type: OutputTypeArg,
     },
+
+    #[command(about = "Report bytes and estimated tokens for TOON compared with JSON")]
+    Stats {
+        #[arg(help = "Input source: file path, URL, or raw JSON or YAML string")]
+        input: String,
+
+        #[arg(
+            long,
+            value_enum,
+            help = "Delimiter for array elements",
+            default_value = "comma"
+        )]
+        delimiter: DelimiterArg,
+
+        #[arg(
+            long,
+            value_parser = parse_encode_indent,
+            help = "Indentation per level: a number of spaces or `tab`",
+            default_value = "2"
+        )]
+        indent: Indent,
+
+        #[arg(long, value_enum, help = "Key folding mode", default_value = "off")]
+        key_folding: KeyFoldingArg,
+    },
+
+    #[command(
+        alias = "q",
+        about = "Select parts of a TOON document with a path expression"
+    )]
+    Query {
+        #[arg(help = "Path expression, such as `users[*].name` or `items[?price>10]`")]
+        expr: String,
+
+        #[arg(help = "Input source: file path, URL, or raw TOON string")]
+        input: String,
+
+        #[arg(
+            long,
+            value_parser = parse_indent,
+            help = "Indentation per level: a number of spaces, `tab` or `auto`",
+            default_value = "2"
+        )]
+        indent: Indent,
+
+        #[arg(short, long, value_enum, help = "Output type", default_value = "toon")]
+        output_type: QueryOutputArg,
+    },
+
+    #[command(about = "Show the structural differences between two TOON documents")]
+    Diff {
+        #[arg(help = "Old input source: file path, URL, or raw TOON string")]
+        old: String,
+
+        #[arg(help = "New input source: file path, URL, or raw TOON string")]
+        new: String,
+
+        #[arg(
+            long = "key",
+            value_name = "FIELD",
+            help = "Match rows of arrays of objects by this field; may be repeated"
+        )]
+        keys: Vec<String>,
+
+        #[arg(
+            long,
+            value_parser = parse_indent,
+            help = "Indentation per level: a number of spaces, `tab` or `auto`",
+            default_value = "2"
+        )]
+        indent: Indent,
+
+        #[arg(long, value_enum, help = "Output format", default_value = "text")]
+        format: DiffFormatArg,
+    },
 }
 
 #[tokio::main]
@@ -171,6 +350,157 @@ fn read_from_file(path: &str) -> Result<String, Box<dyn std::error::Error>> {
     Ok(content)
 }
 
+fn parse_json_or_yaml(content: &str) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    // attempt to parse as JSON first
+    // JSON parsing fails on missing opening `{` for most inputs
+    match serde_json::from_str(content) {
+        Ok(json) => Ok(json),
+        // try YAML instead
+        Err(_) => Ok(serde_yaml::from_str(content)?),
+    }
+}
+
+/// Repairs the first TOON block in `text`, choosing it as `extract::from_str_first` does.
+/// Lines in repairs and errors count from the start of `text`.
+fn repair_first_block(
+    text: &str,
+    options: DecoderOptions,
+) -> Result<repair::Repaired, serde_toon2::Error> {
+    for block in extract::find_blocks(text) {
+        let offset = text[..block.range.start].matches('\n').count();
+        match repair::from_str_repaired(block.text, options.clone()) {
+            Ok(mut repaired) => {
+                for repair in &mut repaired.repairs {
+                    repair.line += offset;
+                }
+                return Ok(repaired);
+            }
+            Err(err) if block.kind == BlockKind::Labelled => {
+                return Err(match (err.line(), err.column()) {
+                    (Some(line), Some(column)) => err.with_location(line + offset, column),
+                    _ => err,
+                });
+            }
+            Err(_) => {}
+        }
+    }
+    Err(serde_toon2::Error::new(
+        ErrorKind::InvalidSyntax,
+        "No TOON block found",
+    ))
+}
+
+fn print_repairs(repaired: &repair::Repaired) {
+    for repair in &repaired.repairs {
+        match repair.kind {
+            RepairKind::CodeFence => eprintln!("line {}: removed code fence", repair.line),
+            RepairKind::Count { declared, received } => eprintln!(
+                "line {}: array declares {} items but has {}",
+                repair.line, declared, received
+            ),
+            RepairKind::TrailingDelimiter => {
+                eprintln!("line {}: dropped trailing delimiter", repair.line)
+            }
+            RepairKind::Indentation { found, expected } => eprintln!(
+                "line {}: indent width {} instead of {}",
+                repair.line, found, expected
+            ),
+        }
+    }
+}
+
+fn print_elisions(truncated: &truncate::Truncated) {
+    for elision in &truncated.elisions {
+        let path = if elision.path.is_empty() {
+            "/"
+        } else {
+            &elision.path
+        };
+        match elision.kind {
+            ElisionKind::Rows { kept, total } => {
+                eprintln!("elided {} of {} rows at {}", total - kept, total, path)
+            }
+            ElisionKind::String { kept, total } => {
+                eprintln!(
+                    "elided {} of {} characters at {}",
+                    total - kept,
+                    total,
+                    path
+                )
+            }
+        }
+    }
+    if !truncated.fits_budget {
+        eprintln!(
+            "warning: output is still ~{} tokens, over the token budget",
+            truncated.tokens
+        );
+    }
+}
+
+fn print_changes(changes: &[diff::Change]) -> Result<(), serde_json::Error> {
+    for change in changes {
+        let path = if change.path.is_empty() {
+            "(root)"
+        } else {
+            &change.path
+        };
+        match &change.kind {
+            ChangeKind::Added(value) => println!("+ {}: {}", path, serde_json::to_string(value)?),
+            ChangeKind::Removed(value) => {
+                println!("- {}: {}", path, serde_json::to_string(value)?)
+            }
+            ChangeKind::Changed { from, to } => println!(
+                "~ {}: {} -> {}",
+                path,
+                serde_json::to_string(from)?,
+                serde_json::to_string(to)?
+            ),
+            ChangeKind::Moved { from } => println!("> {}: moved from {}", path, from),
+        }
+    }
+    Ok(())
+}
+
+fn print_stats(report: &stats::Comparison) {
+    println!(
+        "{:<16}{:>10}{:>10}{:>10}",
+        "format", "bytes", "tokens", "savings"
+    );
+    println!(
+        "{:<16}{:>10}{:>10}{:>10}",
+        "TOON", report.toon.bytes, report.toon.tokens, "-"
+    );
+    for (name, measurement) in [
+        ("JSON (compact)", &report.json_compact),
+        ("JSON (pretty)", &report.json_pretty),
+    ] {
+        println!(
+            "{:<16}{:>10}{:>10}{:>9.1}%",
+            name,
+            measurement.bytes,
+            measurement.tokens,
+            report.toon.token_savings(measurement)
+        );
+    }
+
+    if report.keys.is_empty() {
+        return;
+    }
+
+    println!();
+    println!(
+        "{:<24}{:>12}{:>16}{:>15}",
+        "key", "TOON tokens", "compact tokens", "pretty tokens"
+    );
+    for key in &report.keys {
+        println!(
+            "{:<24}{:>12}{:>16}{:>15}",
+            key.key, key.toon.tokens, key.json_compact.tokens, key.json_pretty.tokens
+        );
+    }
+}
+
 async fn process(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
     match &cli.command {
         Command::Encode {
@@ -179,31 +509,53 @@ async fn process(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
             indent,
             key_folding,
             flatten_depth,
+            max_rows,
+            max_string_len,
+            token_budget,
+            line_ending,
+            trailing_newline,
         } => {
             let content = get_input_content(input).await?;
-
-            // attempt to parse as JSON first
-            // JSON parsing fails on missing opening `{` for most inputs
-            let data: serde_json::Value = match serde_json::from_str(&content) {
-                Ok(json) => json,
-                // try YAML instead
-                Err(_) => serde_yaml::from_str(&content)?,
-            };
+            let data = parse_json_or_yaml(&content)?;
 
             let encoder_opts = EncoderOptions {
                 indent: *indent,
                 delimiter: (*delimiter).into(),
                 key_folding: (*key_folding).into(),
                 flatten_depth: flatten_depth.unwrap_or(usize::MAX),
+                sort_keys: false,
+                line_ending: (*line_ending).into(),
+                trailing_newline: *trailing_newline,
+            };
+
+            if max_rows.is_none() && max_string_len.is_none() && token_budget.is_none() {
+                serde_toon2::async_io::to_async_write_with_options(
+                    tokio::io::stdout(),
+                    &data,
+                    encoder_opts,
+                )
+                .await?;
+                return Ok(());
+            }
+
+            let value = serde_toon2::Value::from(data);
+            let truncate_opts = TruncateOptions {
+                max_rows: *max_rows,
+                max_string_len: *max_string_len,
+                token_budget: *token_budget,
+                ..Default::default()
             };
 
-            let toon_str = serde_toon2::to_string_with_options(&data, encoder_opts)?;
-            print!("{}", toon_str);
+            let truncated = truncate::to_string_truncated(&value, &encoder_opts, &truncate_opts)?;
+            print!("{}", truncated.toon);
+            print_elisions(&truncated);
         }
         Command::Decode {
             input,
             indent,
             strict,
+            repair,
+            extract,
             expand_paths,
             output_type,
         } => {
@@ -213,16 +565,110 @@ async fn process(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
                 indent: *indent,
                 strict: *strict,
                 expand_paths: (*expand_paths).into(),
+                repair: *repair,
             };
 
-            let value: serde_json::Value =
-                serde_toon2::from_str_with_options(&content, decoder_opts)?;
+            let value: serde_json::Value = match (*extract, *repair) {
+                (true, true) => {
+                    let repaired = repair_first_block(&content, decoder_opts)?;
+                    print_repairs(&repaired);
+                    serde_json::to_value(&repaired.value)?
+                }
+                (true, false) => extract::from_str_first(&content, decoder_opts)?.value,
+                (false, true) => {
+                    let repaired = repair::from_str_repaired(&content, decoder_opts)?;
+                    print_repairs(&repaired);
+                    serde_json::to_value(&repaired.value)?
+                }
+                (false, false) => serde_toon2::from_str_with_options(&content, decoder_opts)?,
+            };
 
             match output_type {
                 OutputTypeArg::Json => print!("{}", serde_json::to_string_pretty(&value)?),
                 OutputTypeArg::Yaml => print!("{}", serde_yaml::to_string(&value)?),
             }
         }
+        Command::Stats {
+            input,
+            delimiter,
+            indent,
+            key_folding,
+        } => {
+            let content = get_input_content(input).await?;
+            let data = parse_json_or_yaml(&content)?;
+            let value = serde_toon2::Value::from(data);
+
+            let encoder_opts = EncoderOptions {
+                indent: *indent,
+                delimiter: (*delimiter).into(),
+                key_folding: (*key_folding).into(),
+                flatten_depth: usize::MAX,
+                sort_keys: false,
+                line_ending: LineEnding::Lf,
+                trailing_newline: false,
+            };
+
+            let report = stats::compare(&value, &encoder_opts)?;
+            print_stats(&report);
+        }
+        Command::Query {
+            expr,
+            input,
+            indent,
+            output_type,
+        } => {
+            let query = Query::parse(expr)?;
+            let content = get_input_content(input).await?;
+            let decoder_opts = DecoderOptions {
+                indent: *indent,
+                ..Default::default()
+            };
+            let value: serde_toon2::Value =
+                serde_toon2::from_str_with_options(&content, decoder_opts)?;
+
+            // Names and indexes select a single value, printed on its own; anything
+            // else prints the matches as an array
+            let mut results = query.select_cloned(&value);
+            let output = if query.is_singular() {
+                results.pop().unwrap_or(serde_toon2::Value::Null)
+            } else {
+                serde_toon2::Value::Array(results)
+            };
+
+            match output_type {
+                QueryOutputArg::Toon => println!("{}", serde_toon2::to_string(&output)?),
+                QueryOutputArg::Json => println!("{}", serde_json::to_string_pretty(&output)?),
+            }
+        }
+        Command::Diff {
+            old,
+            new,
+            keys,
+            indent,
+            format,
+        } => {
+            let decoder_opts = DecoderOptions {
+                indent: *indent,
+                ..Default::default()
+            };
+            let old_content = get_input_content(old).await?;
+            let new_content = get_input_content(new).await?;
+            let old_value: serde_toon2::Value =
+                serde_toon2::from_str_with_options(&old_content, decoder_opts.clone())?;
+            let new_value: serde_toon2::Value =
+                serde_toon2::from_str_with_options(&new_content, decoder_opts)?;
+
+            let diff_opts = DiffOptions { keys: keys.clone() };
+            let changes = diff::diff_with_options(&old_value, &new_value, &diff_opts);
+
+            match format {
+                DiffFormatArg::Text => print_changes(&changes)?,
+                DiffFormatArg::JsonPatch => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&diff::to_json_patch(&changes))?
+                ),
+            }
+        }
     }
 
     Ok(())
@@ -0,0 +1,30 @@
+use std::process::Command;
+
+fn toon(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_toon"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_encode_keeps_key_order() {
+    let toon = toon(&["encode", r#"{"b":1,"a":{"d":true,"c":null}}"#]);
+    assert_eq!(toon.trim_end(), "b: 1\na:\n  d: true\n  c: null");
+}
+
+#[test]
+fn test_decode_keeps_key_order() {
+    let json = toon(&["decode", "b: 1\na:\n  d: true\n  c: null"]);
+    let compact: String = json.split_whitespace().collect();
+    assert_eq!(compact, r#"{"b":1,"a":{"d":true,"c":null}}"#);
+
+    let yaml = toon(&["decode", "-o", "yaml", "b: 1\na: 2"]);
+    assert_eq!(yaml, "b: 1\na: 2\n");
+}
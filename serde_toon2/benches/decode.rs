@@ -0,0 +1,74 @@
+//! Decoding throughput on deeply nested and very wide documents, into owned and
+//! borrowed values.
+//!
+//! Run with `cargo bench --bench decode`.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use serde_toon2::{Value, from_str, from_str_borrowed};
+use std::fmt::Write;
+use std::hint::black_box;
+
+/// A chain of `depth` nested objects, each with a few scalar fields.
+fn deep_document(depth: usize) -> String {
+    let mut doc = String::new();
+    for level in 0..depth {
+        let indent = "  ".repeat(level);
+        writeln!(doc, "{indent}id: {level}").unwrap();
+        writeln!(doc, "{indent}name: level {level}").unwrap();
+        writeln!(doc, "{indent}tags[3]: a,b,c").unwrap();
+        writeln!(doc, "{indent}child:").unwrap();
+    }
+    doc
+}
+
+/// An object with `width` sibling sections, each holding a small nested object and
+/// a tabular array.
+fn wide_document(width: usize) -> String {
+    let mut doc = String::new();
+    for i in 0..width {
+        writeln!(doc, "section_{i}:").unwrap();
+        writeln!(doc, "  enabled: true").unwrap();
+        writeln!(doc, "  limits:").unwrap();
+        writeln!(doc, "    max: {}", i * 10).unwrap();
+        writeln!(doc, "    ratio: 0.{i}").unwrap();
+        writeln!(doc, "  users[2]{{id,name,role}}:").unwrap();
+        writeln!(doc, "    {i},Ada,admin").unwrap();
+        writeln!(doc, "    {},Bob,\"ops, on-call\"", i + 1).unwrap();
+    }
+    doc
+}
+
+fn bench_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode_deep");
+    for depth in [16, 64, 256] {
+        let doc = deep_document(depth);
+        group.throughput(Throughput::Bytes(doc.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(depth), &doc, |b, doc| {
+            b.iter(|| from_str::<Value>(black_box(doc)).unwrap())
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("decode_wide");
+    for width in [100, 1_000, 10_000] {
+        let doc = wide_document(width);
+        group.throughput(Throughput::Bytes(doc.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(width), &doc, |b, doc| {
+            b.iter(|| from_str::<Value>(black_box(doc)).unwrap())
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("decode_wide_borrowed");
+    for width in [100, 1_000, 10_000] {
+        let doc = wide_document(width);
+        group.throughput(Throughput::Bytes(doc.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(width), &doc, |b, doc| {
+            b.iter(|| from_str_borrowed(black_box(doc)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_decode);
+criterion_main!(benches);
@@ -0,0 +1,141 @@
+//! Peak heap usage of the streaming serializer compared with the buffered path.
+//!
+//! Run with `cargo bench --bench memory`. The buffered side builds the whole document
+//! as a `Value` first and writes that, as the serializer used to for every array.
+//! Tables are still collected until they end, so `Vec<Payment>` shows that cost.
+
+use serde::Serialize;
+use serde_toon2::ser::Serializer;
+use serde_toon2::{EncoderOptions, to_value, to_writer_with_options};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(current, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[derive(Serialize)]
+struct Address {
+    street: String,
+    city: String,
+    country: String,
+}
+
+#[derive(Serialize)]
+struct Account {
+    id: u64,
+    name: String,
+    email: String,
+    active: bool,
+    balance: f64,
+    address: Address,
+}
+
+#[derive(Serialize)]
+struct Payment {
+    id: u64,
+    account: String,
+    amount: f64,
+    settled: bool,
+}
+
+fn account(i: usize) -> Account {
+    Account {
+        id: i as u64,
+        name: format!("User {}", i),
+        email: format!("user{}@example.com", i),
+        active: !i.is_multiple_of(3),
+        balance: i as f64 * 1.25,
+        address: Address {
+            street: format!("{} Main Street", i),
+            city: "Springfield".to_string(),
+            country: "US".to_string(),
+        },
+    }
+}
+
+fn payment(i: usize) -> Payment {
+    Payment {
+        id: i as u64,
+        account: format!("account_{}", i),
+        amount: i as f64 * 0.75,
+        settled: i.is_multiple_of(2),
+    }
+}
+
+/// Returns the peak heap growth, in bytes, while `encode` runs.
+fn peak_while(encode: impl FnOnce()) -> usize {
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    encode();
+    PEAK.load(Ordering::Relaxed) - baseline
+}
+
+fn streaming<T: Serialize>(value: &T) -> usize {
+    peak_while(|| {
+        to_writer_with_options(std::io::sink(), value, EncoderOptions::default()).unwrap()
+    })
+}
+
+fn buffered<T: Serialize>(value: &T) -> usize {
+    peak_while(|| {
+        let value = to_value(value).unwrap();
+        Serializer::new(std::io::sink(), EncoderOptions::default())
+            .serialize_value(&value)
+            .unwrap();
+    })
+}
+
+fn report<T: Serialize>(case: &str, count: usize, value: &T) {
+    let streaming = streaming(value);
+    let buffered = buffered(value);
+    println!(
+        "{:<16}  {:>8}  {:>12} B  {:>12} B  {:>7.1}x",
+        case,
+        count,
+        streaming,
+        buffered,
+        buffered as f64 / streaming.max(1) as f64
+    );
+}
+
+fn main() {
+    println!(
+        "{:<16}  {:>8}  {:>14}  {:>14}  {:>8}",
+        "input", "count", "streaming", "buffered", "ratio"
+    );
+
+    for count in [1_000, 10_000, 100_000] {
+        let map: BTreeMap<String, Account> = (0..count)
+            .map(|i| (format!("account_{}", i), account(i)))
+            .collect();
+        report("map of accounts", count, &map);
+
+        let list: Vec<Account> = (0..count).map(account).collect();
+        report("Vec<Account>", count, &list);
+
+        let table: Vec<Payment> = (0..count).map(payment).collect();
+        report("Vec<Payment>", count, &table);
+    }
+}
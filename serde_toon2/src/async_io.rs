@@ -0,0 +1,345 @@
+//! Asynchronous reading and writing with tokio.
+//!
+//! [`from_async_read`] and [`to_async_write`] mirror [`from_reader`](crate::from_reader)
+//! and [`to_writer`](crate::to_writer) for tokio's `AsyncRead` and `AsyncWrite`. They
+//! decode and encode a whole document at a time.
+//!
+//! Large tables can be streamed instead: [`AsyncTableReader`] decodes the rows of a
+//! document holding a single tabular array one at a time, and [`AsyncTableWriter`]
+//! writes one row at a time. Only the current row is held in memory.
+//!
+//! This module requires the `tokio` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use serde_toon2::async_io::{AsyncTableReader, AsyncTableWriter};
+//! use serde_toon2::{DecoderOptions, EncoderOptions};
+//!
+//! #[derive(Debug, Serialize, Deserialize, PartialEq)]
+//! struct User {
+//!     id: u32,
+//!     name: String,
+//! }
+//!
+//! # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//! let mut writer = AsyncTableWriter::new(Vec::new(), Some("users"), 2, EncoderOptions::default());
+//! writer.write_row(&User { id: 1, name: "Ada".into() }).await.unwrap();
+//! writer.write_row(&User { id: 2, name: "Bob".into() }).await.unwrap();
+//! let toon = writer.finish().await.unwrap();
+//! assert_eq!(toon, b"users[2]{id,name}:\n  1,Ada\n  2,Bob");
+//!
+//! let mut reader = AsyncTableReader::<_, User>::new(&toon[..], DecoderOptions::default())
+//!     .await
+//!     .unwrap();
+//! assert_eq!(reader.key(), Some("users"));
+//! while let Some(user) = reader.next_row().await.unwrap() {
+//!     println!("{:?}", user);
+//! }
+//! # });
+//! ```
+
+use crate::de::{TableHeader, from_str_with_options, parse_table_header, parse_table_row};
+use crate::error::{Error, ErrorKind, Result};
+use crate::options::{DecoderOptions, EncoderOptions};
+use crate::ser::{Serializer, to_value, to_vec_with_options};
+use crate::value::Value;
+use ::tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use serde::{Serialize, de};
+
+/// Deserializes TOON data from an async reader using default options.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::async_io::from_async_read;
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let data: serde_json::Value = from_async_read("count: 5".as_bytes()).await.unwrap();
+/// assert_eq!(data["count"], 5);
+/// # });
+/// ```
+pub async fn from_async_read<R, T>(reader: R) -> Result<T>
+where
+    R: AsyncRead + Unpin,
+    T: de::DeserializeOwned,
+{
+    from_async_read_with_options(reader, DecoderOptions::default()).await
+}
+
+/// Deserializes TOON data from an async reader with custom options.
+pub async fn from_async_read_with_options<R, T>(mut reader: R, options: DecoderOptions) -> Result<T>
+where
+    R: AsyncRead + Unpin,
+    T: de::DeserializeOwned,
+{
+    let mut buf = String::new();
+    reader.read_to_string(&mut buf).await?;
+    from_str_with_options(&buf, options)
+}
+
+/// Serializes a value as TOON to an async writer using default options.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::async_io::to_async_write;
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let mut out = Vec::new();
+/// to_async_write(&mut out, &vec!["x", "y"]).await.unwrap();
+/// assert_eq!(out, b"[2]: x,y");
+/// # });
+/// ```
+pub async fn to_async_write<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    to_async_write_with_options(writer, value, EncoderOptions::default()).await
+}
+
+/// Serializes a value as TOON to an async writer with custom options.
+///
+/// The document is encoded in memory, then written and flushed.
+pub async fn to_async_write_with_options<W, T>(
+    mut writer: W,
+    value: &T,
+    options: EncoderOptions,
+) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    let buf = to_vec_with_options(value, options)?;
+    writer.write_all(&buf).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Reads the rows of a document holding a single tabular array, one at a time.
+///
+/// The first line must be a tabular array header such as `rows[2]{id,name}:` or
+/// `[2]{id,name}:`, and every following non-blank line an indented row. Each row is
+/// decoded into `T`.
+pub struct AsyncTableReader<R, T> {
+    reader: BufReader<R>,
+    options: DecoderOptions,
+    header: TableHeader,
+    header_line: usize,
+    /// Number of lines read so far.
+    line: usize,
+    received: usize,
+    /// Whether a blank line was seen since the last row.
+    blank_line: bool,
+    buf: String,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<R: AsyncRead + Unpin, T: de::DeserializeOwned> AsyncTableReader<R, T> {
+    /// Reads the table header, skipping any blank lines before it.
+    pub async fn new(reader: R, options: DecoderOptions) -> Result<Self> {
+        let mut reader = BufReader::new(reader);
+        let mut buf = String::new();
+        let mut line = 0;
+
+        loop {
+            buf.clear();
+            if reader.read_line(&mut buf).await? == 0 {
+                return Err(Error::new(
+                    ErrorKind::InvalidHeader,
+                    "Expected a tabular array header, found end of input",
+                ));
+            }
+            line += 1;
+            if !buf.trim().is_empty() {
+                break;
+            }
+        }
+
+        let header = parse_table_header(&buf, line)?;
+        Ok(AsyncTableReader {
+            reader,
+            options,
+            header,
+            header_line: line,
+            line,
+            received: 0,
+            blank_line: false,
+            buf,
+            marker: PhantomData,
+        })
+    }
+
+    /// Returns the key of the array, or `None` for a root array.
+    pub fn key(&self) -> Option<&str> {
+        self.header.key.as_deref()
+    }
+
+    /// Returns the number of rows declared by the header.
+    pub fn declared_rows(&self) -> usize {
+        self.header.length
+    }
+
+    /// Returns the field names from the header.
+    pub fn fields(&self) -> &[String] {
+        &self.header.fields
+    }
+
+    /// Reads and decodes the next row, or returns `None` after the last one.
+    ///
+    /// At the end of the input, fails with a count mismatch if the number of rows
+    /// differs from the header, unless [`DecoderOptions::repair`] is set.
+    pub async fn next_row(&mut self) -> Result<Option<T>> {
+        loop {
+            self.buf.clear();
+            if self.reader.read_line(&mut self.buf).await? == 0 {
+                return self.check_count().map(|()| None);
+            }
+            self.line += 1;
+
+            let content = self.buf.trim_end_matches(['\n', '\r']);
+            if content.trim().is_empty() {
+                self.blank_line = true;
+                continue;
+            }
+
+            if !content.starts_with([' ', '\t']) {
+                return Err(
+                    Error::new(ErrorKind::InvalidSyntax, "Expected an indented table row")
+                        .with_location(self.line, 1),
+                );
+            }
+            if self.options.strict && self.blank_line {
+                return Err(Error::new(
+                    ErrorKind::InvalidSyntax,
+                    "Blank lines are not allowed inside arrays",
+                )
+                .with_location(self.line, 1));
+            }
+            self.blank_line = false;
+
+            if self.received == self.header.length && !self.options.repair {
+                return Err(self.count_mismatch(self.received + 1));
+            }
+
+            let row = parse_table_row(
+                content.trim_start(),
+                &self.header,
+                self.line,
+                self.options.repair,
+            )?;
+            self.received += 1;
+            return T::deserialize(row).map(Some);
+        }
+    }
+
+    fn check_count(&self) -> Result<()> {
+        if self.received == self.header.length || self.options.repair {
+            Ok(())
+        } else {
+            Err(self.count_mismatch(self.received))
+        }
+    }
+
+    fn count_mismatch(&self, received: usize) -> Error {
+        Error::new(
+            ErrorKind::CountMismatch,
+            format!(
+                "Expected {} rows, got {}",
+                self.header.length,
+                if received > self.header.length {
+                    format!("at least {}", received)
+                } else {
+                    received.to_string()
+                }
+            ),
+        )
+        .with_location(self.header_line, 1)
+    }
+}
+
+/// Writes a tabular array one row at a time.
+///
+/// The number of rows is part of the header, so it must be known up front. The field
+/// names are taken from the first row, and every row must serialize to an object with
+/// the same fields holding primitive values.
+pub struct AsyncTableWriter<W> {
+    writer: W,
+    serializer: Serializer<Vec<u8>>,
+    key: Option<String>,
+    len: usize,
+    fields: Option<Vec<String>>,
+    written: usize,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncTableWriter<W> {
+    /// Creates a writer for a table of `len` rows, under `key` or at the root.
+    pub fn new(writer: W, key: Option<&str>, len: usize, options: EncoderOptions) -> Self {
+        AsyncTableWriter {
+            writer,
+            serializer: Serializer::new(Vec::new(), options),
+            key: key.map(str::to_string),
+            len,
+            fields: None,
+            written: 0,
+        }
+    }
+
+    /// Writes the next row, preceded by the header for the first one.
+    pub async fn write_row<T: ?Sized + Serialize>(&mut self, row: &T) -> Result<()> {
+        if self.written == self.len {
+            return Err(Error::new(
+                ErrorKind::CountMismatch,
+                format!("Table declares {} rows", self.len),
+            ));
+        }
+
+        let Value::Object(row) = to_value(row)? else {
+            return Err(Error::custom("Table rows must serialize to objects"));
+        };
+        let fields = match &self.fields {
+            Some(fields) => fields,
+            None => {
+                let fields = self.fields.insert(row.keys().cloned().collect());
+                self.serializer
+                    .write_table_header(self.key.as_deref(), self.len, fields)?;
+                fields
+            }
+        };
+        self.serializer.write_table_row(&row, fields)?;
+        self.written += 1;
+        self.flush_buffer().await
+    }
+
+    /// Checks that every declared row was written, flushes the output and returns the
+    /// underlying writer.
+    pub async fn finish(mut self) -> Result<W> {
+        if self.written != self.len {
+            return Err(Error::new(
+                ErrorKind::CountMismatch,
+                format!("Table declares {} rows, got {}", self.len, self.written),
+            ));
+        }
+        if self.fields.is_none() {
+            self.serializer
+                .write_table_header(self.key.as_deref(), 0, &[])?;
+        }
+        self.serializer.end()?;
+        self.flush_buffer().await?;
+        self.writer.flush().await?;
+        Ok(self.writer)
+    }
+
+    async fn flush_buffer(&mut self) -> Result<()> {
+        let buf = self.serializer.get_mut();
+        self.writer.write_all(buf).await?;
+        buf.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+//! A value type that borrows its strings from the input.
+//!
+//! [`ValueRef`] has the same shape as [`Value`], but its strings and object keys are
+//! `Cow<'a, str>`s. Unquoted scalars and quoted strings without escapes point straight
+//! into the source text, so decoding with [`from_str_borrowed`](crate::from_str_borrowed)
+//! allocates little beyond the arrays and objects themselves. This suits read-only
+//! passes over large documents that look at a few fields of each record.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::borrow::Cow;
+//! use serde_toon2::from_str_borrowed;
+//!
+//! let input = "logs[2]{level,message}:\n  info,started\n  error,\"disk full\"";
+//! let doc = from_str_borrowed(input).unwrap();
+//!
+//! let errors: Vec<&str> = doc["logs"]
+//!     .as_array()
+//!     .unwrap()
+//!     .iter()
+//!     .filter(|log| log["level"] == "error")
+//!     .filter_map(|log| log["message"].as_str())
+//!     .collect();
+//! assert_eq!(errors, ["disk full"]);
+//!
+//! assert!(matches!(doc["logs"][0]["level"], serde_toon2::ValueRef::String(Cow::Borrowed(_))));
+//! ```
+
+use crate::value::{Map, Number, Value, parse_pointer_index, unescape_pointer_token};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::ops;
+use serde::Serialize;
+
+/// A TOON value whose strings may borrow from the input.
+///
+/// See the [module documentation](self) for details. Convert it to an owned [`Value`]
+/// with [`into_owned`](ValueRef::into_owned) or `Value::from`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ValueRef<'a> {
+    /// Represents a null value.
+    Null,
+    /// Represents a boolean value.
+    Bool(bool),
+    /// Represents a numeric value.
+    Number(Number),
+    /// Represents a string value, borrowed from the input where possible.
+    String(Cow<'a, str>),
+    /// Represents an array of values.
+    Array(Vec<ValueRef<'a>>),
+    /// Represents an object, with keys borrowed from the input where possible.
+    Object(Map<Cow<'a, str>, ValueRef<'a>>),
+}
+
+impl<'a> ValueRef<'a> {
+    /// Returns `true` if this value is null.
+    pub fn is_null(&self) -> bool {
+        matches!(self, ValueRef::Null)
+    }
+
+    /// If this value is a boolean, returns it. Otherwise returns `None`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ValueRef::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// If this value is a number, tries to convert it to `i64`. Otherwise returns `None`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            ValueRef::Number(n) => n.as_i64(),
+            _ => None,
+        }
+    }
+
+    /// If this value is a number, tries to convert it to `u64`. Otherwise returns `None`.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            ValueRef::Number(n) => n.as_u64(),
+            _ => None,
+        }
+    }
+
+    /// If this value is a number, converts it to `f64`. Otherwise returns `None`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ValueRef::Number(n) => Some(n.as_f64()),
+            _ => None,
+        }
+    }
+
+    /// If this value is a string, returns it. Otherwise returns `None`.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ValueRef::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// If this value is an array, returns a reference to it. Otherwise returns `None`.
+    pub fn as_array(&self) -> Option<&Vec<ValueRef<'a>>> {
+        match self {
+            ValueRef::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    /// If this value is an object, returns a reference to it. Otherwise returns `None`.
+    pub fn as_object(&self) -> Option<&Map<Cow<'a, str>, ValueRef<'a>>> {
+        match self {
+            ValueRef::Object(obj) => Some(obj),
+            _ => None,
+        }
+    }
+
+    /// Returns the value for an object key, or `None` if this is not an object or has
+    /// no such key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::from_str_borrowed;
+    ///
+    /// let doc = from_str_borrowed("name: Ada\nmanager: null").unwrap();
+    /// assert_eq!(doc.get("name").and_then(|name| name.as_str()), Some("Ada"));
+    /// assert!(doc.get("manager").unwrap().is_null());
+    /// assert_eq!(doc.get("missing"), None);
+    /// ```
+    pub fn get(&self, key: &str) -> Option<&ValueRef<'a>> {
+        self.as_object().and_then(|obj| obj.get(key))
+    }
+
+    /// Looks up a value by a JSON Pointer (RFC 6901), as [`Value::pointer`] does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::from_str_borrowed;
+    ///
+    /// let doc = from_str_borrowed("users[2]{id,name}:\n  1,Ada\n  2,Bob").unwrap();
+    /// assert_eq!(doc.pointer("/users/1/name").and_then(|name| name.as_str()), Some("Bob"));
+    /// assert_eq!(doc.pointer("/users/2"), None);
+    /// ```
+    pub fn pointer(&self, pointer: &str) -> Option<&ValueRef<'a>> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .map(unescape_pointer_token)
+            .try_fold(self, |target, token| match target {
+                ValueRef::Object(obj) => obj.get(&*token),
+                ValueRef::Array(arr) => parse_pointer_index(&token).and_then(|i| arr.get(i)),
+                _ => None,
+            })
+    }
+
+    /// Converts this value into an owned [`Value`], copying any borrowed strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::{from_str_borrowed, toon};
+    ///
+    /// let doc = from_str_borrowed("tags[2]: a,b").unwrap();
+    /// assert_eq!(doc.into_owned(), toon!({ "tags": ["a", "b"] }));
+    /// ```
+    pub fn into_owned(self) -> Value {
+        match self {
+            ValueRef::Null => Value::Null,
+            ValueRef::Bool(b) => Value::Bool(b),
+            ValueRef::Number(n) => Value::Number(n),
+            ValueRef::String(s) => Value::String(s.into_owned()),
+            ValueRef::Array(arr) => {
+                Value::Array(arr.into_iter().map(ValueRef::into_owned).collect())
+            }
+            ValueRef::Object(obj) => Value::Object(
+                obj.into_iter()
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'a> From<ValueRef<'a>> for Value {
+    fn from(value: ValueRef<'a>) -> Self {
+        value.into_owned()
+    }
+}
+
+static NULL: ValueRef<'static> = ValueRef::Null;
+
+/// Indexes into an object by key, returning [`ValueRef::Null`] if this is not an object
+/// or has no such key.
+impl<'a> ops::Index<&str> for ValueRef<'a> {
+    type Output = ValueRef<'a>;
+
+    fn index(&self, key: &str) -> &ValueRef<'a> {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+/// Indexes into an array, returning [`ValueRef::Null`] if this is not an array or the
+/// index is out of bounds.
+impl<'a> ops::Index<usize> for ValueRef<'a> {
+    type Output = ValueRef<'a>;
+
+    fn index(&self, index: usize) -> &ValueRef<'a> {
+        self.as_array()
+            .and_then(|arr| arr.get(index))
+            .unwrap_or(&NULL)
+    }
+}
+
+impl PartialEq<str> for ValueRef<'_> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == Some(other)
+    }
+}
+
+impl PartialEq<&str> for ValueRef<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == Some(*other)
+    }
+}
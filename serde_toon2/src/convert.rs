@@ -0,0 +1,370 @@
+//! Conversions between [`Value`] and the value types of other serde formats.
+//!
+//! Each conversion lives behind a feature of the same name: `json` for
+//! `serde_json::Value`, `yaml` for `serde_yaml::Value` and `toml` for `toml::Value`.
+//! Object key order is kept in both directions.
+//!
+//! Conversions that can always be done exactly are `From` impls. The others are
+//! `TryFrom` impls that fail with [`ErrorKind::Unrepresentable`] instead of changing the
+//! data, for example on YAML tags, TOML datetimes, `null` in TOML or non-finite floats
+//! in JSON. The error message names the offending value with a JSON pointer.
+
+use crate::error::{Error, ErrorKind};
+use crate::value::{Number, Value, push_pointer_token};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Runs `f` with `token` appended to `path`, then restores `path`.
+fn with_token<T>(
+    path: &mut String,
+    token: &str,
+    f: impl FnOnce(&mut String) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let len = path.len();
+    path.push('/');
+    push_pointer_token(path, token);
+    let result = f(path);
+    path.truncate(len);
+    result
+}
+
+fn unrepresentable(what: &str, path: &str) -> Error {
+    let message = if path.is_empty() {
+        String::from(what)
+    } else {
+        format!("{} at {}", what, path)
+    };
+    Error::new(ErrorKind::Unrepresentable, message)
+}
+
+#[cfg(feature = "json")]
+mod json {
+    use super::*;
+
+    /// Converts a JSON value. Every JSON value has an exact TOON equivalent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::Value;
+    ///
+    /// let json = serde_json::json!({ "name": "Ada", "tags": ["admin"] });
+    /// let value = Value::from(json);
+    /// assert_eq!(value.to_string(), "name: Ada\ntags[1]: admin");
+    /// ```
+    impl From<serde_json::Value> for Value {
+        fn from(value: serde_json::Value) -> Self {
+            match value {
+                serde_json::Value::Null => Value::Null,
+                serde_json::Value::Bool(b) => Value::Bool(b),
+                serde_json::Value::Number(n) => Value::Number(if let Some(i) = n.as_i64() {
+                    Number::I64(i)
+                } else if let Some(u) = n.as_u64() {
+                    Number::U64(u)
+                } else {
+                    Number::F64(n.as_f64().unwrap_or(f64::NAN))
+                }),
+                serde_json::Value::String(s) => Value::String(s),
+                serde_json::Value::Array(arr) => {
+                    Value::Array(arr.into_iter().map(Value::from).collect())
+                }
+                serde_json::Value::Object(obj) => Value::Object(
+                    obj.into_iter()
+                        .map(|(key, value)| (key, Value::from(value)))
+                        .collect(),
+                ),
+            }
+        }
+    }
+
+    /// Converts a value to JSON.
+    ///
+    /// Fails with [`ErrorKind::Unrepresentable`] on NaN and infinite numbers, which JSON
+    /// cannot hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::{Number, Value, toon};
+    ///
+    /// let json = serde_json::Value::try_from(toon!({ "id": 1 })).unwrap();
+    /// assert_eq!(json, serde_json::json!({ "id": 1 }));
+    ///
+    /// let err = serde_json::Value::try_from(toon!([1, (f64::NAN)])).unwrap_err();
+    /// assert_eq!(err.to_string(), "NaN cannot be represented in JSON at /1");
+    /// ```
+    impl TryFrom<Value> for serde_json::Value {
+        type Error = Error;
+
+        fn try_from(value: Value) -> Result<Self, Error> {
+            to_json(value, &mut String::new())
+        }
+    }
+
+    fn to_json(value: Value, path: &mut String) -> Result<serde_json::Value, Error> {
+        Ok(match value {
+            Value::Null => serde_json::Value::Null,
+            Value::Bool(b) => serde_json::Value::Bool(b),
+            Value::Number(Number::I64(i)) => serde_json::Value::from(i),
+            Value::Number(Number::U64(u)) => serde_json::Value::from(u),
+            Value::Number(Number::F64(f)) => match serde_json::Number::from_f64(f) {
+                Some(n) => serde_json::Value::Number(n),
+                None => {
+                    return Err(unrepresentable(
+                        &format!("{} cannot be represented in JSON", f),
+                        path,
+                    ));
+                }
+            },
+            Value::String(s) => serde_json::Value::String(s),
+            Value::Array(arr) => serde_json::Value::Array(
+                arr.into_iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        with_token(path, &format!("{}", i), |path| to_json(item, path))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            Value::Object(obj) => {
+                let mut map = serde_json::Map::with_capacity(obj.len());
+                for (key, value) in obj {
+                    let value = with_token(path, &key, |path| to_json(value, path))?;
+                    map.insert(key, value);
+                }
+                serde_json::Value::Object(map)
+            }
+        })
+    }
+}
+
+#[cfg(feature = "yaml")]
+mod yaml {
+    use super::*;
+    use crate::value::Map;
+
+    /// Converts a YAML value.
+    ///
+    /// Fails with [`ErrorKind::Unrepresentable`] on tagged values such as `!Thing x`,
+    /// and on mapping keys that are not strings, since TOON keys are always strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::Value;
+    ///
+    /// let yaml: serde_yaml::Value = serde_yaml::from_str("b: 1\na: [x, y]").unwrap();
+    /// let value = Value::try_from(yaml).unwrap();
+    /// assert_eq!(value.to_string(), "b: 1\na[2]: x,y");
+    ///
+    /// let yaml: serde_yaml::Value = serde_yaml::from_str("point: !Point [1, 2]").unwrap();
+    /// let err = Value::try_from(yaml).unwrap_err();
+    /// assert_eq!(err.to_string(), "YAML tag !Point cannot be represented in TOON at /point");
+    /// ```
+    impl TryFrom<serde_yaml::Value> for Value {
+        type Error = Error;
+
+        fn try_from(value: serde_yaml::Value) -> Result<Self, Error> {
+            from_yaml(value, &mut String::new())
+        }
+    }
+
+    fn from_yaml(value: serde_yaml::Value, path: &mut String) -> Result<Value, Error> {
+        Ok(match value {
+            serde_yaml::Value::Null => Value::Null,
+            serde_yaml::Value::Bool(b) => Value::Bool(b),
+            serde_yaml::Value::Number(n) => Value::Number(if let Some(i) = n.as_i64() {
+                Number::I64(i)
+            } else if let Some(u) = n.as_u64() {
+                Number::U64(u)
+            } else {
+                Number::F64(n.as_f64().unwrap_or(f64::NAN))
+            }),
+            serde_yaml::Value::String(s) => Value::String(s),
+            serde_yaml::Value::Sequence(seq) => Value::Array(
+                seq.into_iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        with_token(path, &format!("{}", i), |path| from_yaml(item, path))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            serde_yaml::Value::Mapping(mapping) => {
+                let mut obj = Map::default();
+                obj.reserve(mapping.len());
+                for (key, value) in mapping {
+                    let serde_yaml::Value::String(key) = key else {
+                        return Err(unrepresentable(
+                            &format!("YAML key {:?} is not a string", key),
+                            path,
+                        ));
+                    };
+                    let value = with_token(path, &key, |path| from_yaml(value, path))?;
+                    obj.insert(key, value);
+                }
+                Value::Object(obj)
+            }
+            serde_yaml::Value::Tagged(tagged) => {
+                return Err(unrepresentable(
+                    &format!("YAML tag {} cannot be represented in TOON", tagged.tag),
+                    path,
+                ));
+            }
+        })
+    }
+
+    /// Converts a value to YAML. Every TOON value has an exact YAML equivalent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::toon;
+    ///
+    /// let yaml = serde_yaml::Value::from(toon!({ "b": 1, "a": [true, null] }));
+    /// assert_eq!(serde_yaml::to_string(&yaml).unwrap(), "b: 1\na:\n- true\n- null\n");
+    /// ```
+    impl From<Value> for serde_yaml::Value {
+        fn from(value: Value) -> Self {
+            match value {
+                Value::Null => serde_yaml::Value::Null,
+                Value::Bool(b) => serde_yaml::Value::Bool(b),
+                Value::Number(Number::I64(i)) => serde_yaml::Value::from(i),
+                Value::Number(Number::U64(u)) => serde_yaml::Value::from(u),
+                Value::Number(Number::F64(f)) => serde_yaml::Value::from(f),
+                Value::String(s) => serde_yaml::Value::String(s),
+                Value::Array(arr) => {
+                    serde_yaml::Value::Sequence(arr.into_iter().map(Into::into).collect())
+                }
+                Value::Object(obj) => serde_yaml::Value::Mapping(
+                    obj.into_iter()
+                        .map(|(key, value)| (serde_yaml::Value::String(key), value.into()))
+                        .collect(),
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "toml")]
+mod toml {
+    use super::*;
+    use crate::value::Map;
+
+    /// Converts a TOML value.
+    ///
+    /// Fails with [`ErrorKind::Unrepresentable`] on datetimes, which TOON has no type
+    /// for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::Value;
+    ///
+    /// let table: toml::Table = toml::from_str("name = \"Ada\"\nport = 8080").unwrap();
+    /// let value = Value::try_from(toml::Value::Table(table)).unwrap();
+    /// assert_eq!(value.to_string(), "name: Ada\nport: 8080");
+    ///
+    /// let table: toml::Table = toml::from_str("at = 1979-05-27").unwrap();
+    /// let err = Value::try_from(toml::Value::Table(table)).unwrap_err();
+    /// assert_eq!(err.to_string(), "TOML datetime 1979-05-27 cannot be represented in TOON at /at");
+    /// ```
+    impl TryFrom<::toml::Value> for Value {
+        type Error = Error;
+
+        fn try_from(value: ::toml::Value) -> Result<Self, Error> {
+            from_toml(value, &mut String::new())
+        }
+    }
+
+    fn from_toml(value: ::toml::Value, path: &mut String) -> Result<Value, Error> {
+        Ok(match value {
+            ::toml::Value::String(s) => Value::String(s),
+            ::toml::Value::Integer(i) => Value::Number(Number::I64(i)),
+            ::toml::Value::Float(f) => Value::Number(Number::F64(f)),
+            ::toml::Value::Boolean(b) => Value::Bool(b),
+            ::toml::Value::Datetime(datetime) => {
+                return Err(unrepresentable(
+                    &format!("TOML datetime {} cannot be represented in TOON", datetime),
+                    path,
+                ));
+            }
+            ::toml::Value::Array(arr) => Value::Array(
+                arr.into_iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        with_token(path, &format!("{}", i), |path| from_toml(item, path))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            ::toml::Value::Table(table) => {
+                let mut obj = Map::default();
+                obj.reserve(table.len());
+                for (key, value) in table {
+                    let value = with_token(path, &key, |path| from_toml(value, path))?;
+                    obj.insert(key, value);
+                }
+                Value::Object(obj)
+            }
+        })
+    }
+
+    /// Converts a value to TOML.
+    ///
+    /// Fails with [`ErrorKind::Unrepresentable`] on `null`, which TOML has no type for,
+    /// and on integers above `i64::MAX`. Call it with `try_into`, as `toml::Value` has an
+    /// inherent `try_from` method that serializes instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::toon;
+    ///
+    /// let value: toml::Value = toon!({ "name": "Ada", "port": 8080 }).try_into().unwrap();
+    /// assert_eq!(value["port"].as_integer(), Some(8080));
+    ///
+    /// let err = TryInto::<toml::Value>::try_into(toon!({ "manager": null })).unwrap_err();
+    /// assert_eq!(err.to_string(), "null cannot be represented in TOML at /manager");
+    /// ```
+    impl TryFrom<Value> for ::toml::Value {
+        type Error = Error;
+
+        fn try_from(value: Value) -> Result<Self, Error> {
+            to_toml(value, &mut String::new())
+        }
+    }
+
+    fn to_toml(value: Value, path: &mut String) -> Result<::toml::Value, Error> {
+        Ok(match value {
+            Value::Null => return Err(unrepresentable("null cannot be represented in TOML", path)),
+            Value::Bool(b) => ::toml::Value::Boolean(b),
+            Value::Number(Number::I64(i)) => ::toml::Value::Integer(i),
+            Value::Number(Number::U64(u)) => match i64::try_from(u) {
+                Ok(i) => ::toml::Value::Integer(i),
+                Err(_) => {
+                    return Err(unrepresentable(
+                        &format!("{} is too large for a TOML integer", u),
+                        path,
+                    ));
+                }
+            },
+            Value::Number(Number::F64(f)) => ::toml::Value::Float(f),
+            Value::String(s) => ::toml::Value::String(s),
+            Value::Array(arr) => ::toml::Value::Array(
+                arr.into_iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        with_token(path, &format!("{}", i), |path| to_toml(item, path))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            Value::Object(obj) => {
+                let mut table = ::toml::Table::with_capacity(obj.len());
+                for (key, value) in obj {
+                    let value = with_token(path, &key, |path| to_toml(value, path))?;
+                    table.insert(key, value);
+                }
+                ::toml::Value::Table(table)
+            }
+        })
+    }
+}
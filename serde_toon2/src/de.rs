@@ -1,24 +1,242 @@
+use crate::borrowed::ValueRef;
 use crate::error::{Error, ErrorKind, Result};
-use crate::options::{DecoderOptions, Delimiter, PathExpansion};
+use crate::incremental::PendingArray;
+use crate::options::{DecoderOptions, Delimiter, Indent, PathExpansion};
+use crate::repair::{Repair, RepairKind};
+use crate::scan::{Structurals, find_colon, split_unquoted};
+use crate::spanned::{self, Node, NodeDeserializer, Source};
 use crate::value::{Map, Number, Value};
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cell::RefCell;
+use core::hash::Hash;
+#[cfg(feature = "std")]
+use core::marker::PhantomData;
 use serde::de;
 use serde::forward_to_deserialize_any;
-use std::io::Read;
+#[cfg(feature = "std")]
+use std::io::{BufRead, BufReader, Read};
 
-#[derive(Debug, Clone)]
-struct Line {
-    content: String,
+/// Content of the line that separates documents in a multi-document stream.
+pub(crate) const DOCUMENT_SEPARATOR: &str = "---";
+
+/// A non-blank input line, borrowed from the source text.
+///
+/// `depth` is absolute; nested parsers compare it against the depth they expect
+/// rather than re-basing the lines they are given.
+#[derive(Debug, Clone, Copy)]
+struct Line<'a> {
+    content: &'a str,
     depth: usize,
     line_number: usize,
-    original: String,
+}
+
+/// State shared by the parsing functions while decoding one document.
+struct Context<'o> {
+    options: &'o DecoderOptions,
+    /// Arrays holding fewer items than declared. When set, such arrays are recorded
+    /// here instead of failing with a count mismatch.
+    unfilled: Option<RefCell<Vec<PendingArray>>>,
+    /// Fixes applied to the input when `options.repair` is set.
+    repairs: RefCell<Vec<Repair>>,
+}
+
+impl<'o> Context<'o> {
+    fn new(options: &'o DecoderOptions) -> Self {
+        Context {
+            options,
+            unfilled: None,
+            repairs: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// A context that records unfilled arrays instead of failing on them.
+    fn partial(options: &'o DecoderOptions) -> Self {
+        Context {
+            unfilled: Some(RefCell::new(Vec::new())),
+            ..Context::new(options)
+        }
+    }
+
+    fn into_unfilled(self) -> Vec<PendingArray> {
+        self.unfilled.map(RefCell::into_inner).unwrap_or_default()
+    }
+
+    fn repair(&self, line: usize, kind: RepairKind) {
+        self.repairs.borrow_mut().push(Repair { line, kind });
+    }
+
+    fn check_count(
+        &self,
+        header: &ArrayHeader<'_>,
+        header_line: usize,
+        received: usize,
+        error: impl FnOnce() -> Error,
+    ) -> Result<()> {
+        if received == header.length {
+            return Ok(());
+        }
+        if received < header.length
+            && let Some(unfilled) = &self.unfilled
+        {
+            unfilled.borrow_mut().push(PendingArray {
+                line: header_line,
+                key: header.key.as_deref().map(String::from),
+                declared: header.length,
+                received,
+            });
+            return Ok(());
+        }
+        if self.options.repair {
+            self.repair(
+                header_line,
+                RepairKind::Count {
+                    declared: header.length,
+                    received,
+                },
+            );
+            return Ok(());
+        }
+        Err(error())
+    }
 }
 
 #[derive(Debug, Clone)]
-struct ArrayHeader {
-    key: Option<String>,
+struct ArrayHeader<'a> {
+    key: Option<Cow<'a, str>>,
     length: usize,
     delimiter: Delimiter,
-    fields: Option<Vec<String>>,
+    fields: Option<Vec<Cow<'a, str>>>,
+}
+
+/// A document tree the parser can build: owned as a [`Value`], borrowing its strings
+/// from the input as a [`ValueRef`], or keeping source locations for
+/// [`Spanned`](crate::Spanned) fields.
+pub(crate) trait Tree<'a>: Sized + Send {
+    type Key: Hash + Eq + Borrow<str> + Send + Sync;
+
+    fn key(key: Cow<'a, str>) -> Self::Key;
+    fn null() -> Self;
+    fn bool(b: bool) -> Self;
+    fn number(n: Number) -> Self;
+    fn string(s: Cow<'a, str>) -> Self;
+    fn array(items: Vec<Self>) -> Self;
+    fn object(obj: Map<Self::Key, Self>) -> Self;
+    /// Splits off an object's entries, for path expansion.
+    fn into_object(self) -> core::result::Result<Map<Self::Key, Self>, Self>;
+    /// Splits off an array's items, for path expansion.
+    fn into_array(self) -> core::result::Result<Vec<Self>, Self>;
+    fn as_object_mut(&mut self) -> Option<&mut Map<Self::Key, Self>>;
+    /// Records that the value was read from the input running from the start of `first`
+    /// to the end of `last`. Only trees that keep source locations need this.
+    fn set_span(&mut self, _first: &'a str, _last: &'a str) {}
+}
+
+impl<'a> Tree<'a> for Value {
+    type Key = String;
+
+    fn key(key: Cow<'a, str>) -> String {
+        key.into_owned()
+    }
+
+    fn null() -> Self {
+        Value::Null
+    }
+
+    fn bool(b: bool) -> Self {
+        Value::Bool(b)
+    }
+
+    fn number(n: Number) -> Self {
+        Value::Number(n)
+    }
+
+    fn string(s: Cow<'a, str>) -> Self {
+        Value::String(s.into_owned())
+    }
+
+    fn array(items: Vec<Self>) -> Self {
+        Value::Array(items)
+    }
+
+    fn object(obj: Map<String, Self>) -> Self {
+        Value::Object(obj)
+    }
+
+    fn into_object(self) -> core::result::Result<Map<String, Self>, Self> {
+        match self {
+            Value::Object(obj) => Ok(obj),
+            other => Err(other),
+        }
+    }
+
+    fn into_array(self) -> core::result::Result<Vec<Self>, Self> {
+        match self {
+            Value::Array(arr) => Ok(arr),
+            other => Err(other),
+        }
+    }
+
+    fn as_object_mut(&mut self) -> Option<&mut Map<String, Self>> {
+        Value::as_object_mut(self)
+    }
+}
+
+impl<'a> Tree<'a> for ValueRef<'a> {
+    type Key = Cow<'a, str>;
+
+    fn key(key: Cow<'a, str>) -> Cow<'a, str> {
+        key
+    }
+
+    fn null() -> Self {
+        ValueRef::Null
+    }
+
+    fn bool(b: bool) -> Self {
+        ValueRef::Bool(b)
+    }
+
+    fn number(n: Number) -> Self {
+        ValueRef::Number(n)
+    }
+
+    fn string(s: Cow<'a, str>) -> Self {
+        ValueRef::String(s)
+    }
+
+    fn array(items: Vec<Self>) -> Self {
+        ValueRef::Array(items)
+    }
+
+    fn object(obj: Map<Cow<'a, str>, Self>) -> Self {
+        ValueRef::Object(obj)
+    }
+
+    fn into_object(self) -> core::result::Result<Map<Cow<'a, str>, Self>, Self> {
+        match self {
+            ValueRef::Object(obj) => Ok(obj),
+            other => Err(other),
+        }
+    }
+
+    fn into_array(self) -> core::result::Result<Vec<Self>, Self> {
+        match self {
+            ValueRef::Array(arr) => Ok(arr),
+            other => Err(other),
+        }
+    }
+
+    fn as_object_mut(&mut self) -> Option<&mut Map<Cow<'a, str>, Self>> {
+        match self {
+            ValueRef::Object(obj) => Some(obj),
+            _ => None,
+        }
+    }
 }
 
 #[allow(dead_code)]
@@ -85,15 +303,105 @@ pub fn from_str_with_options<'a, T: de::Deserialize<'a>>(
     s: &'a str,
     options: DecoderOptions,
 ) -> Result<T> {
-    let lines = tokenize_lines(s, &options)?;
-    let value = parse_root(&lines, &options)?;
-    let value = if options.expand_paths != PathExpansion::Off {
-        expand_paths(value, &options)?
+    let ctx = Context::new(&options);
+    let root: Node = parse_document(s, &ctx)?;
+    T::deserialize(NodeDeserializer::new(root, &Source::new(s)))
+}
+
+/// Parses a TOON string into a [`ValueRef`] that borrows its strings from the input,
+/// using default options.
+///
+/// Unquoted strings, keys and quoted strings without escape sequences are not copied.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::from_str_borrowed;
+///
+/// let input = "id: 7\nname: Ada";
+/// let doc = from_str_borrowed(input).unwrap();
+///
+/// assert_eq!(doc["id"].as_u64(), Some(7));
+/// let name = doc["name"].as_str().unwrap();
+/// assert!(input.as_bytes().as_ptr_range().contains(&name.as_ptr()));
+/// ```
+pub fn from_str_borrowed(s: &str) -> Result<ValueRef<'_>> {
+    from_str_borrowed_with_options(s, DecoderOptions::default())
+}
+
+/// Parses a TOON string into a [`ValueRef`] with custom options.
+pub fn from_str_borrowed_with_options(s: &str, options: DecoderOptions) -> Result<ValueRef<'_>> {
+    let ctx = Context::new(&options);
+    parse_document(s, &ctx)
+}
+
+/// Parses a whole document into a tree, expanding dotted keys if the options ask for
+/// it.
+fn parse_document<'a, T: Tree<'a>>(s: &'a str, ctx: &Context) -> Result<T> {
+    let lines = tokenize_lines(s, ctx)?;
+    let value = parse_root(&lines, ctx)?;
+    if ctx.options.expand_paths != PathExpansion::Off {
+        expand_paths(value, ctx.options)
     } else {
-        value
-    };
+        Ok(value)
+    }
+}
 
-    T::deserialize(value)
+/// Parses a document that may be cut short, returning the arrays that hold fewer
+/// items than their headers declare instead of failing on them. Dotted keys are left
+/// for [`expand_paths`].
+pub(crate) fn parse_partial<'a>(
+    s: &'a str,
+    options: &DecoderOptions,
+) -> Result<(Node<'a>, Vec<PendingArray>)> {
+    let ctx = Context::partial(options);
+    let lines = tokenize_lines(s, &ctx)?;
+    let root = parse_root(&lines, &ctx)?;
+    Ok((root, ctx.into_unfilled()))
+}
+
+/// Like [`parse_partial`], for lines cut from a document at the start of a top-level
+/// field; `at_start` tells whether they open the document. Returns the fields as an
+/// object, or `None` if the whole document would not read these lines as fields: when
+/// it is a root array, or when an indented line that belongs to no field ends it.
+pub(crate) fn parse_partial_fields<'a>(
+    s: &'a str,
+    options: &DecoderOptions,
+    at_start: bool,
+) -> Result<Option<(Node<'a>, Vec<PendingArray>)>> {
+    let ctx = Context::partial(options);
+    let lines = tokenize_lines(s, &ctx)?;
+    if at_start && let Some(first) = lines.first() {
+        if first.depth != 0 {
+            return Err(
+                Error::new(ErrorKind::InvalidSyntax, "First line must be at depth 0")
+                    .with_location(first.line_number, 1),
+            );
+        }
+        if is_array_header(first.content)
+            && let Some(header) = try_parse_array_header(first.content)?
+            && header.key.is_none()
+        {
+            return Ok(None);
+        }
+    }
+
+    let mut cursor = 0;
+    let fields = parse_object(&lines, &mut cursor, 0, &ctx)?;
+    if cursor < lines.len() {
+        return Ok(None);
+    }
+    Ok(Some((fields, ctx.into_unfilled())))
+}
+
+/// Parses a document with `options.repair` set, returning the fixes applied.
+pub(crate) fn parse_repaired(s: &str, options: &DecoderOptions) -> Result<(Value, Vec<Repair>)> {
+    let ctx = Context::new(options);
+    let value: Value = parse_document(s, &ctx)?;
+
+    let mut repairs = ctx.repairs.into_inner();
+    repairs.sort_by_key(|repair| repair.line);
+    Ok((value, repairs))
 }
 
 /// Deserializes a TOON byte slice using default options.
@@ -119,11 +427,11 @@ pub fn from_slice<'a, T: de::Deserialize<'a>>(v: &'a [u8]) -> Result<T> {
 /// # Examples
 ///
 /// ```
-/// use serde_toon2::{from_slice_with_options, DecoderOptions};
+/// use serde_toon2::{from_slice_with_options, DecoderOptions, Indent};
 ///
 /// let toon = b"name: Bob\nage: 30";
 /// let opts = DecoderOptions {
-///     indent: 2,
+///     indent: Indent::Auto,
 ///     ..Default::default()
 /// };
 ///
@@ -134,7 +442,7 @@ pub fn from_slice_with_options<'a, T: de::Deserialize<'a>>(
     v: &'a [u8],
     options: DecoderOptions,
 ) -> Result<T> {
-    let s = std::str::from_utf8(v).map_err(|e| Error::custom(e.to_string()))?;
+    let s = core::str::from_utf8(v).map_err(|e| Error::custom(e.to_string()))?;
     from_str_with_options(s, options)
 }
 
@@ -153,6 +461,7 @@ pub fn from_slice_with_options<'a, T: de::Deserialize<'a>>(
 /// assert_eq!(data["count"], 5);
 /// assert_eq!(data["active"], true);
 /// ```
+#[cfg(feature = "std")]
 pub fn from_reader<R: Read, T: de::DeserializeOwned>(rdr: R) -> Result<T> {
     from_reader_with_options(rdr, DecoderOptions::default())
 }
@@ -176,6 +485,7 @@ pub fn from_reader<R: Read, T: de::DeserializeOwned>(rdr: R) -> Result<T> {
 /// let data: serde_json::Value = from_reader_with_options(cursor, opts).unwrap();
 /// assert_eq!(data["score"], 95);
 /// ```
+#[cfg(feature = "std")]
 pub fn from_reader_with_options<R: Read, T: de::DeserializeOwned>(
     mut rdr: R,
     options: DecoderOptions,
@@ -185,31 +495,163 @@ pub fn from_reader_with_options<R: Read, T: de::DeserializeOwned>(
     from_str_with_options(&buf, options)
 }
 
-fn tokenize_lines(input: &str, options: &DecoderOptions) -> Result<Vec<Line>> {
+/// An iterator over the documents of a multi-document TOON stream.
+///
+/// Documents are separated by lines holding only `---` at column 0, as written by
+/// [`Serializer::write_document`](crate::ser::Serializer::write_document). Each
+/// document is read and decoded on its own, so memory use is bounded by the largest
+/// document rather than the whole stream. Documents that contain only blank lines,
+/// such as one before a leading separator, are skipped.
+///
+/// Errors report line numbers within the whole stream. A document that fails to
+/// decode does not end the iteration; an I/O error does.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_toon2::StreamDeserializer;
+///
+/// #[derive(Deserialize)]
+/// struct Event {
+///     id: u32,
+///     kind: String,
+/// }
+///
+/// let log = "id: 1\nkind: start\n---\nid: 2\nkind: stop\n";
+/// let events: Vec<Event> = StreamDeserializer::new(log.as_bytes())
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+///
+/// assert_eq!(events.len(), 2);
+/// assert_eq!(events[1].kind, "stop");
+/// ```
+#[cfg(feature = "std")]
+pub struct StreamDeserializer<R, T> {
+    reader: BufReader<R>,
+    options: DecoderOptions,
+    /// Number of lines read so far.
+    line: usize,
+    done: bool,
+    marker: PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read, T: de::DeserializeOwned> StreamDeserializer<R, T> {
+    /// Creates a stream deserializer using default options.
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, DecoderOptions::default())
+    }
+
+    /// Creates a stream deserializer with custom options, applied to every document.
+    pub fn with_options(reader: R, options: DecoderOptions) -> Self {
+        StreamDeserializer {
+            reader: BufReader::new(reader),
+            options,
+            line: 0,
+            done: false,
+            marker: PhantomData,
+        }
+    }
+
+    /// Reads the next document's text, returning it with the line number it starts at.
+    fn read_document(&mut self) -> Result<Option<(String, usize)>> {
+        let mut document = String::new();
+        let mut start = self.line + 1;
+        let mut buf = String::new();
+
+        loop {
+            buf.clear();
+            if self.reader.read_line(&mut buf)? == 0 {
+                self.done = true;
+                break;
+            }
+            self.line += 1;
+
+            if buf.trim_end_matches(['\n', '\r']) == DOCUMENT_SEPARATOR {
+                if document.trim().is_empty() {
+                    document.clear();
+                    start = self.line + 1;
+                    continue;
+                }
+                break;
+            }
+            document.push_str(&buf);
+        }
+
+        if document.trim().is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some((document, start)))
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read, T: de::DeserializeOwned> Iterator for StreamDeserializer<R, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.done {
+            return None;
+        }
+
+        match self.read_document() {
+            Ok(Some((document, start))) => Some(
+                from_str_with_options(&document, self.options.clone())
+                    .map_err(|err| err.offset_line(start - 1)),
+            ),
+            Ok(None) => None,
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+fn tokenize_lines<'a>(input: &'a str, ctx: &Context) -> Result<Vec<Line<'a>>> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let options = ctx.options;
     let mut lines = Vec::new();
-    let indent_size = options.indent;
+    let (indent_char, indent_size) = indent_unit(input, options.indent);
+    // Indentation widths of the open nesting levels, used to place lines in repair mode
+    let mut levels: Vec<usize> = vec![0];
 
     for (line_number, line_str) in input.lines().enumerate() {
         let line_number = line_number + 1;
 
-        let leading_spaces = line_str.chars().take_while(|&c| c == ' ').count();
+        // `lines` strips the `\r` of a `\r\n`, so any left over stands alone
+        if let Some(pos) = line_str.find('\r') {
+            let column = line_str[..pos].chars().count() + 1;
+            return Err(Error::new(
+                ErrorKind::InvalidSyntax,
+                "Carriage return must be followed by a line feed",
+            )
+            .with_location(line_number, column));
+        }
+
+        let leading = line_str.bytes().take_while(|&b| b == indent_char).count();
 
-        if options.strict {
-            // Check for tabs in the leading whitespace (before any non-whitespace)
-            let leading_whitespace: String = line_str
+        if options.strict && !options.repair {
+            // Check for the other indentation character in the leading whitespace
+            let (other, message) = if indent_char == b' ' {
+                ('\t', "Tabs are not allowed in indentation")
+            } else {
+                (' ', "Spaces are not allowed in tab indentation")
+            };
+            let has_other = line_str
                 .chars()
                 .take_while(|&c| c.is_whitespace())
-                .collect();
-            if leading_whitespace.contains('\t') {
-                return Err(Error::new(
-                    ErrorKind::IndentationError,
-                    "Tabs are not allowed in indentation",
-                )
-                .with_location(line_number, 1));
+                .any(|c| c == other);
+            if has_other {
+                return Err(
+                    Error::new(ErrorKind::IndentationError, message).with_location(line_number, 1)
+                );
             }
 
             // Only check non-empty lines for indentation multiples
-            if !line_str.trim().is_empty() && leading_spaces % indent_size != 0 {
+            if !line_str.trim().is_empty() && leading % indent_size != 0 {
                 return Err(Error::new(
                     ErrorKind::IndentationError,
                     format!("Indentation must be a multiple of {}", indent_size),
@@ -218,31 +660,101 @@ fn tokenize_lines(input: &str, options: &DecoderOptions) -> Result<Vec<Line>> {
             }
         }
 
-        let depth = leading_spaces / indent_size;
-        // Convert character count to byte position for slicing
-        let byte_pos = line_str
-            .char_indices()
-            .nth(leading_spaces)
-            .map(|(pos, _)| pos)
-            .unwrap_or(line_str.len());
-        let content = line_str[byte_pos..].to_string();
-
-        if !content.is_empty() {
-            lines.push(Line {
-                content,
-                depth,
-                line_number,
-                original: line_str.to_string(),
-            });
+        // Spaces and tabs are single bytes, so the count is also the byte offset of the content
+        let content = &line_str[leading..];
+        if content.is_empty() {
+            continue;
         }
+
+        let depth = if options.repair {
+            // A line closes every level indented deeper than itself and opens a new
+            // level when it is indented deeper than the innermost one still open
+            while levels.len() > 1 && levels[levels.len() - 1] > leading {
+                levels.pop();
+            }
+            if levels[levels.len() - 1] < leading {
+                levels.push(leading);
+            }
+            let depth = levels.len() - 1;
+            if leading != depth * indent_size {
+                ctx.repair(
+                    line_number,
+                    RepairKind::Indentation {
+                        found: leading,
+                        expected: depth * indent_size,
+                    },
+                );
+            }
+            depth
+        } else {
+            leading / indent_size
+        };
+
+        lines.push(Line {
+            content,
+            depth,
+            line_number,
+        });
+    }
+
+    if options.repair {
+        strip_code_fences(&mut lines, ctx);
     }
 
     Ok(lines)
 }
 
-fn parse_root(lines: &[Line], options: &DecoderOptions) -> Result<Value> {
+/// Returns the byte used for indentation and how many of them make up one level.
+fn indent_unit(input: &str, indent: Indent) -> (u8, usize) {
+    match indent {
+        Indent::Spaces(width) => (b' ', width),
+        Indent::Tabs => (b'\t', 1),
+        Indent::Auto => indent_unit(input, detect_indent(input).unwrap_or(Indent::Spaces(2))),
+    }
+}
+
+/// Returns the indentation of the first indented line, which [`Indent::Auto`] uses for
+/// the whole document, or `None` if no line is indented.
+pub(crate) fn detect_indent(input: &str) -> Option<Indent> {
+    for line in input.lines() {
+        let leading = line.bytes().take_while(|&b| b == b' ').count();
+        if line.starts_with('\t') {
+            return Some(Indent::Tabs);
+        } else if leading > 0 && leading < line.len() {
+            return Some(Indent::Spaces(leading));
+        }
+    }
+    None
+}
+
+/// Removes a Markdown code fence around the document.
+fn strip_code_fences(lines: &mut Vec<Line>, ctx: &Context) {
+    let is_fence = |line: &Line| {
+        line.content
+            .trim_end()
+            .strip_prefix("```")
+            .is_some_and(|lang| {
+                lang.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            })
+    };
+
+    if lines.first().is_some_and(is_fence) {
+        let fence = lines.remove(0);
+        ctx.repair(fence.line_number, RepairKind::CodeFence);
+    }
+    if lines
+        .last()
+        .is_some_and(|line| line.content.trim_end() == "```")
+    {
+        let fence = lines.pop().unwrap();
+        ctx.repair(fence.line_number, RepairKind::CodeFence);
+    }
+}
+
+fn parse_root<'a, T: Tree<'a>>(lines: &[Line<'a>], ctx: &Context) -> Result<T> {
     if lines.is_empty() {
-        return Ok(Value::Object(Map::new()));
+        return Ok(T::object(Map::default()));
     }
 
     let first = &lines[0];
@@ -253,160 +765,96 @@ fn parse_root(lines: &[Line], options: &DecoderOptions) -> Result<Value> {
         );
     }
 
-    if is_array_header(&first.content)
-        && let Some(header) = try_parse_array_header(&first.content)?
+    if is_array_header(first.content)
+        && let Some(header) = try_parse_array_header(first.content)?
         && header.key.is_none()
     {
         let mut cursor = 0;
-        return parse_value(lines, &mut cursor, 0, Delimiter::Comma, options);
+        return parse_value(lines, &mut cursor, 0, Delimiter::Comma, ctx);
     }
 
     // Check if it's a single primitive value (no colon outside quotes)
     if lines.len() == 1 {
-        let has_unquoted_colon = has_colon_outside_quotes(&first.content);
+        let has_unquoted_colon = find_colon(first.content).is_some();
         if !has_unquoted_colon {
-            return parse_primitive(&first.content, first.line_number);
-        }
-    }
-
-    parse_object(lines, 0, options)
-}
-
-fn has_colon_outside_quotes(content: &str) -> bool {
-    let mut in_quotes = false;
-    let mut escape_next = false;
-
-    for ch in content.chars() {
-        if escape_next {
-            escape_next = false;
-            continue;
-        }
-
-        if ch == '\\' && in_quotes {
-            escape_next = true;
-            continue;
-        }
-
-        if ch == '"' {
-            in_quotes = !in_quotes;
-            continue;
-        }
-
-        if ch == ':' && !in_quotes {
-            return true;
-        }
-    }
-
-    false
-}
-
-fn find_colon_outside_quotes(content: &str) -> Option<usize> {
-    let mut in_quotes = false;
-    let mut escape_next = false;
-
-    for (i, ch) in content.chars().enumerate() {
-        if escape_next {
-            escape_next = false;
-            continue;
-        }
-
-        if ch == '\\' && in_quotes {
-            escape_next = true;
-            continue;
-        }
-
-        if ch == '"' {
-            in_quotes = !in_quotes;
-            continue;
-        }
-
-        if ch == ':' && !in_quotes {
-            return Some(i);
+            return parse_primitive(first.content, first.line_number);
         }
     }
 
-    None
+    let mut cursor = 0;
+    parse_object(lines, &mut cursor, 0, ctx)
 }
 
-fn parse_object(lines: &[Line], start_idx: usize, options: &DecoderOptions) -> Result<Value> {
-    let mut obj = Map::new();
-    let mut i = start_idx;
+/// Parses the fields of an object whose keys sit at `depth`, starting at `cursor`.
+fn parse_object<'a, T: Tree<'a>>(
+    lines: &[Line<'a>],
+    cursor: &mut usize,
+    depth: usize,
+    ctx: &Context,
+) -> Result<T> {
+    let start = *cursor;
+    let mut obj = Map::default();
 
-    while i < lines.len() {
-        let line = &lines[i];
+    while *cursor < lines.len() {
+        let line = &lines[*cursor];
 
-        if line.depth != 0 {
+        if line.depth != depth {
             break;
         }
 
-        if let Some(header) = try_parse_array_header(&line.content)? {
+        if let Some(header) = try_parse_array_header(line.content)? {
             let key = header.key.clone().unwrap_or_default();
-            let colon_pos = find_colon_outside_quotes(&line.content).unwrap();
+            let colon_pos = find_colon(line.content).unwrap();
             let value_part = line.content[colon_pos + 1..].trim_start();
 
             if !value_part.is_empty() {
-                let values = parse_inline_array(
-                    value_part,
-                    header.delimiter,
-                    header.length,
-                    line.line_number,
-                )?;
-                obj.insert(key, Value::Array(values));
+                let value = parse_inline_array(value_part, &header, line.line_number, ctx)?;
+                obj.insert(T::key(key), value);
             } else {
-                i += 1;
-                let value = parse_array_body(lines, &mut i, line.depth, header, options)?;
-                obj.insert(key, value);
+                *cursor += 1;
+                let value = parse_array_body(lines, cursor, line.depth, header, ctx)?;
+                obj.insert(T::key(key), value);
                 continue;
             }
         } else {
-            let (key, value_part) = parse_key_value_line(&line.content, line.line_number)?;
+            let (key, value_part) = parse_key_value_line(line.content, line.line_number)?;
 
             if value_part.is_empty() {
-                let nested_start = i + 1;
+                *cursor += 1;
                 let nested_depth = line.depth + 1;
 
-                let mut nested_end = nested_start;
-                while nested_end < lines.len() && lines[nested_end].depth >= nested_depth {
-                    nested_end += 1;
-                }
-
-                if nested_end > nested_start {
-                    let nested_lines: Vec<Line> = lines[nested_start..nested_end]
-                        .iter()
-                        .map(|l| Line {
-                            content: l.content.clone(),
-                            depth: l.depth - nested_depth,
-                            line_number: l.line_number,
-                            original: l.original.clone(),
-                        })
-                        .collect();
-
-                    let value = parse_object(&nested_lines, 0, options)?;
-                    obj.insert(key, value);
-                    i = nested_end;
-                    continue;
+                if *cursor < lines.len() && lines[*cursor].depth >= nested_depth {
+                    let value = parse_object(lines, cursor, nested_depth, ctx)?;
+                    // Lines indented deeper than the nested fields do not belong to any key
+                    while *cursor < lines.len() && lines[*cursor].depth >= nested_depth {
+                        *cursor += 1;
+                    }
+                    obj.insert(T::key(key), value);
                 } else {
-                    obj.insert(key, Value::Object(Map::new()));
+                    obj.insert(T::key(key), empty_object(line));
                 }
+                continue;
             } else {
                 let value = parse_primitive(value_part, line.line_number)?;
-                obj.insert(key, value);
+                obj.insert(T::key(key), value);
             }
         }
 
-        i += 1;
+        *cursor += 1;
     }
 
-    Ok(Value::Object(obj))
+    let mut value = T::object(obj);
+    span_lines(&mut value, lines, start, *cursor);
+    Ok(value)
 }
 
-fn parse_value(
-    lines: &[Line],
+fn parse_value<'a, T: Tree<'a>>(
+    lines: &[Line<'a>],
     cursor: &mut usize,
     parent_depth: usize,
     _parent_delimiter: Delimiter,
-    options: &DecoderOptions,
-) -> Result<Value> {
+    ctx: &Context,
+) -> Result<T> {
     if *cursor >= lines.len() {
         return Err(Error::new(
             ErrorKind::InvalidSyntax,
@@ -416,174 +864,355 @@ fn parse_value(
 
     let line = &lines[*cursor];
 
-    if let Some(header) = try_parse_array_header(&line.content)? {
-        let value_start = line.content.find(':').unwrap() + 1;
+    if let Some(header) = try_parse_array_header(line.content)? {
+        let value_start = find_colon(line.content).unwrap() + 1;
         let value_part = line.content[value_start..].trim_start();
 
         if !value_part.is_empty() {
-            let values = parse_inline_array(
-                value_part,
-                header.delimiter,
-                header.length,
-                line.line_number,
-            )?;
+            let value = parse_inline_array(value_part, &header, line.line_number, ctx)?;
             *cursor += 1;
-            Ok(Value::Array(values))
+            Ok(value)
         } else {
             *cursor += 1;
-            parse_array_body(lines, cursor, parent_depth, header, options)
+            parse_array_body(lines, cursor, parent_depth, header, ctx)
         }
-    } else if line.content.contains(':') {
-        let obj = parse_object_at_depth(lines, cursor, parent_depth, options)?;
-        Ok(Value::Object(obj))
+    } else if find_colon(line.content).is_some() {
+        parse_object_at_depth(lines, cursor, parent_depth, ctx)
     } else {
-        let value = parse_primitive(&line.content, line.line_number)?;
+        let value = parse_primitive(line.content, line.line_number)?;
         *cursor += 1;
         Ok(value)
     }
 }
 
-fn parse_object_at_depth(
-    lines: &[Line],
+fn parse_object_at_depth<'a, T: Tree<'a>>(
+    lines: &[Line<'a>],
     cursor: &mut usize,
     depth: usize,
-    options: &DecoderOptions,
-) -> Result<Map<String, Value>> {
-    let mut obj = Map::new();
+    ctx: &Context,
+) -> Result<T> {
+    let start = *cursor;
+    let mut obj = Map::default();
 
     while *cursor < lines.len() && lines[*cursor].depth == depth {
         let line = &lines[*cursor];
 
-        if let Some(header) = try_parse_array_header(&line.content)? {
+        if let Some(header) = try_parse_array_header(line.content)? {
             let key = header.key.clone().unwrap_or_default();
-            let colon_pos = find_colon_outside_quotes(&line.content).unwrap();
+            let colon_pos = find_colon(line.content).unwrap();
             let value_part = line.content[colon_pos + 1..].trim_start();
 
             if !value_part.is_empty() {
-                let values = parse_inline_array(
-                    value_part,
-                    header.delimiter,
-                    header.length,
-                    line.line_number,
-                )?;
-                obj.insert(key, Value::Array(values));
+                let value = parse_inline_array(value_part, &header, line.line_number, ctx)?;
+                obj.insert(T::key(key), value);
                 *cursor += 1;
             } else {
                 *cursor += 1;
-                let value = parse_array_body(lines, cursor, depth, header, options)?;
-                obj.insert(key, value);
+                let value = parse_array_body(lines, cursor, depth, header, ctx)?;
+                obj.insert(T::key(key), value);
             }
         } else {
-            let (key, value_part) = parse_key_value_line(&line.content, line.line_number)?;
+            let (key, value_part) = parse_key_value_line(line.content, line.line_number)?;
 
             if value_part.is_empty() {
                 *cursor += 1;
                 let nested_depth = depth + 1;
 
                 if *cursor < lines.len() && lines[*cursor].depth == nested_depth {
-                    let nested_obj = parse_object_at_depth(lines, cursor, nested_depth, options)?;
-                    obj.insert(key, Value::Object(nested_obj));
+                    let value = parse_object_at_depth(lines, cursor, nested_depth, ctx)?;
+                    obj.insert(T::key(key), value);
                 } else {
-                    obj.insert(key, Value::Object(Map::new()));
+                    obj.insert(T::key(key), empty_object(line));
                 }
             } else {
                 let value = parse_primitive(value_part, line.line_number)?;
-                obj.insert(key, value);
+                obj.insert(T::key(key), value);
                 *cursor += 1;
             }
         }
     }
 
-    Ok(obj)
+    let mut value = T::object(obj);
+    span_lines(&mut value, lines, start, *cursor);
+    Ok(value)
 }
 
-fn parse_array_body(
-    lines: &[Line],
+fn parse_array_body<'a, T: Tree<'a>>(
+    lines: &[Line<'a>],
     cursor: &mut usize,
     parent_depth: usize,
-    header: ArrayHeader,
-    options: &DecoderOptions,
-) -> Result<Value> {
+    header: ArrayHeader<'a>,
+    ctx: &Context,
+) -> Result<T> {
     let item_depth = parent_depth + 1;
+    // Callers move the cursor past the header line before parsing the body
+    let opening = lines[*cursor - 1];
+    let header_line = opening.line_number;
+    let start = *cursor;
 
-    if header.fields.is_some() {
-        parse_tabular_array(lines, cursor, item_depth, header, options)
+    let mut value: T = if header.fields.is_some() {
+        parse_tabular_array(lines, cursor, item_depth, header, header_line, ctx)?
+    } else {
+        parse_list_array(lines, cursor, item_depth, header, header_line, ctx)?
+    };
+    if *cursor > start {
+        span_lines(&mut value, lines, start, *cursor);
     } else {
-        parse_list_array(lines, cursor, item_depth, header, options)
+        let end = &opening.content[opening.content.len()..];
+        value.set_span(end, end);
+    }
+    Ok(value)
+}
+
+/// Records that `value` was read from `lines[start..end]`, unless that range is empty.
+fn span_lines<'a, T: Tree<'a>>(value: &mut T, lines: &[Line<'a>], start: usize, end: usize) {
+    if end > start {
+        value.set_span(lines[start].content, lines[end - 1].content);
     }
 }
 
-fn parse_tabular_array(
-    lines: &[Line],
+/// The value of a key with nothing nested under it, placed at the end of its line.
+fn empty_object<'a, T: Tree<'a>>(line: &Line<'a>) -> T {
+    let mut value = T::object(Map::default());
+    let end = &line.content[line.content.len()..];
+    value.set_span(end, end);
+    value
+}
+
+fn parse_tabular_array<'a, T: Tree<'a>>(
+    lines: &[Line<'a>],
     cursor: &mut usize,
     item_depth: usize,
-    header: ArrayHeader,
-    options: &DecoderOptions,
-) -> Result<Value> {
+    header: ArrayHeader<'a>,
+    header_line: usize,
+    ctx: &Context,
+) -> Result<T> {
     let fields = header.fields.as_ref().unwrap();
-    let mut rows = Vec::new();
+    let start = *cursor;
     let mut prev_line_number: Option<usize> = None;
+    let mut blank_line_error = None;
 
+    // Find the row boundaries first; rows are then independent of each other
     while *cursor < lines.len() && lines[*cursor].depth == item_depth {
         let line = &lines[*cursor];
 
-        if is_tabular_row(&line.content, header.delimiter) {
-            if options.strict
-                && let Some(prev) = prev_line_number
-                && line.line_number > prev + 1
-            {
-                return Err(Error::new(
+        if !is_tabular_row(line.content, header.delimiter) {
+            break;
+        }
+
+        if ctx.options.strict
+            && let Some(prev) = prev_line_number
+            && line.line_number > prev + 1
+        {
+            blank_line_error = Some(
+                Error::new(
                     ErrorKind::InvalidSyntax,
                     "Blank lines are not allowed inside arrays",
                 )
-                .with_location(line.line_number, 1));
-            }
-            prev_line_number = Some(line.line_number);
-            let values = parse_delimited_values(&line.content, header.delimiter, line.line_number)?;
-
-            if values.len() != fields.len() {
-                return Err(Error::new(
-                    ErrorKind::WidthMismatch,
-                    format!("Expected {} values, got {}", fields.len(), values.len()),
-                )
-                .with_location(line.line_number, 1));
-            }
-
-            let mut obj = Map::new();
-            for (i, field) in fields.iter().enumerate() {
-                let value = if i < values.len() {
-                    parse_primitive(&values[i], line.line_number)?
-                } else {
-                    Value::Null
-                };
-                obj.insert(field.clone(), value);
-            }
-            rows.push(Value::Object(obj));
-            *cursor += 1;
-        } else {
+                .with_location(line.line_number, 1),
+            );
             break;
         }
+        prev_line_number = Some(line.line_number);
+        *cursor += 1;
     }
 
-    if rows.len() != header.length {
-        return Err(Error::new(
+    // Errors in rows before a blank line take precedence over the blank line itself
+    let row_lines = &lines[start..*cursor];
+    let rows = parse_tabular_rows(row_lines, fields, header.delimiter, ctx.options.repair)?;
+    if let Some(err) = blank_line_error {
+        return Err(err);
+    }
+
+    let rows = rows
+        .into_iter()
+        .zip(row_lines)
+        .map(|((row, trimmed), line)| {
+            if trimmed {
+                ctx.repair(line.line_number, RepairKind::TrailingDelimiter);
+            }
+            row
+        })
+        .collect::<Vec<_>>();
+
+    ctx.check_count(&header, header_line, rows.len(), || {
+        Error::new(
             ErrorKind::CountMismatch,
             format!("Expected {} rows, got {}", header.length, rows.len()),
-        ));
+        )
+    })?;
+
+    Ok(T::array(rows))
+}
+
+#[cfg(not(feature = "parallel"))]
+fn parse_tabular_rows<'a, T: Tree<'a>>(
+    rows: &[Line<'a>],
+    fields: &[Cow<'a, str>],
+    delimiter: Delimiter,
+    repair: bool,
+) -> Result<Vec<(T, bool)>> {
+    rows.iter()
+        .map(|line| parse_tabular_row(line, fields, delimiter, repair))
+        .collect()
+}
+
+/// Tables with at least this many rows are parsed on the rayon pool.
+#[cfg(feature = "parallel")]
+const PARALLEL_MIN_ROWS: usize = 1024;
+
+/// Number of rows parsed by each rayon task.
+#[cfg(feature = "parallel")]
+const PARALLEL_CHUNK_ROWS: usize = 256;
+
+#[cfg(feature = "parallel")]
+fn parse_tabular_rows<'a, T: Tree<'a>>(
+    rows: &[Line<'a>],
+    fields: &[Cow<'a, str>],
+    delimiter: Delimiter,
+    repair: bool,
+) -> Result<Vec<(T, bool)>> {
+    use rayon::prelude::*;
+
+    if rows.len() < PARALLEL_MIN_ROWS {
+        return rows
+            .iter()
+            .map(|line| parse_tabular_row(line, fields, delimiter, repair))
+            .collect();
+    }
+
+    // Each chunk stops at its first error, so the first failing chunk in order
+    // holds the same error a sequential parse would report
+    let chunks: Vec<Result<Vec<(T, bool)>>> = rows
+        .par_chunks(PARALLEL_CHUNK_ROWS)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|line| parse_tabular_row(line, fields, delimiter, repair))
+                .collect()
+        })
+        .collect();
+
+    let mut values = Vec::with_capacity(rows.len());
+    for chunk in chunks {
+        values.extend(chunk?);
+    }
+    Ok(values)
+}
+
+/// Parses one row of a tabular array. The flag is set when a trailing delimiter was
+/// dropped in repair mode.
+fn parse_tabular_row<'a, T: Tree<'a>, F: Clone + Into<Cow<'a, str>>>(
+    line: &Line<'a>,
+    fields: &[F],
+    delimiter: Delimiter,
+    repair: bool,
+) -> Result<(T, bool)> {
+    let mut values = split_unquoted(line.content, delimiter.as_char() as u8);
+
+    let trimmed = repair && values.len() == fields.len() + 1 && has_trailing_delimiter(&values);
+    if trimmed {
+        values.pop();
+    }
+
+    if values.len() != fields.len() {
+        return Err(Error::new(
+            ErrorKind::WidthMismatch,
+            format!("Expected {} values, got {}", fields.len(), values.len()),
+        )
+        .with_location(line.line_number, 1));
     }
 
-    Ok(Value::Array(rows))
+    let mut obj = Map::default();
+    for (field, value) in fields.iter().zip(values) {
+        obj.insert(
+            T::key(field.clone().into()),
+            parse_primitive(value, line.line_number)?,
+        );
+    }
+    let mut row = T::object(obj);
+    row.set_span(line.content, line.content);
+    Ok((row, trimmed))
 }
 
-fn parse_list_array(
-    lines: &[Line],
+/// Returns whether the last of the split values is empty, i.e. the line ended in a
+/// delimiter.
+fn has_trailing_delimiter(values: &[&str]) -> bool {
+    values.len() > 1 && values.last().is_some_and(|value| value.is_empty())
+}
+
+/// Header of a tabular array that is read row by row.
+#[cfg(feature = "tokio")]
+pub(crate) struct TableHeader {
+    pub(crate) key: Option<String>,
+    pub(crate) length: usize,
+    pub(crate) delimiter: Delimiter,
+    pub(crate) fields: Vec<String>,
+}
+
+/// Parses a line holding only a tabular array header, such as `rows[2]{id,name}:`.
+#[cfg(feature = "tokio")]
+pub(crate) fn parse_table_header(content: &str, line_number: usize) -> Result<TableHeader> {
+    let content = content
+        .strip_prefix('\u{feff}')
+        .unwrap_or(content)
+        .trim_end();
+    let header = try_parse_array_header(content)
+        .map_err(|err| err.with_location(line_number, 1))?
+        .filter(|_| content.ends_with(':'));
+
+    match header {
+        Some(ArrayHeader {
+            key,
+            length,
+            delimiter,
+            fields: Some(fields),
+        }) => Ok(TableHeader {
+            key: key.map(Cow::into_owned),
+            length,
+            delimiter,
+            fields: fields.into_iter().map(Cow::into_owned).collect(),
+        }),
+        // An empty array has no field list
+        Some(header) if header.length == 0 => Ok(TableHeader {
+            key: header.key.map(Cow::into_owned),
+            length: 0,
+            delimiter: header.delimiter,
+            fields: Vec::new(),
+        }),
+        _ => Err(Error::new(
+            ErrorKind::InvalidHeader,
+            "Expected a tabular array header such as `rows[2]{id,name}:`",
+        )
+        .with_location(line_number, 1)),
+    }
+}
+
+/// Parses one row of a tabular array that is read row by row.
+#[cfg(feature = "tokio")]
+pub(crate) fn parse_table_row(
+    content: &str,
+    header: &TableHeader,
+    line_number: usize,
+    repair: bool,
+) -> Result<Value> {
+    let line = Line {
+        content,
+        depth: 1,
+        line_number,
+    };
+    parse_tabular_row(&line, &header.fields, header.delimiter, repair).map(|(row, _)| row)
+}
+
+fn parse_list_array<'a, T: Tree<'a>>(
+    lines: &[Line<'a>],
     cursor: &mut usize,
     item_depth: usize,
-    header: ArrayHeader,
-    options: &DecoderOptions,
-) -> Result<Value> {
+    header: ArrayHeader<'a>,
+    header_line: usize,
+    ctx: &Context,
+) -> Result<T> {
     let mut items = Vec::new();
-    let mut prev_line_number: Option<usize> = None;
 
     while *cursor < lines.len() && lines[*cursor].depth == item_depth {
         let line = &lines[*cursor];
@@ -592,9 +1221,10 @@ fn parse_list_array(
             break;
         }
 
-        if options.strict
-            && let Some(prev) = prev_line_number
-            && line.line_number > prev + 1
+        // Measure from the last line of the previous item, which may span several lines
+        if ctx.options.strict
+            && !items.is_empty()
+            && line.line_number > lines[*cursor - 1].line_number + 1
         {
             return Err(Error::new(
                 ErrorKind::InvalidSyntax,
@@ -602,7 +1232,6 @@ fn parse_list_array(
             )
             .with_location(line.line_number, 1));
         }
-        prev_line_number = Some(line.line_number);
 
         let item_content = if line.content.starts_with("- ") {
             &line.content[2..]
@@ -613,7 +1242,7 @@ fn parse_list_array(
         };
 
         if item_content.is_empty() {
-            items.push(Value::Object(Map::new()));
+            items.push(empty_object(line));
             *cursor += 1;
             continue;
         } else if let Some(inner_header) = try_parse_array_header(item_content)? {
@@ -624,48 +1253,39 @@ fn parse_list_array(
                 // Fall through to object parsing
             } else {
                 // Root array without key - parse as direct array value
-                let value_start = item_content.find(':').unwrap() + 1;
+                let value_start = find_colon(item_content).unwrap() + 1;
                 let value_part = item_content[value_start..].trim_start();
 
                 if !value_part.is_empty() {
-                    let values = parse_inline_array(
-                        value_part,
-                        inner_header.delimiter,
-                        inner_header.length,
-                        line.line_number,
-                    )?;
-                    items.push(Value::Array(values));
+                    let value =
+                        parse_inline_array(value_part, &inner_header, line.line_number, ctx)?;
+                    items.push(value);
                     *cursor += 1;
                     continue;
                 } else {
                     *cursor += 1;
-                    let value = parse_array_body(lines, cursor, item_depth, inner_header, options)?;
+                    let value = parse_array_body(lines, cursor, item_depth, inner_header, ctx)?;
                     items.push(value);
                     continue;
                 }
             }
         }
 
-        if item_content.contains(':') {
+        if find_colon(item_content).is_some() {
             let (key, value_part) = parse_key_value_line(item_content, line.line_number)?;
-            let mut obj = Map::new();
+            let mut obj = Map::default();
 
             if let Some(arr_header) = try_parse_array_header(item_content)? {
                 // Use the key from the array header, not the parsed key which includes brackets
                 let array_key = arr_header.key.clone().unwrap_or(key.clone());
                 if !value_part.is_empty() {
-                    let values = parse_inline_array(
-                        value_part,
-                        arr_header.delimiter,
-                        arr_header.length,
-                        line.line_number,
-                    )?;
-                    obj.insert(array_key, Value::Array(values));
+                    let value = parse_inline_array(value_part, &arr_header, line.line_number, ctx)?;
+                    obj.insert(T::key(array_key), value);
                     *cursor += 1;
                 } else {
                     *cursor += 1;
-                    let value = parse_array_body(lines, cursor, item_depth, arr_header, options)?;
-                    obj.insert(array_key, value);
+                    let value = parse_array_body(lines, cursor, item_depth, arr_header, ctx)?;
+                    obj.insert(T::key(array_key), value);
                 }
 
                 // Check for sibling fields at item_depth + 1
@@ -673,18 +1293,18 @@ fn parse_list_array(
                 while *cursor < lines.len() && lines[*cursor].depth == sibling_depth {
                     let sibling_line = &lines[*cursor];
                     let (sib_key, sib_value_part) =
-                        parse_key_value_line(&sibling_line.content, sibling_line.line_number)?;
+                        parse_key_value_line(sibling_line.content, sibling_line.line_number)?;
 
-                    if let Some(sib_header) = try_parse_array_header(&sibling_line.content)? {
+                    if let Some(sib_header) = try_parse_array_header(sibling_line.content)? {
                         let array_key = sib_header.key.clone().unwrap_or(sib_key.clone());
                         if !sib_value_part.is_empty() {
-                            let values = parse_inline_array(
+                            let value = parse_inline_array(
                                 sib_value_part,
-                                sib_header.delimiter,
-                                sib_header.length,
+                                &sib_header,
                                 sibling_line.line_number,
+                                ctx,
                             )?;
-                            obj.insert(array_key, Value::Array(values));
+                            obj.insert(T::key(array_key), value);
                             *cursor += 1;
                         } else {
                             *cursor += 1;
@@ -693,18 +1313,17 @@ fn parse_list_array(
                                 cursor,
                                 sibling_depth - 1,
                                 sib_header,
-                                options,
+                                ctx,
                             )?;
-                            obj.insert(array_key, value);
+                            obj.insert(T::key(array_key), value);
                         }
                     } else if sib_value_part.is_empty() {
                         *cursor += 1;
-                        let nested_obj =
-                            parse_object_at_depth(lines, cursor, sibling_depth + 1, options)?;
-                        obj.insert(sib_key, Value::Object(nested_obj));
+                        let value = parse_object_at_depth(lines, cursor, sibling_depth + 1, ctx)?;
+                        obj.insert(T::key(sib_key), value);
                     } else {
                         let value = parse_primitive(sib_value_part, sibling_line.line_number)?;
-                        obj.insert(sib_key, value);
+                        obj.insert(T::key(sib_key), value);
                         *cursor += 1;
                     }
                 }
@@ -713,28 +1332,28 @@ fn parse_list_array(
                 let nested_depth = item_depth + 2;
 
                 if *cursor < lines.len() && lines[*cursor].depth == nested_depth {
-                    let nested_obj = parse_object_at_depth(lines, cursor, nested_depth, options)?;
-                    obj.insert(key, Value::Object(nested_obj));
+                    let value = parse_object_at_depth(lines, cursor, nested_depth, ctx)?;
+                    obj.insert(T::key(key), value);
                 } else {
-                    obj.insert(key, Value::Object(Map::new()));
+                    obj.insert(T::key(key), empty_object(line));
                 }
 
                 let sibling_depth = item_depth + 1;
                 while *cursor < lines.len() && lines[*cursor].depth == sibling_depth {
                     let sibling_line = &lines[*cursor];
                     let (sib_key, sib_value_part) =
-                        parse_key_value_line(&sibling_line.content, sibling_line.line_number)?;
+                        parse_key_value_line(sibling_line.content, sibling_line.line_number)?;
 
-                    if let Some(sib_header) = try_parse_array_header(&sibling_line.content)? {
+                    if let Some(sib_header) = try_parse_array_header(sibling_line.content)? {
                         let array_key = sib_header.key.clone().unwrap_or(sib_key.clone());
                         if !sib_value_part.is_empty() {
-                            let values = parse_inline_array(
+                            let value = parse_inline_array(
                                 sib_value_part,
-                                sib_header.delimiter,
-                                sib_header.length,
+                                &sib_header,
                                 sibling_line.line_number,
+                                ctx,
                             )?;
-                            obj.insert(array_key, Value::Array(values));
+                            obj.insert(T::key(array_key), value);
                             *cursor += 1;
                         } else {
                             *cursor += 1;
@@ -743,42 +1362,41 @@ fn parse_list_array(
                                 cursor,
                                 sibling_depth - 1,
                                 sib_header,
-                                options,
+                                ctx,
                             )?;
-                            obj.insert(array_key, value);
+                            obj.insert(T::key(array_key), value);
                         }
                     } else if sib_value_part.is_empty() {
                         *cursor += 1;
-                        let nested_obj =
-                            parse_object_at_depth(lines, cursor, sibling_depth + 1, options)?;
-                        obj.insert(sib_key, Value::Object(nested_obj));
+                        let value = parse_object_at_depth(lines, cursor, sibling_depth + 1, ctx)?;
+                        obj.insert(T::key(sib_key), value);
                     } else {
                         let value = parse_primitive(sib_value_part, sibling_line.line_number)?;
-                        obj.insert(sib_key, value);
+                        obj.insert(T::key(sib_key), value);
                         *cursor += 1;
                     }
                 }
             } else {
                 let value = parse_primitive(value_part, line.line_number)?;
-                obj.insert(key, value);
+                obj.insert(T::key(key), value);
                 *cursor += 1;
 
                 let sibling_depth = item_depth + 1;
                 while *cursor < lines.len() && lines[*cursor].depth == sibling_depth {
                     let sibling_line = &lines[*cursor];
                     let (sib_key, sib_value_part) =
-                        parse_key_value_line(&sibling_line.content, sibling_line.line_number)?;
+                        parse_key_value_line(sibling_line.content, sibling_line.line_number)?;
 
-                    if let Some(sib_header) = try_parse_array_header(&sibling_line.content)? {
+                    if let Some(sib_header) = try_parse_array_header(sibling_line.content)? {
                         let array_key = sib_header.key.clone().unwrap_or(sib_key.clone());
                         if !sib_value_part.is_empty() {
-                            let values = parse_inline_array(
+                            let value = parse_inline_array(
                                 sib_value_part,
-                                sib_header.delimiter,
-                                sib_header.length,
+                                &sib_header,
                                 sibling_line.line_number,
+                                ctx,
                             )?;
-                            obj.insert(array_key, Value::Array(values));
+                            obj.insert(T::key(array_key), value);
                             *cursor += 1;
                         } else {
                             *cursor += 1;
@@ -787,24 +1405,25 @@ fn parse_list_array(
                                 cursor,
                                 sibling_depth - 1,
                                 sib_header,
-                                options,
+                                ctx,
                             )?;
-                            obj.insert(array_key, value);
+                            obj.insert(T::key(array_key), value);
                         }
                     } else if sib_value_part.is_empty() {
                         *cursor += 1;
-                        let nested_obj =
-                            parse_object_at_depth(lines, cursor, sibling_depth + 1, options)?;
-                        obj.insert(sib_key, Value::Object(nested_obj));
+                        let value = parse_object_at_depth(lines, cursor, sibling_depth + 1, ctx)?;
+                        obj.insert(T::key(sib_key), value);
                     } else {
                         let value = parse_primitive(sib_value_part, sibling_line.line_number)?;
-                        obj.insert(sib_key, value);
+                        obj.insert(T::key(sib_key), value);
                         *cursor += 1;
                     }
                 }
             }
 
-            items.push(Value::Object(obj));
+            let mut item = T::object(obj);
+            item.set_span(item_content, lines[*cursor - 1].content);
+            items.push(item);
         } else {
             let value = parse_primitive(item_content, line.line_number)?;
             items.push(value);
@@ -812,101 +1431,49 @@ fn parse_list_array(
         }
     }
 
-    if items.len() != header.length {
-        return Err(Error::new(
+    ctx.check_count(&header, header_line, items.len(), || {
+        Error::new(
             ErrorKind::CountMismatch,
             format!("Expected {} items, got {}", header.length, items.len()),
-        ));
-    }
+        )
+    })?;
 
-    Ok(Value::Array(items))
+    Ok(T::array(items))
 }
 
-fn parse_inline_array(
-    content: &str,
-    delimiter: Delimiter,
-    expected_count: usize,
+fn parse_inline_array<'a, T: Tree<'a>>(
+    content: &'a str,
+    header: &ArrayHeader<'a>,
     line_number: usize,
-) -> Result<Vec<Value>> {
-    let values_str = parse_delimited_values(content, delimiter, line_number)?;
+    ctx: &Context,
+) -> Result<T> {
+    let mut values_str = split_unquoted(content, header.delimiter.as_char() as u8);
+    if ctx.options.repair && values_str.len() > header.length && has_trailing_delimiter(&values_str)
+    {
+        values_str.pop();
+        ctx.repair(line_number, RepairKind::TrailingDelimiter);
+    }
     let mut values = Vec::new();
 
     for val_str in values_str {
-        values.push(parse_primitive(&val_str, line_number)?);
+        values.push(parse_primitive(val_str, line_number)?);
     }
 
-    if values.len() != expected_count {
-        return Err(Error::new(
+    ctx.check_count(header, line_number, values.len(), || {
+        Error::new(
             ErrorKind::CountMismatch,
-            format!("Expected {} values, got {}", expected_count, values.len()),
+            format!("Expected {} values, got {}", header.length, values.len()),
         )
-        .with_location(line_number, 1));
-    }
-
-    Ok(values)
-}
-
-fn parse_delimited_values(
-    content: &str,
-    delimiter: Delimiter,
-    _line_number: usize,
-) -> Result<Vec<String>> {
-    let delim_char = delimiter.as_char();
-    let mut values = Vec::new();
-    let mut current = String::new();
-    let mut in_quotes = false;
-    let mut escape_next = false;
-    let chars = content.chars().peekable();
-
-    for ch in chars {
-        if escape_next {
-            current.push(ch);
-            escape_next = false;
-        } else if ch == '\\' && in_quotes {
-            escape_next = true;
-            current.push(ch);
-        } else if ch == '"' {
-            in_quotes = !in_quotes;
-            current.push(ch);
-        } else if ch == delim_char && !in_quotes {
-            values.push(current.trim().to_string());
-            current.clear();
-        } else {
-            current.push(ch);
-        }
-    }
-
-    values.push(current.trim().to_string());
+        .with_location(line_number, 1)
+    })?;
 
-    Ok(values)
+    let mut array = T::array(values);
+    array.set_span(content, content);
+    Ok(array)
 }
 
-fn parse_key_value_line(content: &str, line_number: usize) -> Result<(String, &str)> {
-    let mut in_quotes = false;
-    let mut escape_next = false;
-    let mut colon_pos = None;
-
-    for (i, ch) in content.chars().enumerate() {
-        if escape_next {
-            escape_next = false;
-            continue;
-        }
-
-        if ch == '\\' && in_quotes {
-            escape_next = true;
-            continue;
-        }
-
-        if ch == '"' {
-            in_quotes = !in_quotes;
-            continue;
-        }
-
-        if ch == ':' && !in_quotes {
-            colon_pos = Some(i);
-            break;
-        }
-    }
+fn parse_key_value_line(content: &str, line_number: usize) -> Result<(Cow<'_, str>, &str)> {
+    let colon_pos = find_colon(content);
 
     let colon_pos = colon_pos.ok_or_else(|| {
         Error::new(ErrorKind::MissingColon, "Missing colon after key").with_location(line_number, 1)
@@ -923,12 +1490,12 @@ fn parse_key_value_line(content: &str, line_number: usize) -> Result<(String, &s
         let unescaped = unescape_string(&key_part[1..key_part.len() - 1], line_number)?;
         // Mark quoted keys with a null byte prefix so path expansion can skip them
         if unescaped.contains('.') {
-            format!("\x00{}", unescaped)
+            Cow::Owned(format!("\x00{}", unescaped))
         } else {
             unescaped
         }
     } else {
-        key_part.to_string()
+        Cow::Borrowed(key_part)
     };
 
     let value_part = value_part.trim_start();
@@ -940,56 +1507,27 @@ fn is_array_header(content: &str) -> bool {
     content.contains('[') && content.contains(']') && content.contains(':')
 }
 
-fn try_parse_array_header(content: &str) -> Result<Option<ArrayHeader>> {
+fn try_parse_array_header(content: &str) -> Result<Option<ArrayHeader<'_>>> {
     if !content.contains('[') || !content.contains(']') || !content.contains(':') {
         return Ok(None);
     }
 
-    let mut in_quotes = false;
-    let mut escape_next = false;
-    let mut bracket_start = None;
-    let mut bracket_end = None;
-
-    for (i, ch) in content.chars().enumerate() {
-        if escape_next {
-            escape_next = false;
-            continue;
-        }
-
-        if ch == '\\' && in_quotes {
-            escape_next = true;
-            continue;
-        }
-
-        if ch == '"' {
-            in_quotes = !in_quotes;
-            continue;
-        }
-
-        if !in_quotes {
-            if ch == '[' && bracket_start.is_none() {
-                bracket_start = Some(i);
-            } else if ch == ']' && bracket_start.is_some() && bracket_end.is_none() {
-                bracket_end = Some(i);
-            }
-        }
-    }
-
-    if bracket_start.is_none() || bracket_end.is_none() {
+    let mut structurals = Structurals::new(content);
+    let Some((bracket_start, _)) = structurals.find(|&(_, byte)| byte == b'[') else {
         return Ok(None);
-    }
-
-    let bracket_start = bracket_start.unwrap();
-    let bracket_end = bracket_end.unwrap();
+    };
+    let Some((bracket_end, _)) = structurals.find(|&(_, byte)| byte == b']') else {
+        return Ok(None);
+    };
 
     let key_part = if bracket_start > 0 {
         let key_str = content[..bracket_start].trim();
         if key_str.is_empty() {
             None
         } else if key_str.starts_with('"') && key_str.ends_with('"') {
-            Some(key_str[1..key_str.len() - 1].to_string())
+            Some(Cow::Borrowed(&key_str[1..key_str.len() - 1]))
         } else {
-            Some(key_str.to_string())
+            Some(Cow::Borrowed(key_str))
         }
     } else {
         None
@@ -1015,42 +1553,20 @@ fn try_parse_array_header(content: &str) -> Result<Option<ArrayHeader>> {
     let after_bracket = &content[bracket_end + 1..].trim_start();
 
     let fields = if after_bracket.starts_with('{') {
-        let mut close_brace_pos = None;
-        let mut in_quotes = false;
-        let mut escape_next = false;
-
-        for (i, ch) in after_bracket.chars().enumerate().skip(1) {
-            if escape_next {
-                escape_next = false;
-                continue;
-            }
-
-            if ch == '\\' && in_quotes {
-                escape_next = true;
-                continue;
-            }
-
-            if ch == '"' {
-                in_quotes = !in_quotes;
-                continue;
-            }
-
-            if ch == '}' && !in_quotes {
-                close_brace_pos = Some(i);
-                break;
-            }
-        }
+        let close_brace_pos = Structurals::new(after_bracket)
+            .find(|&(i, byte)| i > 0 && byte == b'}')
+            .map(|(i, _)| i);
 
         if let Some(close_brace) = close_brace_pos {
             let fields_content = &after_bracket[1..close_brace];
-            let field_strings = parse_delimited_values(fields_content, delimiter, 0)?;
+            let field_strings = split_unquoted(fields_content, delimiter.as_char() as u8);
             let mut fields = Vec::new();
 
             for field_str in field_strings {
                 let field = if field_str.starts_with('"') && field_str.ends_with('"') {
                     unescape_string(&field_str[1..field_str.len() - 1], 0)?
                 } else {
-                    field_str
+                    Cow::Borrowed(field_str)
                 };
                 fields.push(field);
             }
@@ -1072,63 +1588,24 @@ fn try_parse_array_header(content: &str) -> Result<Option<ArrayHeader>> {
 }
 
 fn is_tabular_row(content: &str, delimiter: Delimiter) -> bool {
-    let delim_char = delimiter.as_char();
-    let mut in_quotes = false;
-    let mut escape_next = false;
-    let mut has_delimiter = false;
-    let mut has_colon = false;
-    let mut first_delim_pos = None;
-    let mut first_colon_pos = None;
-
-    for (i, ch) in content.chars().enumerate() {
-        if escape_next {
-            escape_next = false;
-            continue;
-        }
-
-        if ch == '\\' && in_quotes {
-            escape_next = true;
-            continue;
-        }
-
-        if ch == '"' {
-            in_quotes = !in_quotes;
-            continue;
-        }
-
-        if !in_quotes {
-            if ch == delim_char && !has_delimiter {
-                has_delimiter = true;
-                first_delim_pos = Some(i);
-            }
-            if ch == ':' && !has_colon {
-                has_colon = true;
-                first_colon_pos = Some(i);
-            }
-        }
-    }
-
-    if !has_colon {
-        return true;
-    }
-
-    if !has_delimiter {
-        return false;
-    }
-
-    match (first_delim_pos, first_colon_pos) {
-        (Some(d), Some(c)) => d < c,
-        (Some(_), None) => true,
-        (None, Some(_)) => false,
-        (None, None) => true,
-    }
+    // A row is tabular unless a colon appears before the first delimiter
+    let delim = delimiter.as_char() as u8;
+    !matches!(
+        Structurals::new(content).find(|&(_, byte)| byte == delim || byte == b':'),
+        Some((_, b':'))
+    )
 }
 
-fn parse_primitive(content: &str, line_number: usize) -> Result<Value> {
+fn parse_primitive<'a, T: Tree<'a>>(content: &'a str, line_number: usize) -> Result<T> {
     let trimmed = content.trim();
+    let mut value: T = parse_scalar(trimmed, line_number)?;
+    value.set_span(trimmed, trimmed);
+    Ok(value)
+}
 
+fn parse_scalar<'a, T: Tree<'a>>(trimmed: &'a str, line_number: usize) -> Result<T> {
     if trimmed.is_empty() {
-        return Ok(Value::String(String::new()));
+        return Ok(T::string(Cow::Borrowed("")));
     }
 
     if trimmed.starts_with('"') {
@@ -1141,21 +1618,21 @@ fn parse_primitive(content: &str, line_number: usize) -> Result<Value> {
         }
         let inner = &trimmed[1..trimmed.len() - 1];
         let unescaped = unescape_string(inner, line_number)?;
-        return Ok(Value::String(unescaped));
+        return Ok(T::string(unescaped));
     }
 
     match trimmed {
-        "true" => return Ok(Value::Bool(true)),
-        "false" => return Ok(Value::Bool(false)),
-        "null" => return Ok(Value::Null),
+        "true" => return Ok(T::bool(true)),
+        "false" => return Ok(T::bool(false)),
+        "null" => return Ok(T::null()),
         _ => {}
     }
 
     if let Ok(num) = parse_number(trimmed) {
-        return Ok(Value::Number(num));
+        return Ok(T::number(num));
     }
 
-    Ok(Value::String(trimmed.to_string()))
+    Ok(T::string(Cow::Borrowed(trimmed)))
 }
 
 fn parse_number(s: &str) -> Result<Number> {
@@ -1186,8 +1663,12 @@ fn parse_number(s: &str) -> Result<Number> {
     }
 }
 
-fn unescape_string(s: &str, line_number: usize) -> Result<String> {
-    let mut result = String::new();
+fn unescape_string(s: &str, line_number: usize) -> Result<Cow<'_, str>> {
+    if !s.contains('\\') {
+        return Ok(Cow::Borrowed(s));
+    }
+
+    let mut result = String::with_capacity(s.len());
     let mut chars = s.chars();
 
     while let Some(ch) = chars.next() {
@@ -1218,74 +1699,96 @@ fn unescape_string(s: &str, line_number: usize) -> Result<String> {
         }
     }
 
-    Ok(result)
+    Ok(Cow::Owned(result))
 }
 
-fn expand_paths(value: Value, options: &DecoderOptions) -> Result<Value> {
-    match value {
-        Value::Object(obj) => {
-            let mut result = Map::new();
+/// Expands the dotted keys of every object in `value`.
+pub(crate) fn expand_paths<'a, T: Tree<'a>>(value: T, options: &DecoderOptions) -> Result<T> {
+    let obj = match value.into_object() {
+        Ok(obj) => obj,
+        Err(value) => {
+            return match value.into_array() {
+                Ok(arr) => Ok(T::array(
+                    arr.into_iter()
+                        .map(|v| expand_paths(v, options))
+                        .collect::<Result<Vec<_>>>()?,
+                )),
+                Err(other) => Ok(other),
+            };
+        }
+    };
 
-            for (key, val) in obj {
-                let expanded_val = expand_paths(val, options)?;
+    let mut result = Map::default();
+    expand_fields(&mut result, obj, options)?;
+    Ok(T::object(result))
+}
 
-                // Check if key was originally quoted (marked with \x00 prefix)
-                let (is_quoted, clean_key) = if let Some(stripped) = key.strip_prefix('\x00') {
-                    (true, stripped.to_string())
-                } else {
-                    (false, key.clone())
-                };
+/// Expands the dotted keys of `fields` and their values into `result`, merging them
+/// with the fields already there.
+pub(crate) fn expand_fields<'a, T: Tree<'a>>(
+    result: &mut Map<T::Key, T>,
+    fields: Map<T::Key, T>,
+    options: &DecoderOptions,
+) -> Result<()> {
+    for (key, val) in fields {
+        let mut expanded_val = expand_paths(val, options)?;
 
-                if !is_quoted
-                    && options.expand_paths == PathExpansion::Safe
-                    && clean_key.contains('.')
-                {
-                    let segments: Vec<&str> = clean_key.split('.').collect();
-                    let all_safe = segments.iter().all(|seg| {
-                        !seg.is_empty()
-                            && seg
-                                .chars()
-                                .next()
-                                .map(|c| c.is_ascii_alphabetic() || c == '_')
-                                .unwrap_or(false)
-                            && seg.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
-                    });
-
-                    if all_safe && segments.len() > 1 {
-                        merge_path(&mut result, &segments, expanded_val, options)?;
-                        continue;
-                    }
-                }
+        // Check if key was originally quoted (marked with \x00 prefix)
+        let (is_quoted, clean_key) = if let Some(stripped) = key.borrow().strip_prefix('\x00') {
+            (true, stripped.to_string())
+        } else {
+            (false, key.borrow().to_string())
+        };
 
-                let final_key = if is_quoted { clean_key } else { key };
-                if let Some(existing) = result.get(&final_key)
-                    && options.strict
-                    && (!matches!(existing, Value::Object(_))
-                        || !matches!(&expanded_val, Value::Object(_)))
-                {
-                    return Err(Error::new(
-                        ErrorKind::ExpansionConflict,
-                        format!("Path expansion conflict at '{}'", final_key),
-                    ));
-                }
-                result.insert(final_key, expanded_val);
-            }
+        if !is_quoted && let Some(segments) = expansion_segments(&clean_key, options) {
+            merge_path(result, &segments, expanded_val, options)?;
+            continue;
+        }
 
-            Ok(Value::Object(result))
+        let final_key = if is_quoted {
+            T::key(Cow::Owned(clean_key))
+        } else {
+            key
+        };
+        if let Some(existing) = result.get_mut(final_key.borrow())
+            && options.strict
+            && (existing.as_object_mut().is_none() || expanded_val.as_object_mut().is_none())
+        {
+            return Err(Error::new(
+                ErrorKind::ExpansionConflict,
+                format!("Path expansion conflict at '{}'", final_key.borrow()),
+            ));
         }
-        Value::Array(arr) => Ok(Value::Array(
-            arr.into_iter()
-                .map(|v| expand_paths(v, options))
-                .collect::<Result<Vec<_>>>()?,
-        )),
-        other => Ok(other),
+        result.insert(final_key, expanded_val);
     }
+    Ok(())
 }
 
-fn merge_path(
-    obj: &mut Map<String, Value>,
+/// Returns the path segments an unquoted key expands to, or `None` if it stays whole.
+pub(crate) fn expansion_segments<'k>(
+    key: &'k str,
+    options: &DecoderOptions,
+) -> Option<Vec<&'k str>> {
+    if options.expand_paths != PathExpansion::Safe || !key.contains('.') {
+        return None;
+    }
+    let segments: Vec<&str> = key.split('.').collect();
+    let all_safe = segments.iter().all(|seg| {
+        !seg.is_empty()
+            && seg
+                .chars()
+                .next()
+                .map(|c| c.is_ascii_alphabetic() || c == '_')
+                .unwrap_or(false)
+            && seg.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    });
+    (all_safe && segments.len() > 1).then_some(segments)
+}
+
+fn merge_path<'a, T: Tree<'a>>(
+    obj: &mut Map<T::Key, T>,
     segments: &[&str],
-    value: Value,
+    mut value: T,
     options: &DecoderOptions,
 ) -> Result<()> {
     if segments.is_empty() {
@@ -1293,48 +1796,72 @@ fn merge_path(
     }
 
     if segments.len() == 1 {
-        let key = segments[0].to_string();
-        if let Some(existing) = obj.get(&key)
+        let key = segments[0];
+        if let Some(existing) = obj.get_mut(key)
             && options.strict
-            && (!matches!(existing, Value::Object(_)) || !matches!(&value, Value::Object(_)))
+            && (existing.as_object_mut().is_none() || value.as_object_mut().is_none())
         {
             return Err(Error::new(
                 ErrorKind::ExpansionConflict,
                 format!("Path expansion conflict at '{}'", key),
             ));
         }
-        obj.insert(key, value);
+        obj.insert(T::key(Cow::Owned(key.to_string())), value);
         return Ok(());
     }
 
-    let first = segments[0].to_string();
+    let first = segments[0];
     let rest = &segments[1..];
 
-    match obj.get_mut(&first) {
-        Some(Value::Object(nested)) => {
+    match obj.get_mut(first).map(T::as_object_mut) {
+        Some(Some(nested)) => {
             merge_path(nested, rest, value, options)?;
         }
-        Some(_) => {
+        Some(None) => {
             if options.strict {
                 return Err(Error::new(
                     ErrorKind::ExpansionConflict,
                     format!("Path expansion conflict at '{}'", first),
                 ));
             }
-            let mut nested = Map::new();
+            let mut nested = Map::default();
             merge_path(&mut nested, rest, value, options)?;
-            obj.insert(first, Value::Object(nested));
+            obj.insert(T::key(Cow::Owned(first.to_string())), T::object(nested));
         }
         None => {
-            let mut nested = Map::new();
+            let mut nested = Map::default();
             merge_path(&mut nested, rest, value, options)?;
-            obj.insert(first, Value::Object(nested));
+            obj.insert(T::key(Cow::Owned(first.to_string())), T::object(nested));
         }
     }
 
     Ok(())
 }
 
+/// Converts a [`Value`] into any deserializable type.
+///
+/// To deserialize without consuming the value, or into types that borrow from it,
+/// call `T::deserialize(&value)` instead.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_toon2::{from_value, toon};
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// struct User {
+///     name: String,
+///     tags: Vec<String>,
+/// }
+///
+/// let user: User = from_value(toon!({ "name": "Ada", "tags": ["admin"] })).unwrap();
+/// assert_eq!(user, User { name: "Ada".into(), tags: vec!["admin".into()] });
+/// ```
+pub fn from_value<T: de::DeserializeOwned>(value: Value) -> Result<T> {
+    T::deserialize(value)
+}
+
 impl<'de> de::Deserializer<'de> for Value {
     type Error = Error;
 
@@ -1360,15 +1887,206 @@ impl<'de> de::Deserializer<'de> for Value {
         }
     }
 
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let (variant, value) = match (enum_shape(&self)?, self) {
+            (EnumShape::Unit, Value::String(variant)) => (variant, None),
+            (EnumShape::Keyed, Value::Object(obj)) => {
+                let (variant, value) = obj.into_iter().next().unwrap();
+                (variant, Some(Variant::Value(value)))
+            }
+            (EnumShape::Fields, Value::Array(mut arr)) => {
+                let Value::String(variant) = arr.remove(0) else {
+                    unreachable!()
+                };
+                (variant, Some(Variant::Fields(arr)))
+            }
+            _ => unreachable!(),
+        };
+        visitor.visit_enum(EnumDeserializer { variant, value })
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if name == spanned::NAME {
+            return Err(spanned::not_from_value());
+        }
+        self.deserialize_any(visitor)
+    }
+
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-        bytes byte_buf option unit unit_struct newtype_struct seq tuple
-        tuple_struct map struct enum identifier ignored_any
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map identifier ignored_any
+    }
+}
+
+const EXPECTED_ENUM: &str =
+    "a string, an object with a single key or an array starting with the variant name";
+
+/// How a value encodes an enum, as the serializer writes variants: `Unit`,
+/// `{Variant: value}` or, for tuple variants, `[Variant, fields...]`.
+enum EnumShape {
+    Unit,
+    Keyed,
+    Fields,
+}
+
+/// Returns the shape of an enum encoded in `value`, shared by the owned and borrowed
+/// deserializers so both accept the same inputs.
+fn enum_shape(value: &Value) -> Result<EnumShape> {
+    match value {
+        Value::String(_) => Ok(EnumShape::Unit),
+        Value::Object(obj) if obj.len() == 1 => Ok(EnumShape::Keyed),
+        Value::Array(arr) if matches!(arr.first(), Some(Value::String(_))) => Ok(EnumShape::Fields),
+        other => Err(de::Error::invalid_type(unexpected(other), &EXPECTED_ENUM)),
+    }
+}
+
+/// The content of an enum variant.
+enum Variant {
+    /// The value under the variant's key.
+    Value(Value),
+    /// The fields after the variant name in a `[Variant, fields...]` array.
+    Fields(Vec<Value>),
+}
+
+/// Error for a newtype variant written as `[Variant, fields...]` with other than one
+/// field.
+fn newtype_fields(len: usize) -> Error {
+    de::Error::invalid_length(len, &"one field after the variant name")
+}
+
+/// Describes a value for `invalid_type` errors.
+fn unexpected(value: &Value) -> de::Unexpected<'_> {
+    match value {
+        Value::Null => de::Unexpected::Unit,
+        Value::Bool(b) => de::Unexpected::Bool(*b),
+        Value::Number(Number::I64(n)) => de::Unexpected::Signed(*n),
+        Value::Number(Number::U64(n)) => de::Unexpected::Unsigned(*n),
+        Value::Number(Number::F64(n)) => de::Unexpected::Float(*n),
+        Value::String(s) => de::Unexpected::Str(s),
+        Value::Array(_) => de::Unexpected::Seq,
+        Value::Object(_) => de::Unexpected::Map,
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: Option<Variant>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, VariantDeserializer)>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(StringDeserializer(self.variant))?;
+        Ok((variant, VariantDeserializer(self.value)))
+    }
+}
+
+struct VariantDeserializer(Option<Variant>);
+
+impl<'de> de::VariantAccess<'de> for VariantDeserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        match self.0 {
+            None | Some(Variant::Value(Value::Null)) => Ok(()),
+            Some(Variant::Fields(fields)) if fields.is_empty() => Ok(()),
+            Some(Variant::Value(value)) => {
+                Err(de::Error::invalid_type(unexpected(&value), &"unit variant"))
+            }
+            Some(Variant::Fields(_)) => Err(de::Error::invalid_type(
+                de::Unexpected::Seq,
+                &"unit variant",
+            )),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.0 {
+            Some(Variant::Value(value)) => seed.deserialize(value),
+            Some(Variant::Fields(fields)) => match <[Value; 1]>::try_from(fields) {
+                Ok([value]) => seed.deserialize(value),
+                Err(fields) => Err(newtype_fields(fields.len())),
+            },
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.0 {
+            Some(Variant::Value(value)) => de::Deserializer::deserialize_any(value, visitor),
+            Some(Variant::Fields(fields)) => visitor.visit_seq(SeqDeserializer::new(fields)),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.0 {
+            Some(Variant::Value(value)) => de::Deserializer::deserialize_any(value, visitor),
+            Some(Variant::Fields(fields)) => visitor.visit_seq(SeqDeserializer::new(fields)),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
     }
 }
 
 struct SeqDeserializer {
-    iter: std::vec::IntoIter<Value>,
+    iter: alloc::vec::IntoIter<Value>,
 }
 
 impl SeqDeserializer {
@@ -1452,3 +2170,271 @@ impl<'de> de::Deserializer<'de> for StringDeserializer {
         tuple_struct map struct enum identifier ignored_any
     }
 }
+
+/// Deserializes from a borrowed [`Value`] without cloning it. Strings and keys are
+/// lent to the output, so types holding `&str` can borrow from the value.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_toon2::toon;
+///
+/// #[derive(Deserialize)]
+/// struct User<'a> {
+///     name: &'a str,
+///     tags: Vec<&'a str>,
+/// }
+///
+/// let value = toon!({ "name": "Ada", "tags": ["admin"] });
+/// let user = User::deserialize(&value).unwrap();
+///
+/// assert_eq!(user.name, "Ada");
+/// assert_eq!(user.tags, ["admin"]);
+/// ```
+impl<'de> de::Deserializer<'de> for &'de Value {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(*b),
+            Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    visitor.visit_i64(i)
+                } else if let Some(u) = n.as_u64() {
+                    visitor.visit_u64(u)
+                } else {
+                    visitor.visit_f64(n.as_f64())
+                }
+            }
+            Value::String(s) => visitor.visit_borrowed_str(s),
+            Value::Array(arr) => visitor.visit_seq(SeqRefDeserializer { iter: arr.iter() }),
+            Value::Object(obj) => visitor.visit_map(MapRefDeserializer {
+                iter: obj.iter(),
+                value: None,
+            }),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let (variant, value) = match (enum_shape(self)?, self) {
+            (EnumShape::Unit, Value::String(variant)) => (variant.as_str(), None),
+            (EnumShape::Keyed, Value::Object(obj)) => {
+                let (variant, value) = obj.iter().next().unwrap();
+                (variant.as_str(), Some(VariantRef::Value(value)))
+            }
+            (EnumShape::Fields, Value::Array(arr)) => match arr.split_first() {
+                Some((Value::String(variant), fields)) => {
+                    (variant.as_str(), Some(VariantRef::Fields(fields)))
+                }
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+        visitor.visit_enum(EnumRefDeserializer { variant, value })
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if name == spanned::NAME {
+            return Err(spanned::not_from_value());
+        }
+        self.deserialize_any(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map identifier ignored_any
+    }
+}
+
+struct SeqRefDeserializer<'de> {
+    iter: core::slice::Iter<'de, Value>,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqRefDeserializer<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some(value) => seed.deserialize(value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct MapRefDeserializer<'de> {
+    iter: indexmap::map::Iter<'de, String, Value>,
+    value: Option<&'de Value>,
+}
+
+impl<'de> de::MapAccess<'de> for MapRefDeserializer<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(de::value::BorrowedStrDeserializer::<Error>::new(key))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        match self.value.take() {
+            Some(value) => seed.deserialize(value),
+            None => Err(Error::custom("Value is missing")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// The content of an enum variant in a borrowed value.
+enum VariantRef<'de> {
+    /// The value under the variant's key.
+    Value(&'de Value),
+    /// The fields after the variant name in a `[Variant, fields...]` array.
+    Fields(&'de [Value]),
+}
+
+struct EnumRefDeserializer<'de> {
+    variant: &'de str,
+    value: Option<VariantRef<'de>>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumRefDeserializer<'de> {
+    type Error = Error;
+    type Variant = VariantRefDeserializer<'de>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, VariantRefDeserializer<'de>)>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(de::value::BorrowedStrDeserializer::<Error>::new(
+            self.variant,
+        ))?;
+        Ok((variant, VariantRefDeserializer(self.value)))
+    }
+}
+
+struct VariantRefDeserializer<'de>(Option<VariantRef<'de>>);
+
+impl<'de> de::VariantAccess<'de> for VariantRefDeserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        match self.0 {
+            None | Some(VariantRef::Value(Value::Null)) | Some(VariantRef::Fields([])) => Ok(()),
+            Some(VariantRef::Value(value)) => {
+                Err(de::Error::invalid_type(unexpected(value), &"unit variant"))
+            }
+            Some(VariantRef::Fields(_)) => Err(de::Error::invalid_type(
+                de::Unexpected::Seq,
+                &"unit variant",
+            )),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.0 {
+            Some(VariantRef::Value(value)) | Some(VariantRef::Fields([value])) => {
+                seed.deserialize(value)
+            }
+            Some(VariantRef::Fields(fields)) => Err(newtype_fields(fields.len())),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.0 {
+            Some(VariantRef::Value(value)) => de::Deserializer::deserialize_any(value, visitor),
+            Some(VariantRef::Fields(fields)) => visitor.visit_seq(SeqRefDeserializer {
+                iter: fields.iter(),
+            }),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.0 {
+            Some(VariantRef::Value(value)) => de::Deserializer::deserialize_any(value, visitor),
+            Some(VariantRef::Fields(fields)) => visitor.visit_seq(SeqRefDeserializer {
+                iter: fields.iter(),
+            }),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+}
@@ -0,0 +1,313 @@
+//! Structural comparison of two values.
+//!
+//! [`diff`] walks two values side by side and lists what was added, removed or changed
+//! as [`Change`]s located by JSON Pointer (RFC 6901). Objects are compared key by key,
+//! whatever their key order. Array elements are matched by index, or, for arrays of
+//! objects such as tabular arrays, by a key field named in [`DiffOptions::keys`], so a
+//! single edited row shows up as a single change however the rows around it moved.
+//!
+//! Changes are listed in an order in which they can be applied one after another: each
+//! path refers to the document with the earlier changes already made. [`to_json_patch`]
+//! turns them into an RFC 6902 JSON Patch that rewrites the old value into the new one.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde_toon2::diff::{diff_with_options, ChangeKind, DiffOptions};
+//! use serde_toon2::{Value, from_str};
+//!
+//! let old: Value = from_str("users[3]{id,name}:\n  1,Ada\n  2,Bob\n  3,Cy").unwrap();
+//! let new: Value = from_str("users[2]{id,name}:\n  1,Ada\n  3,Cyd").unwrap();
+//!
+//! let options = DiffOptions {
+//!     keys: vec!["id".to_string()],
+//! };
+//! let changes = diff_with_options(&old, &new, &options);
+//!
+//! assert_eq!(changes.len(), 2);
+//! assert_eq!(changes[0].path, "/users/1");
+//! assert!(matches!(changes[0].kind, ChangeKind::Removed(_)));
+//! assert_eq!(changes[1].path, "/users/1/name");
+//! assert!(matches!(changes[1].kind, ChangeKind::Changed { .. }));
+//! ```
+
+use crate::value::{Map, Value, push_pointer_token};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Options for [`diff_with_options`].
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::diff::DiffOptions;
+///
+/// let options = DiffOptions {
+///     keys: vec!["id".to_string(), "sku".to_string()],
+/// };
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    /// Fields that identify the elements of arrays of objects, tried in order. A pair
+    /// of arrays is matched by the first field that every element on both sides holds,
+    /// with no value repeated on either side. Other arrays are matched by index.
+    ///
+    /// Default: empty (match every array by index)
+    pub keys: Vec<String>,
+}
+
+/// What happened at a particular location.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeKind {
+    /// A value was added.
+    Added(Value),
+    /// A value was removed; holds the old value.
+    Removed(Value),
+    /// A value was replaced by a different one.
+    Changed {
+        /// The old value.
+        from: Value,
+        /// The new value.
+        to: Value,
+    },
+    /// An array element matched by key moved here from another position.
+    Moved {
+        /// JSON Pointer to the element's position before the move.
+        from: String,
+    },
+}
+
+/// A single difference between two values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// JSON Pointer to the location of the change.
+    pub path: String,
+    /// What happened there.
+    pub kind: ChangeKind,
+}
+
+/// Compares two values, matching array elements by index.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::diff::{diff, Change, ChangeKind};
+/// use serde_toon2::toon;
+///
+/// let old = toon!({ "name": "Ada", "tags": ["a"] });
+/// let new = toon!({ "name": "Ada", "tags": ["a", "b"] });
+///
+/// assert_eq!(
+///     diff(&old, &new),
+///     [Change {
+///         path: "/tags/1".to_string(),
+///         kind: ChangeKind::Added(toon!("b")),
+///     }]
+/// );
+/// ```
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+    diff_with_options(old, new, &DiffOptions::default())
+}
+
+/// Compares two values, matching the elements of arrays of objects by the key fields
+/// in `options`.
+pub fn diff_with_options(old: &Value, new: &Value, options: &DiffOptions) -> Vec<Change> {
+    let mut differ = Differ {
+        options,
+        path: String::new(),
+        changes: Vec::new(),
+    };
+    differ.value(old, new);
+    differ.changes
+}
+
+/// Converts changes into an RFC 6902 JSON Patch: an array of `add`, `remove`,
+/// `replace` and `move` operations.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::diff::{diff, to_json_patch};
+/// use serde_toon2::toon;
+///
+/// let patch = to_json_patch(&diff(&toon!({ "a": 1 }), &toon!({ "a": 2, "b": true })));
+/// assert_eq!(
+///     patch,
+///     toon!([
+///         { "op": "replace", "path": "/a", "value": 2 },
+///         { "op": "add", "path": "/b", "value": true },
+///     ])
+/// );
+/// ```
+pub fn to_json_patch(changes: &[Change]) -> Value {
+    let operations = changes
+        .iter()
+        .map(|change| {
+            let mut op = Map::default();
+            let (name, value) = match &change.kind {
+                ChangeKind::Added(value) => ("add", Some(value)),
+                ChangeKind::Removed(_) => ("remove", None),
+                ChangeKind::Changed { to, .. } => ("replace", Some(to)),
+                ChangeKind::Moved { from } => {
+                    op.insert("op".to_string(), Value::from("move"));
+                    op.insert("from".to_string(), Value::from(from.as_str()));
+                    op.insert("path".to_string(), Value::from(change.path.as_str()));
+                    return Value::Object(op);
+                }
+            };
+            op.insert("op".to_string(), Value::from(name));
+            op.insert("path".to_string(), Value::from(change.path.as_str()));
+            if let Some(value) = value {
+                op.insert("value".to_string(), value.clone());
+            }
+            Value::Object(op)
+        })
+        .collect();
+    Value::Array(operations)
+}
+
+/// Array indexes keyed by the value of each element's key field.
+type RowIndex<'v> = Map<&'v Value, usize>;
+
+struct Differ<'o> {
+    options: &'o DiffOptions,
+    /// Pointer to the values being compared.
+    path: String,
+    changes: Vec<Change>,
+}
+
+impl Differ<'_> {
+    fn push(&mut self, kind: ChangeKind) {
+        self.changes.push(Change {
+            path: self.path.clone(),
+            kind,
+        });
+    }
+
+    /// Runs `f` with `token` appended to the path.
+    fn at(&mut self, token: &str, f: impl FnOnce(&mut Self)) {
+        let len = self.path.len();
+        self.path.push('/');
+        push_pointer_token(&mut self.path, token);
+        f(self);
+        self.path.truncate(len);
+    }
+
+    fn at_index(&mut self, index: usize, f: impl FnOnce(&mut Self)) {
+        self.at(&index.to_string(), f);
+    }
+
+    fn pointer(&self, index: usize) -> String {
+        let mut path = self.path.clone();
+        path.push('/');
+        path.push_str(&index.to_string());
+        path
+    }
+
+    fn value(&mut self, old: &Value, new: &Value) {
+        match (old, new) {
+            (Value::Object(old), Value::Object(new)) => self.object(old, new),
+            (Value::Array(old), Value::Array(new)) => match self.row_keys(old, new) {
+                Some((old_keys, new_keys)) => self.keyed(old, new, &old_keys, &new_keys),
+                None => self.indexed(old, new),
+            },
+            _ if old == new => {}
+            _ => self.push(ChangeKind::Changed {
+                from: old.clone(),
+                to: new.clone(),
+            }),
+        }
+    }
+
+    fn object(&mut self, old: &Map<String, Value>, new: &Map<String, Value>) {
+        for (key, old_value) in old {
+            self.at(key, |d| match new.get(key) {
+                Some(new_value) => d.value(old_value, new_value),
+                None => d.push(ChangeKind::Removed(old_value.clone())),
+            });
+        }
+        for (key, new_value) in new {
+            if !old.contains_key(key) {
+                self.at(key, |d| d.push(ChangeKind::Added(new_value.clone())));
+            }
+        }
+    }
+
+    fn indexed(&mut self, old: &[Value], new: &[Value]) {
+        let common = old.len().min(new.len());
+        for (i, (old_item, new_item)) in old.iter().zip(new).enumerate() {
+            self.at_index(i, |d| d.value(old_item, new_item));
+        }
+        // Remove from the end, so the indexes of the remaining elements stay put
+        for i in (common..old.len()).rev() {
+            self.at_index(i, |d| d.push(ChangeKind::Removed(old[i].clone())));
+        }
+        for (i, new_item) in new.iter().enumerate().skip(common) {
+            self.at_index(i, |d| d.push(ChangeKind::Added(new_item.clone())));
+        }
+    }
+
+    /// Indexes both arrays by the first key field that identifies their elements.
+    fn row_keys<'v>(
+        &self,
+        old: &'v [Value],
+        new: &'v [Value],
+    ) -> Option<(RowIndex<'v>, RowIndex<'v>)> {
+        self.options
+            .keys
+            .iter()
+            .find_map(|key| Some((index_by(old, key)?, index_by(new, key)?)))
+    }
+
+    fn keyed(
+        &mut self,
+        old: &[Value],
+        new: &[Value],
+        old_keys: &RowIndex<'_>,
+        new_keys: &RowIndex<'_>,
+    ) {
+        // Old indexes of the elements, in their current order as changes are applied
+        let mut order: Vec<Option<usize>> = (0..old.len()).map(Some).collect();
+
+        for (i, key) in old_keys.keys().enumerate().rev() {
+            if !new_keys.contains_key(key) {
+                self.at_index(i, |d| d.push(ChangeKind::Removed(old[i].clone())));
+                order.remove(i);
+            }
+        }
+
+        for (i, (key, _)) in new_keys.iter().enumerate() {
+            let new_item = &new[i];
+            let Some(&old_index) = old_keys.get(key) else {
+                self.at_index(i, |d| d.push(ChangeKind::Added(new_item.clone())));
+                order.insert(i, None);
+                continue;
+            };
+            // Elements before `i` are in place, so the element is at `i` or after it
+            let current = i + order[i..]
+                .iter()
+                .position(|&index| index == Some(old_index))
+                .unwrap();
+            if current != i {
+                let from = self.pointer(current);
+                self.at_index(i, |d| d.push(ChangeKind::Moved { from }));
+                order.remove(current);
+                order.insert(i, Some(old_index));
+            }
+            self.at_index(i, |d| d.value(&old[old_index], new_item));
+        }
+    }
+}
+
+/// Maps each element's `key` field to its index, or returns `None` if an element is
+/// not an object with that field or two elements share a value.
+fn index_by<'v>(items: &'v [Value], key: &str) -> Option<RowIndex<'v>> {
+    let mut index = Map::default();
+    for (i, item) in items.iter().enumerate() {
+        let id = item.as_object()?.get(key)?;
+        if index.insert(id, i).is_some() {
+            return None;
+        }
+    }
+    Some(index)
+}
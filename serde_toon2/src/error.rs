@@ -3,10 +3,11 @@
 //! This module provides strongly-typed error handling with location information
 //! to aid in debugging TOON format issues.
 
-use std::fmt;
+use alloc::string::{String, ToString};
+use core::fmt;
 
 /// A specialized `Result` type for TOON operations.
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
 /// The main error type for TOON operations.
 ///
@@ -74,6 +75,11 @@ pub enum ErrorKind {
     DelimiterMismatch,
     /// Array header syntax is invalid.
     InvalidHeader,
+    /// A value has no equivalent in the format it is being converted to or from, such
+    /// as a TOML datetime or a non-finite float converted to JSON.
+    Unrepresentable,
+    /// A query expression could not be parsed.
+    InvalidQuery,
     /// An I/O error occurred during reading or writing.
     Io(String),
     /// A custom error message.
@@ -121,6 +127,34 @@ impl Error {
         self
     }
 
+    /// Returns the 1-based line the error was found on, if known.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::{from_str, Value};
+    ///
+    /// let err = from_str::<Value>("a: 1\nb[2]: x").unwrap_err();
+    /// assert_eq!(err.line(), Some(2));
+    /// ```
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// Returns the 1-based column the error was found at, if known.
+    pub fn column(&self) -> Option<usize> {
+        self.column
+    }
+
+    /// Moves the error's line number down by `offset` lines, for errors in a document
+    /// embedded in a larger text.
+    pub(crate) fn offset_line(mut self, offset: usize) -> Self {
+        if let Some(line) = &mut self.line {
+            *line += offset;
+        }
+        self
+    }
+
     /// Creates a custom error with a free-form message.
     ///
     /// # Examples
@@ -164,7 +198,7 @@ impl fmt::Display for Error {
     }
 }
 
-impl std::error::Error for Error {}
+impl core::error::Error for Error {}
 
 impl serde::ser::Error for Error {
     fn custom<T: fmt::Display>(msg: T) -> Self {
@@ -178,8 +212,8 @@ impl serde::de::Error for Error {
     }
 }
 
-impl From<std::io::Error> for Error {
-    fn from(err: std::io::Error) -> Self {
+impl From<crate::io::Error> for Error {
+    fn from(err: crate::io::Error) -> Self {
         Error::new(ErrorKind::Io(err.to_string()), err.to_string())
     }
 }
@@ -0,0 +1,302 @@
+//! Finding and decoding TOON embedded in larger text, such as Markdown LLM replies.
+//!
+//! [`find_blocks`] locates two kinds of candidate regions:
+//!
+//! - **Fenced** blocks: the contents of a Markdown code fence labelled `toon`, or of an
+//!   unlabelled fence.
+//! - **Unfenced** regions: runs of lines outside any fence that start with a
+//!   `key: value` or array header line at column 0. Indented lines continue a region;
+//!   a blank line ends it unless the next line continues it.
+//!
+//! [`from_str_first`] and [`from_str_all`] decode those candidates. Unfenced regions
+//! and unlabelled fences are only guesses, so candidates among them that fail to
+//! decode are skipped. A fence labelled `toon` is expected to hold TOON, and its
+//! errors are returned.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde_toon2::extract::from_str_first;
+//! use serde_toon2::{DecoderOptions, Value};
+//!
+//! let reply = "Here are the users:\n\n```toon\nusers[2]{id,name}:\n  1,Ada\n  2,Bob\n```\n\nAnything else?";
+//! let found = from_str_first::<Value>(reply, DecoderOptions::default()).unwrap();
+//!
+//! assert_eq!(&reply[found.range.clone()], "users[2]{id,name}:\n  1,Ada\n  2,Bob\n");
+//! assert_eq!(found.value.as_object().unwrap()["users"].as_array().unwrap().len(), 2);
+//! ```
+
+use crate::de::from_str_with_options;
+use crate::error::{Error, ErrorKind, Result};
+use crate::options::DecoderOptions;
+use crate::scan::find_colon;
+use alloc::vec::Vec;
+use core::ops::Range;
+use serde::de;
+
+/// A candidate TOON region within a larger text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block<'a> {
+    /// Byte range of the region in the original text, excluding fence lines.
+    pub range: Range<usize>,
+    /// The text of the region.
+    pub text: &'a str,
+    /// How the region was found.
+    pub kind: BlockKind,
+}
+
+/// How a [`Block`] was delimited in the original text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockKind {
+    /// A code fence labelled `toon`.
+    Labelled,
+    /// A code fence without a language label.
+    Fenced,
+    /// Lines outside any code fence that look like TOON.
+    Unfenced,
+}
+
+/// A decoded block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Extracted<T> {
+    /// The decoded value.
+    pub value: T,
+    /// Byte range of the block in the original text.
+    pub range: Range<usize>,
+}
+
+/// Finds the candidate TOON regions in `text`, in order of appearance.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::extract::{find_blocks, BlockKind};
+///
+/// let text = "Result:\nname: Ada\nage: 42\n\n```json\n{}\n```\n";
+/// let blocks = find_blocks(text);
+///
+/// assert_eq!(blocks.len(), 1);
+/// assert_eq!(blocks[0].text, "name: Ada\nage: 42\n");
+/// assert_eq!(blocks[0].kind, BlockKind::Unfenced);
+/// ```
+pub fn find_blocks(text: &str) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+    let mut lines = LineSpans::new(text).peekable();
+    // Start and end of the unfenced region being collected
+    let mut region: Option<(usize, usize)> = None;
+
+    while let Some((start, end)) = lines.next() {
+        let line = text[start..end].trim_end_matches(['\n', '\r']);
+
+        if let Some(lang) = line.trim_start().strip_prefix("```") {
+            close_region(text, &mut region, &mut blocks);
+
+            let body_start = end;
+            let mut body_end = text.len();
+            for (fence_start, fence_end) in lines.by_ref() {
+                if text[fence_start..fence_end].trim() == "```" {
+                    body_end = fence_start;
+                    break;
+                }
+            }
+
+            let kind = match lang.trim() {
+                "" => BlockKind::Fenced,
+                lang if lang.eq_ignore_ascii_case("toon") => BlockKind::Labelled,
+                _ => continue,
+            };
+            if !text[body_start..body_end].trim().is_empty() {
+                blocks.push(Block {
+                    range: body_start..body_end,
+                    text: &text[body_start..body_end],
+                    kind,
+                });
+            }
+            continue;
+        }
+
+        match region {
+            Some((_, ref mut region_end)) => {
+                if (line.starts_with([' ', '\t']) && !line.trim().is_empty())
+                    || looks_like_toon_line(line)
+                {
+                    *region_end = end;
+                } else if line.trim().is_empty()
+                    && lines.peek().is_some_and(|&(next_start, next_end)| {
+                        let next = text[next_start..next_end].trim_end_matches(['\n', '\r']);
+                        looks_like_toon_line(next)
+                    })
+                {
+                    // A blank line between two top-level entries
+                    *region_end = end;
+                } else {
+                    close_region(text, &mut region, &mut blocks);
+                }
+            }
+            None => {
+                // A line opening a nested value only starts a region if the value follows,
+                // which keeps prose such as "Result:" out
+                let opens_nested = line.trim_end().ends_with(':');
+                if looks_like_toon_line(line)
+                    && (!opens_nested
+                        || lines.peek().is_some_and(|&(next_start, _)| {
+                            text[next_start..].starts_with([' ', '\t'])
+                        }))
+                {
+                    region = Some((start, end));
+                }
+            }
+        }
+    }
+    close_region(text, &mut region, &mut blocks);
+
+    blocks
+}
+
+/// Decodes the first TOON block in `text`.
+///
+/// Fails if a fence labelled `toon` comes before any block that decodes and does not
+/// decode itself, or if no block decodes.
+pub fn from_str_first<'a, T: de::Deserialize<'a>>(
+    text: &'a str,
+    options: DecoderOptions,
+) -> Result<Extracted<T>> {
+    for block in find_blocks(text) {
+        if let Some(extracted) = decode_block(text, &block, &options)? {
+            return Ok(extracted);
+        }
+    }
+    Err(Error::new(ErrorKind::InvalidSyntax, "No TOON block found"))
+}
+
+/// Decodes every TOON block in `text`.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::extract::from_str_all;
+/// use serde_toon2::DecoderOptions;
+/// use std::collections::HashMap;
+///
+/// let text = "First:\n```toon\nid: 1\n```\nSecond:\n```toon\nid: 2\n```";
+/// let found = from_str_all::<HashMap<String, u32>>(text, DecoderOptions::default()).unwrap();
+///
+/// assert_eq!(found.len(), 2);
+/// assert_eq!(found[1].value["id"], 2);
+/// ```
+pub fn from_str_all<'a, T: de::Deserialize<'a>>(
+    text: &'a str,
+    options: DecoderOptions,
+) -> Result<Vec<Extracted<T>>> {
+    let mut found = Vec::new();
+    for block in find_blocks(text) {
+        if let Some(extracted) = decode_block(text, &block, &options)? {
+            found.push(extracted);
+        }
+    }
+    Ok(found)
+}
+
+/// Decodes one block. Returns `None` for a guessed block that does not decode.
+fn decode_block<'a, T: de::Deserialize<'a>>(
+    text: &'a str,
+    block: &Block<'a>,
+    options: &DecoderOptions,
+) -> Result<Option<Extracted<T>>> {
+    match from_str_with_options(block.text, options.clone()) {
+        Ok(value) => Ok(Some(Extracted {
+            value,
+            range: block.range.clone(),
+        })),
+        Err(err) if block.kind == BlockKind::Labelled => {
+            // Report lines relative to the whole text
+            let offset = text[..block.range.start].matches('\n').count();
+            Err(err.offset_line(offset))
+        }
+        Err(_) => Ok(None),
+    }
+}
+
+fn close_region<'a>(
+    text: &'a str,
+    region: &mut Option<(usize, usize)>,
+    blocks: &mut Vec<Block<'a>>,
+) {
+    if let Some((start, end)) = region.take() {
+        blocks.push(Block {
+            range: start..end,
+            text: &text[start..end],
+            kind: BlockKind::Unfenced,
+        });
+    }
+}
+
+/// Returns whether a line can start a TOON document: a key or array header followed
+/// by a colon and either the end of the line or a space.
+fn looks_like_toon_line(line: &str) -> bool {
+    let Some(colon) = find_colon(line) else {
+        return false;
+    };
+    let after = &line[colon + 1..];
+    if !(after.is_empty() || after.starts_with(' ')) {
+        return false;
+    }
+
+    let head = &line[..colon];
+    // Split off an array header, e.g. `users[2]{id,name}`
+    let key = match head.find('[') {
+        Some(bracket) => {
+            let header = &head[bracket..];
+            let Some(close) = header.find(']') else {
+                return false;
+            };
+            let length = header[1..close].trim_end_matches(['|', '\t']);
+            let rest = &header[close + 1..];
+            if length.is_empty()
+                || !length.bytes().all(|b| b.is_ascii_digit())
+                || !(rest.is_empty() || rest.starts_with('{') && rest.ends_with('}'))
+            {
+                return false;
+            }
+            if bracket == 0 {
+                return true;
+            }
+            &head[..bracket]
+        }
+        None => head,
+    };
+
+    if key.len() >= 2 && key.starts_with('"') && key.ends_with('"') {
+        return true;
+    }
+    let mut chars = key.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Iterates over the byte spans of the lines in a text, including line terminators.
+struct LineSpans<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> LineSpans<'a> {
+    fn new(text: &'a str) -> Self {
+        LineSpans { text, pos: 0 }
+    }
+}
+
+impl Iterator for LineSpans<'_> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        if self.pos >= self.text.len() {
+            return None;
+        }
+        let start = self.pos;
+        let end = memchr::memchr(b'\n', &self.text.as_bytes()[start..])
+            .map_or(self.text.len(), |i| start + i + 1);
+        self.pos = end;
+        Some((start, end))
+    }
+}
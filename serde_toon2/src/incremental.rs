@@ -0,0 +1,361 @@
+//! Incremental decoding of TOON that arrives in chunks.
+//!
+//! A [`Parser`] buffers text passed to [`Parser::feed`] and returns the best reading of
+//! the document so far: arrays that have not received all of their declared items are
+//! kept with the items seen so far, and a scalar on the last, unfinished line is
+//! flagged as incomplete since more characters may still arrive. Once the input is
+//! complete, [`Parser::finish`] decodes it with full validation.
+//!
+//! Top-level fields that a later top-level line has closed are parsed once and kept, so
+//! each call to `feed` only parses the document from its last top-level field on. A
+//! document whose root is an array, or that is decoded with
+//! [`repair`](DecoderOptions::repair), is parsed again in full on every call.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde_toon2::incremental::Parser;
+//! use serde_toon2::Value;
+//!
+//! let mut parser = Parser::new();
+//!
+//! let partial = parser.feed("users[3]{id,name}:\n  1,Ada\n  2,Bo").unwrap();
+//! let users = partial.value.as_object().unwrap()["users"].as_array().unwrap();
+//! assert_eq!(users.len(), 2);
+//! assert_eq!(partial.incomplete.as_deref(), Some("/users/1/name"));
+//! assert_eq!(partial.pending[0].received, 2);
+//!
+//! parser.feed("b\n  3,Cy").unwrap();
+//! let value: Value = parser.finish().unwrap();
+//! assert_eq!(value.as_object().unwrap()["users"].as_array().unwrap().len(), 3);
+//! ```
+
+use crate::de::{
+    detect_indent, expand_fields, expand_paths, expansion_segments, from_str_with_options,
+    parse_partial, parse_partial_fields,
+};
+use crate::error::Result;
+use crate::options::{DecoderOptions, Indent, PathExpansion};
+use crate::spanned::{Node, Step};
+use crate::value::{Map, Value, push_pointer_token};
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::de::DeserializeOwned;
+
+/// An array whose header declares more items than have been received so far.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingArray {
+    /// Line number of the array header.
+    pub line: usize,
+    /// Key of the array, or `None` for a root array or a bare list item array.
+    pub key: Option<String>,
+    /// Number of items declared by the `[N]` header.
+    pub declared: usize,
+    /// Number of items received so far.
+    pub received: usize,
+}
+
+/// The document as decoded so far.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Partial {
+    /// The decoded value, with unfilled arrays truncated to the items received.
+    pub value: Value,
+    /// JSON Pointer to the last scalar when its line is still being written.
+    pub incomplete: Option<String>,
+    /// Arrays still waiting for items, in the order their bodies end.
+    pub pending: Vec<PendingArray>,
+}
+
+/// A decoder that accepts TOON text in chunks.
+#[derive(Debug, Clone)]
+pub struct Parser {
+    buffer: String,
+    options: DecoderOptions,
+    /// Whether the document can be parsed a few top-level fields at a time.
+    cacheable: bool,
+    /// Length of the buffer prefix whose fields are parsed into `fields`.
+    committed: usize,
+    /// Number of lines in that prefix.
+    committed_lines: usize,
+    /// The fields of the prefix as parsed.
+    raw: Map<String, Value>,
+    /// The fields of the prefix with dotted keys expanded, if the options ask for it.
+    expanded: Map<String, Value>,
+    /// Unfilled arrays in the prefix.
+    pending: Vec<PendingArray>,
+    /// The indentation the prefix was found to use, for `Indent::Auto`.
+    indent: Indent,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Parser {
+    /// Creates a parser with strict validation.
+    pub fn new() -> Self {
+        Self::with_options(DecoderOptions {
+            strict: true,
+            ..Default::default()
+        })
+    }
+
+    /// Creates a parser with custom decoder options.
+    pub fn with_options(options: DecoderOptions) -> Self {
+        Parser {
+            buffer: String::new(),
+            // Repairs look at the document as a whole, such as a code fence around it
+            cacheable: !options.repair,
+            committed: 0,
+            committed_lines: 0,
+            raw: Map::new(),
+            expanded: Map::new(),
+            pending: Vec::new(),
+            indent: options.indent,
+            options,
+        }
+    }
+
+    /// Returns the text received so far.
+    pub fn buffer(&self) -> &str {
+        &self.buffer
+    }
+
+    /// Appends a chunk of text and returns the document decoded so far.
+    ///
+    /// Errors are only returned for problems in complete lines, which more input
+    /// cannot fix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::incremental::Parser;
+    /// use serde_toon2::Value;
+    ///
+    /// let mut parser = Parser::new();
+    ///
+    /// let partial = parser.feed("title: \"Hello, wor").unwrap();
+    /// assert_eq!(partial.value.as_object().unwrap()["title"], Value::from("Hello, wor"));
+    /// assert_eq!(partial.incomplete.as_deref(), Some("/title"));
+    ///
+    /// let partial = parser.feed("ld\"\n").unwrap();
+    /// assert_eq!(partial.value.as_object().unwrap()["title"], Value::from("Hello, world"));
+    /// assert_eq!(partial.incomplete, None);
+    /// ```
+    pub fn feed(&mut self, chunk: &str) -> Result<Partial> {
+        self.buffer.push_str(chunk);
+
+        let split = self.buffer.rfind('\n').map_or(0, |i| i + 1);
+        self.commit(split)?;
+        let start = if self.cacheable { self.committed } else { 0 };
+
+        // Read the unfinished line if it makes sense on its own, otherwise wait for more
+        let tail = &self.buffer[split..];
+        if !tail.trim().is_empty()
+            && let Ok(partial) = self.read(start, split, Some(&close_open_string(tail)))
+        {
+            return Ok(partial);
+        }
+        self.read(start, split, None)
+    }
+
+    /// Parses the top-level fields that end before `split`, except the last, which the
+    /// next line may still add to.
+    fn commit(&mut self, split: usize) -> Result<()> {
+        if !self.cacheable {
+            return Ok(());
+        }
+
+        // A line without indentation starts a top-level field and ends the one before
+        let Some(boundary) = self.buffer[self.committed..split]
+            .rmatch_indices('\n')
+            .map(|(i, _)| self.committed + i + 1)
+            .find(|&i| i < split && !self.buffer[i..].starts_with([' ', '\t', '\r', '\n']))
+        else {
+            return Ok(());
+        };
+
+        let text = &self.buffer[self.committed..boundary];
+        if self.indent == Indent::Auto
+            && let Some(indent) = detect_indent(text)
+        {
+            self.indent = indent;
+        }
+        let options = DecoderOptions {
+            indent: self.indent,
+            ..self.options.clone()
+        };
+
+        let at_start = self.committed == 0;
+        let parsed = parse_partial_fields(text, &options, at_start)
+            .map_err(|err| err.offset_line(self.committed_lines))?;
+        let Some((fields, pending)) = parsed else {
+            self.cacheable = false;
+            return Ok(());
+        };
+        let Value::Object(fields) = fields.into_value() else {
+            unreachable!("fields are parsed into an object");
+        };
+        // Nothing but blank lines so far, which must not hide what the first field is
+        if fields.is_empty() {
+            return Ok(());
+        }
+
+        if options.expand_paths != PathExpansion::Off {
+            self.expanded = self.with_fields(fields.clone(), &options)?;
+        }
+        self.raw.extend(fields);
+        self.pending
+            .extend(pending.into_iter().map(|array| PendingArray {
+                line: array.line + self.committed_lines,
+                ..array
+            }));
+        self.committed_lines += text.matches('\n').count();
+        self.committed = boundary;
+        Ok(())
+    }
+
+    /// Returns the committed fields followed by `fields`, as the whole document would
+    /// hold them.
+    fn with_fields(
+        &self,
+        fields: Map<String, Value>,
+        options: &DecoderOptions,
+    ) -> Result<Map<String, Value>> {
+        if options.expand_paths == PathExpansion::Off {
+            let mut all = self.raw.clone();
+            all.extend(fields);
+            return Ok(all);
+        }
+
+        // A repeated key keeps its first place with its last value, and only then are
+        // keys expanded, so a repeat means expanding everything again
+        let mut all;
+        if fields.keys().any(|key| self.raw.contains_key(key)) {
+            let mut raw = self.raw.clone();
+            raw.extend(fields);
+            all = Map::new();
+            expand_fields(&mut all, raw, options)?;
+        } else {
+            all = self.expanded.clone();
+            expand_fields(&mut all, fields, options)?;
+        }
+        Ok(all)
+    }
+
+    /// Reads the buffer from `start` to `split` followed by `tail`, an unfinished line.
+    fn read(&self, start: usize, split: usize, tail: Option<&str>) -> Result<Partial> {
+        let complete = &self.buffer[start..split];
+        let mut text = String::with_capacity(complete.len() + tail.map_or(0, str::len));
+        text.push_str(complete);
+        text.push_str(tail.unwrap_or_default());
+        let tail_start = text.as_ptr() as usize + complete.len();
+
+        let options = DecoderOptions {
+            indent: self.indent,
+            ..self.options.clone()
+        };
+        let offset = |err: crate::Error| err.offset_line(self.committed_lines);
+
+        if start == 0 {
+            let (root, pending) = parse_partial(&text, &options)?;
+            let incomplete = tail.and_then(|_| incomplete_path(&root, tail_start, &options));
+            let mut value = root.into_value();
+            if options.expand_paths != PathExpansion::Off {
+                value = expand_paths(value, &options)?;
+            }
+            return Ok(Partial {
+                value,
+                incomplete,
+                pending,
+            });
+        }
+
+        let Some((root, pending)) = parse_partial_fields(&text, &options, false).map_err(offset)?
+        else {
+            return self.read(0, split, tail);
+        };
+        let incomplete = tail.and_then(|_| incomplete_path(&root, tail_start, &options));
+        let Value::Object(rest) = root.into_value() else {
+            unreachable!("fields are parsed into an object");
+        };
+        let fields = self.with_fields(rest, &options)?;
+
+        let mut all_pending = self.pending.clone();
+        all_pending.extend(pending.into_iter().map(|array| PendingArray {
+            line: array.line + self.committed_lines,
+            ..array
+        }));
+        Ok(Partial {
+            value: Value::Object(fields),
+            incomplete,
+            pending: all_pending,
+        })
+    }
+
+    /// Decodes the complete input with full validation.
+    pub fn finish<T: DeserializeOwned>(self) -> Result<T> {
+        from_str_with_options(&self.buffer, self.options)
+    }
+}
+
+/// Closes a quoted string left open at the end of an unfinished line.
+fn close_open_string(line: &str) -> Cow<'_, str> {
+    let mut in_quotes = false;
+    let mut escape_next = false;
+    for byte in line.bytes() {
+        if escape_next {
+            escape_next = false;
+        } else if byte == b'\\' && in_quotes {
+            escape_next = true;
+        } else if byte == b'"' {
+            in_quotes = !in_quotes;
+        }
+    }
+
+    if !in_quotes {
+        return Cow::Borrowed(line);
+    }
+
+    // Drop a dangling backslash so the closing quote is not escaped
+    let mut closed = line
+        .strip_suffix('\\')
+        .filter(|_| escape_next)
+        .unwrap_or(line)
+        .to_string();
+    closed.push('"');
+    Cow::Owned(closed)
+}
+
+/// Returns the JSON Pointer of the scalar on the unfinished line, which starts at the
+/// address `tail_start`, as it will be found once dotted keys are expanded.
+fn incomplete_path(root: &Node<'_>, tail_start: usize, options: &DecoderOptions) -> Option<String> {
+    let mut path = String::new();
+    for step in root.last_scalar_from(tail_start)? {
+        match step {
+            Step::Key(key) => {
+                // Path expansion strips the mark of quoted keys and splits dotted ones
+                let segments = if options.expand_paths == PathExpansion::Off {
+                    vec![key]
+                } else if let Some(quoted) = key.strip_prefix('\x00') {
+                    vec![quoted]
+                } else {
+                    expansion_segments(key, options).unwrap_or_else(|| vec![key])
+                };
+                for segment in segments {
+                    path.push('/');
+                    push_pointer_token(&mut path, segment);
+                }
+            }
+            Step::Index(i) => {
+                path.push('/');
+                path.push_str(&i.to_string());
+            }
+        }
+    }
+    Some(path)
+}
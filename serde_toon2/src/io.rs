@@ -0,0 +1,88 @@
+//! The output trait used by the serializer.
+//!
+//! With the `std` feature (on by default), this module re-exports `std::io::Write`
+//! and its error types. Without it, it provides a minimal [`Write`] trait with the
+//! methods the serializer needs, implemented for `Vec<u8>`, so that
+//! [`to_string`](crate::to_string) and custom [`Formatter`](crate::ser::Formatter)s
+//! work with only `alloc`.
+
+#[cfg(feature = "std")]
+pub use std::io::{Error, Result, Write};
+
+#[cfg(not(feature = "std"))]
+pub use self::core_io::{Error, Result, Write};
+
+#[cfg(not(feature = "std"))]
+mod core_io {
+    use alloc::vec::Vec;
+    use core::fmt;
+
+    /// An error returned by a [`Write`] implementation.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Error;
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("write error")
+        }
+    }
+
+    impl core::error::Error for Error {}
+
+    /// A specialized `Result` type for [`Write`] operations.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// A byte sink, mirroring the parts of `std::io::Write` the serializer uses.
+    pub trait Write {
+        /// Writes an entire buffer.
+        fn write_all(&mut self, buf: &[u8]) -> Result<()>;
+
+        /// Flushes any buffered output.
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        /// Writes formatted output, as used by the `write!` macro.
+        fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> Result<()> {
+            struct Adapter<'a, W: ?Sized> {
+                inner: &'a mut W,
+                error: Result<()>,
+            }
+
+            impl<W: Write + ?Sized> fmt::Write for Adapter<'_, W> {
+                fn write_str(&mut self, s: &str) -> fmt::Result {
+                    self.inner.write_all(s.as_bytes()).map_err(|err| {
+                        self.error = Err(err);
+                        fmt::Error
+                    })
+                }
+            }
+
+            let mut adapter = Adapter {
+                inner: self,
+                error: Ok(()),
+            };
+            match fmt::write(&mut adapter, args) {
+                Ok(()) => Ok(()),
+                Err(_) => adapter.error.and(Err(Error)),
+            }
+        }
+    }
+
+    impl Write for Vec<u8> {
+        fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+            self.extend_from_slice(buf);
+            Ok(())
+        }
+    }
+
+    impl<W: Write + ?Sized> Write for &mut W {
+        fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+            (**self).write_all(buf)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            (**self).flush()
+        }
+    }
+}
@@ -11,6 +11,10 @@
 //! - **Array headers**: Declare length and optional field lists
 //! - **Multiple delimiters**: Arrays can use comma, tab, or pipe delimiters
 //! - **Serde integration**: Full compatibility with Rust's serde ecosystem
+//! - **`no_std` support**: Without the default `std` feature, the crate needs only
+//!   `alloc`. Readers, writers and the `parallel` and `tokio` features require `std`.
+//! - **Format conversions**: The `json`, `yaml` and `toml` features convert [`Value`]
+//!   to and from the value types of `serde_json`, `serde_yaml` and `toml`.
 //!
 //! # Usage
 //!
@@ -101,10 +105,10 @@
 //! Both serialization and deserialization can be customized using options:
 //!
 //! ```
-//! use serde_toon2::{to_string_with_options, EncoderOptions, Delimiter};
+//! use serde_toon2::{to_string_with_options, EncoderOptions, Delimiter, Indent};
 //!
 //! let opts = EncoderOptions {
-//!     indent: 4,
+//!     indent: Indent::Spaces(4),
 //!     delimiter: Delimiter::Pipe,
 //!     ..Default::default()
 //! };
@@ -128,20 +132,55 @@
 //! assert!(result.is_err());
 //! ```
 
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "tokio")]
+pub mod async_io;
+pub mod borrowed;
+#[cfg(any(feature = "json", feature = "yaml", feature = "toml"))]
+mod convert;
 pub mod de;
+pub mod diff;
 pub mod error;
+pub mod extract;
+pub mod incremental;
+pub mod io;
+mod macros;
+mod map;
 pub mod options;
+pub mod query;
+pub mod repair;
+mod scan;
 pub mod ser;
+pub mod spanned;
+pub mod stats;
+pub mod truncate;
 pub mod value;
 
+#[cfg(feature = "tokio")]
+pub use async_io::{from_async_read, to_async_write};
+pub use borrowed::ValueRef;
+#[cfg(feature = "std")]
+pub use de::{StreamDeserializer, from_reader, from_reader_with_options};
 pub use de::{
-    from_reader, from_reader_with_options, from_slice, from_slice_with_options, from_str,
-    from_str_with_options,
+    from_slice, from_slice_with_options, from_str, from_str_borrowed,
+    from_str_borrowed_with_options, from_str_with_options, from_value,
 };
 pub use error::{Error, Result};
-pub use options::{DecoderOptions, Delimiter, EncoderOptions, KeyFolding, PathExpansion};
-pub use ser::{
-    to_string, to_string_with_options, to_vec, to_vec_with_options, to_writer,
-    to_writer_with_options,
+pub use options::{
+    DecoderOptions, Delimiter, EncoderOptions, Indent, KeyFolding, LineEnding, PathExpansion,
 };
+pub use ser::{to_string, to_string_with_options, to_value, to_vec, to_vec_with_options};
+#[cfg(feature = "std")]
+pub use ser::{to_writer, to_writer_with_options};
+pub use spanned::Spanned;
 pub use value::{Map, Number, Value};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::vec;
+}
@@ -0,0 +1,182 @@
+/// Builds a [`Value`](crate::Value) from a JSON-like literal.
+///
+/// Objects keep their keys in the order written, as in encoded TOON.
+///
+/// ```
+/// use serde_toon2::{toon, to_string};
+///
+/// let value = toon!({
+///     "name": "Ada",
+///     "tags": ["admin", "ops"],
+///     "manager": null,
+/// });
+///
+/// assert_eq!(to_string(&value).unwrap(), "name: Ada\ntags[2]: admin,ops\nmanager: null");
+/// ```
+///
+/// Variables and expressions can be interpolated. Values must implement `Serialize`
+/// and keys `Into<String>`; the macro panics if a value fails to serialize. Keys that
+/// are not string literals or identifiers must be wrapped in parentheses.
+///
+/// ```
+/// use serde_toon2::toon;
+///
+/// let ids = vec![1, 2, 3];
+/// let key = "count";
+///
+/// let value = toon!({
+///     (key): ids.len(),
+///     "first": ids[0],
+///     "even": ids.iter().filter(|id| *id % 2 == 0).count() > 0,
+/// });
+///
+/// assert_eq!(value.as_object().unwrap()["count"].as_u64(), Some(3));
+/// ```
+#[macro_export]
+macro_rules! toon {
+    ($($toon:tt)+) => {
+        $crate::toon_internal!($($toon)+)
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! toon_internal {
+    // Array elements, collected into `[$($elems,)*]` one at a time.
+
+    (@array [$($elems:expr,)*]) => {
+        $crate::__private::vec![$($elems,)*]
+    };
+    (@array [$($elems:expr),*]) => {
+        $crate::__private::vec![$($elems),*]
+    };
+    (@array [$($elems:expr,)*] null $($rest:tt)*) => {
+        $crate::toon_internal!(@array [$($elems,)* $crate::toon_internal!(null)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] true $($rest:tt)*) => {
+        $crate::toon_internal!(@array [$($elems,)* $crate::toon_internal!(true)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] false $($rest:tt)*) => {
+        $crate::toon_internal!(@array [$($elems,)* $crate::toon_internal!(false)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::toon_internal!(@array [$($elems,)* $crate::toon_internal!([$($array)*])] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] {$($map:tt)*} $($rest:tt)*) => {
+        $crate::toon_internal!(@array [$($elems,)* $crate::toon_internal!({$($map)*})] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::toon_internal!(@array [$($elems,)* $crate::toon_internal!($next),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $last:expr) => {
+        $crate::toon_internal!(@array [$($elems,)* $crate::toon_internal!($last)])
+    };
+    (@array [$($elems:expr),*] , $($rest:tt)*) => {
+        $crate::toon_internal!(@array [$($elems,)*] $($rest)*)
+    };
+    (@array [$($elems:expr),*] $unexpected:tt $($rest:tt)*) => {
+        $crate::toon_unexpected!($unexpected)
+    };
+
+    // Object entries. `(@object $map (key tokens) (rest) (rest copy))` munches the
+    // key up to the colon, then the value up to the next comma.
+
+    (@object $object:ident () () ()) => {};
+    (@object $object:ident [$($key:tt)+] ($value:expr) , $($rest:tt)*) => {
+        let _ = $object.insert(($($key)+).into(), $value);
+        $crate::toon_internal!(@object $object () ($($rest)*) ($($rest)*));
+    };
+    (@object $object:ident [$($key:tt)+] ($value:expr) $unexpected:tt $($rest:tt)*) => {
+        $crate::toon_unexpected!($unexpected);
+    };
+    (@object $object:ident [$($key:tt)+] ($value:expr)) => {
+        let _ = $object.insert(($($key)+).into(), $value);
+    };
+    (@object $object:ident ($($key:tt)+) (: null $($rest:tt)*) $copy:tt) => {
+        $crate::toon_internal!(@object $object [$($key)+] ($crate::toon_internal!(null)) $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: true $($rest:tt)*) $copy:tt) => {
+        $crate::toon_internal!(@object $object [$($key)+] ($crate::toon_internal!(true)) $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: false $($rest:tt)*) $copy:tt) => {
+        $crate::toon_internal!(@object $object [$($key)+] ($crate::toon_internal!(false)) $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: [$($array:tt)*] $($rest:tt)*) $copy:tt) => {
+        $crate::toon_internal!(@object $object [$($key)+] ($crate::toon_internal!([$($array)*])) $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: {$($map:tt)*} $($rest:tt)*) $copy:tt) => {
+        $crate::toon_internal!(@object $object [$($key)+] ($crate::toon_internal!({$($map)*})) $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: $value:expr , $($rest:tt)*) $copy:tt) => {
+        $crate::toon_internal!(@object $object [$($key)+] ($crate::toon_internal!($value)) , $($rest)*);
+    };
+    (@object $object:ident ($($key:tt)+) (: $value:expr) $copy:tt) => {
+        $crate::toon_internal!(@object $object [$($key)+] ($crate::toon_internal!($value)));
+    };
+    (@object $object:ident ($($key:tt)+) (:) $copy:tt) => {
+        // A missing value gives "unexpected end of macro invocation"
+        $crate::toon_internal!();
+    };
+    (@object $object:ident ($($key:tt)+) () $copy:tt) => {
+        // A missing colon gives "unexpected end of macro invocation"
+        $crate::toon_internal!();
+    };
+    (@object $object:ident () (: $($rest:tt)*) ($colon:tt $($copy:tt)*)) => {
+        $crate::toon_unexpected!($colon);
+    };
+    (@object $object:ident ($($key:tt)*) (, $($rest:tt)*) ($comma:tt $($copy:tt)*)) => {
+        $crate::toon_unexpected!($comma);
+    };
+    (@object $object:ident () (($key:expr) : $($rest:tt)*) $copy:tt) => {
+        $crate::toon_internal!(@object $object ($key) (: $($rest)*) (: $($rest)*));
+    };
+    (@object $object:ident ($($key:tt)*) (: $($unexpected:tt)+) $copy:tt) => {
+        $crate::toon_expect_expr_comma!($($unexpected)+);
+    };
+    (@object $object:ident ($($key:tt)*) ($tt:tt $($rest:tt)*) $copy:tt) => {
+        $crate::toon_internal!(@object $object ($($key)* $tt) ($($rest)*) ($($rest)*));
+    };
+
+    // Values.
+
+    (null) => {
+        $crate::Value::Null
+    };
+    (true) => {
+        $crate::Value::Bool(true)
+    };
+    (false) => {
+        $crate::Value::Bool(false)
+    };
+    ([]) => {
+        $crate::Value::Array($crate::__private::vec![])
+    };
+    ([ $($tt:tt)+ ]) => {
+        $crate::Value::Array($crate::toon_internal!(@array [] $($tt)+))
+    };
+    ({}) => {
+        $crate::Value::Object($crate::Map::default())
+    };
+    ({ $($tt:tt)+ }) => {
+        $crate::Value::Object({
+            let mut object = $crate::Map::default();
+            $crate::toon_internal!(@object object () ($($tt)+) ($($tt)+));
+            object
+        })
+    };
+    ($other:expr) => {
+        $crate::to_value(&$other).unwrap()
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! toon_unexpected {
+    () => {};
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! toon_expect_expr_comma {
+    ($e:expr , $($tt:tt)*) => {};
+}
@@ -0,0 +1,152 @@
+//! The order-preserving map behind [`Value::Object`](crate::Value::Object).
+
+use core::fmt;
+use core::hash::Hash;
+use core::ops::{Deref, DerefMut};
+use foldhash::fast::RandomState;
+use indexmap::IndexMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// An order-preserving map type used for TOON objects.
+///
+/// Wraps an [`IndexMap`] to preserve the insertion order of keys, which is important
+/// for maintaining consistent serialization output, and dereferences to it for the
+/// full `IndexMap` API. The hasher is the same with and without the `std` feature, so
+/// enabling `std` elsewhere in a build never changes the type.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::Map;
+///
+/// let mut map = Map::new();
+/// map.insert("b", 1);
+/// map.insert("a", 2);
+///
+/// assert_eq!(map.keys().copied().collect::<Vec<_>>(), ["b", "a"]);
+/// assert_eq!(map.shift_remove("b"), Some(1));
+/// ```
+pub struct Map<K, V> {
+    map: IndexMap<K, V, RandomState>,
+}
+
+impl<K, V> Map<K, V> {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Map {
+            map: IndexMap::default(),
+        }
+    }
+
+    /// Creates an empty map with room for at least `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Map {
+            map: IndexMap::with_capacity_and_hasher(capacity, RandomState::default()),
+        }
+    }
+}
+
+impl<K, V> Default for Map<K, V> {
+    fn default() -> Self {
+        Map::new()
+    }
+}
+
+impl<K: Clone, V: Clone> Clone for Map<K, V> {
+    fn clone(&self) -> Self {
+        Map {
+            map: self.map.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Map<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl<K: Hash + Eq, V: PartialEq> PartialEq for Map<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<K: Hash + Eq, V: Eq> Eq for Map<K, V> {}
+
+impl<K, V> Deref for Map<K, V> {
+    type Target = IndexMap<K, V, RandomState>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl<K, V> DerefMut for Map<K, V> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.map
+    }
+}
+
+impl<K: Hash + Eq, V, S> From<IndexMap<K, V, S>> for Map<K, V> {
+    fn from(map: IndexMap<K, V, S>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<K: Hash + Eq, V> FromIterator<(K, V)> for Map<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Map {
+            map: IndexMap::from_iter(iter),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> Extend<(K, V)> for Map<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.map.extend(iter);
+    }
+}
+
+impl<K, V> IntoIterator for Map<K, V> {
+    type Item = (K, V);
+    type IntoIter = indexmap::map::IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.into_iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a Map<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = indexmap::map::Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut Map<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = indexmap::map::IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.iter_mut()
+    }
+}
+
+impl<K: Serialize, V: Serialize> Serialize for Map<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.map.serialize(serializer)
+    }
+}
+
+impl<'de, K, V> Deserialize<'de> for Map<K, V>
+where
+    K: Deserialize<'de> + Hash + Eq,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        IndexMap::deserialize(deserializer).map(|map| Map { map })
+    }
+}
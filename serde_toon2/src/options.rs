@@ -1,27 +1,32 @@
 //! Configuration options for TOON serialization and deserialization.
 
-use std::fmt;
+use core::fmt;
 
 /// Options for controlling TOON serialization behavior.
 ///
 /// # Examples
 ///
 /// ```
-/// use serde_toon2::{EncoderOptions, Delimiter, KeyFolding};
+/// use serde_toon2::{EncoderOptions, Delimiter, Indent, KeyFolding, LineEnding};
 ///
 /// let opts = EncoderOptions {
-///     indent: 4,
+///     indent: Indent::Spaces(4),
 ///     delimiter: Delimiter::Pipe,
 ///     key_folding: KeyFolding::Off,
 ///     flatten_depth: usize::MAX,
+///     sort_keys: false,
+///     line_ending: LineEnding::Lf,
+///     trailing_newline: false,
 /// };
 /// ```
 #[derive(Debug, Clone)]
 pub struct EncoderOptions {
-    /// Number of spaces per indentation level.
+    /// Indentation written for each nesting level.
     ///
-    /// Default: `2`
-    pub indent: usize,
+    /// [`Indent::Auto`] is written as two spaces.
+    ///
+    /// Default: `Indent::Spaces(2)`
+    pub indent: Indent,
 
     /// Delimiter to use for array elements.
     ///
@@ -37,15 +42,69 @@ pub struct EncoderOptions {
     ///
     /// Default: `usize::MAX` (inline everything possible)
     pub flatten_depth: usize,
+
+    /// Whether to write object keys in sorted order instead of insertion order.
+    ///
+    /// Keys are compared by their UTF-8 bytes.
+    ///
+    /// Default: `false`
+    pub sort_keys: bool,
+
+    /// Line break written between lines.
+    ///
+    /// Default: [`LineEnding::Lf`]
+    pub line_ending: LineEnding,
+
+    /// Whether to end the document with a line break.
+    ///
+    /// Default: `false`
+    pub trailing_newline: bool,
 }
 
 impl Default for EncoderOptions {
     fn default() -> Self {
         EncoderOptions {
-            indent: 2,
+            indent: Indent::Spaces(2),
+            delimiter: Delimiter::Comma,
+            key_folding: KeyFolding::Off,
+            flatten_depth: usize::MAX,
+            sort_keys: false,
+            line_ending: LineEnding::Lf,
+            trailing_newline: false,
+        }
+    }
+}
+
+impl EncoderOptions {
+    /// Returns options that produce a canonical encoding.
+    ///
+    /// Equal values always encode to byte-identical TOON with these options, regardless
+    /// of the insertion order of their keys: keys are sorted, the layout uses the
+    /// default indentation and delimiter without key folding, and numbers are written
+    /// in their normalised form (`1.0` as `1`, `-0.0` as `0`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::{from_str, to_string_with_options, EncoderOptions, Value};
+    ///
+    /// let a: Value = from_str("b: 1\na: 2.0").unwrap();
+    /// let b: Value = from_str("a: 2\nb: 1").unwrap();
+    ///
+    /// let a = to_string_with_options(&a, EncoderOptions::canonical()).unwrap();
+    /// let b = to_string_with_options(&b, EncoderOptions::canonical()).unwrap();
+    /// assert_eq!(a, "a: 2\nb: 1");
+    /// assert_eq!(a, b);
+    /// ```
+    pub fn canonical() -> Self {
+        EncoderOptions {
+            indent: Indent::Spaces(2),
             delimiter: Delimiter::Comma,
             key_folding: KeyFolding::Off,
             flatten_depth: usize::MAX,
+            sort_keys: true,
+            line_ending: LineEnding::Lf,
+            trailing_newline: false,
         }
     }
 }
@@ -55,21 +114,24 @@ impl Default for EncoderOptions {
 /// # Examples
 ///
 /// ```
-/// use serde_toon2::{DecoderOptions, PathExpansion};
+/// use serde_toon2::{DecoderOptions, Indent, PathExpansion};
 ///
 /// // Enable strict validation
 /// let opts = DecoderOptions {
-///     indent: 2,
+///     indent: Indent::Spaces(2),
 ///     strict: true,
 ///     expand_paths: PathExpansion::Off,
+///     repair: false,
 /// };
 /// ```
 #[derive(Debug, Clone)]
 pub struct DecoderOptions {
-    /// Expected number of spaces per indentation level.
+    /// Indentation used for each nesting level of the input.
+    ///
+    /// With [`Indent::Auto`], the unit is taken from the first indented line.
     ///
-    /// Default: `2`
-    pub indent: usize,
+    /// Default: `Indent::Spaces(2)`
+    pub indent: Indent,
 
     /// Enable strict validation mode.
     ///
@@ -82,14 +144,78 @@ pub struct DecoderOptions {
     ///
     /// Default: [`PathExpansion::Off`]
     pub expand_paths: PathExpansion,
+
+    /// Fix common mistakes in nearly valid input instead of rejecting it.
+    ///
+    /// See the [`repair`](crate::repair) module for the fixes applied. Use
+    /// [`repair::from_str_repaired`](crate::repair::from_str_repaired) to find out
+    /// which ones were needed.
+    ///
+    /// Default: `false`
+    pub repair: bool,
 }
 
 impl Default for DecoderOptions {
     fn default() -> Self {
         DecoderOptions {
-            indent: 2,
+            indent: Indent::Spaces(2),
             strict: false,
             expand_paths: PathExpansion::Off,
+            repair: false,
+        }
+    }
+}
+
+/// The indentation used for one nesting level.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::{from_str_with_options, to_string_with_options};
+/// use serde_toon2::{DecoderOptions, EncoderOptions, Indent, Value};
+///
+/// let value: Value = serde_toon2::from_str("user:\n  name: Ada").unwrap();
+///
+/// let opts = EncoderOptions {
+///     indent: Indent::Tabs,
+///     ..Default::default()
+/// };
+/// let toon = to_string_with_options(&value, opts).unwrap();
+/// assert_eq!(toon, "user:\n\tname: Ada");
+///
+/// let opts = DecoderOptions {
+///     indent: Indent::Auto,
+///     ..Default::default()
+/// };
+/// let decoded: Value = from_str_with_options(&toon, opts.clone()).unwrap();
+/// assert_eq!(decoded, value);
+///
+/// let decoded: Value = from_str_with_options("user:\n    name: Ada", opts).unwrap();
+/// assert_eq!(decoded, value);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    /// A fixed number of spaces per level.
+    Spaces(usize),
+    /// One tab per level.
+    Tabs,
+    /// Detect the indentation when decoding: a tab if the first indented line starts
+    /// with one, otherwise as many spaces as that line starts with.
+    Auto,
+}
+
+impl From<usize> for Indent {
+    fn from(spaces: usize) -> Self {
+        Indent::Spaces(spaces)
+    }
+}
+
+impl fmt::Display for Indent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Indent::Spaces(n) => write!(f, "{} spaces", n),
+            Indent::Tabs => write!(f, "tabs"),
+            Indent::Auto => write!(f, "auto"),
         }
     }
 }
@@ -208,6 +334,52 @@ impl fmt::Display for Delimiter {
     }
 }
 
+/// Line break written by the encoder.
+///
+/// The decoder accepts both, and rejects a carriage return that is not followed by a
+/// line feed.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::{to_string_with_options, EncoderOptions, LineEnding, Value};
+///
+/// let value: Value = serde_toon2::from_str("a: 1\nb: 2").unwrap();
+/// let opts = EncoderOptions {
+///     line_ending: LineEnding::CrLf,
+///     trailing_newline: true,
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(to_string_with_options(&value, opts).unwrap(), "a: 1\r\nb: 2\r\n");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`, as used on Unix.
+    Lf,
+    /// `\r\n`, as used on Windows.
+    CrLf,
+}
+
+impl LineEnding {
+    /// Returns the line break as a string slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::LineEnding;
+    ///
+    /// assert_eq!(LineEnding::Lf.as_str(), "\n");
+    /// assert_eq!(LineEnding::CrLf.as_str(), "\r\n");
+    /// ```
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
 /// Controls whether nested object keys are compressed during serialization.
 ///
 /// # Examples
@@ -0,0 +1,670 @@
+//! A small path language for selecting parts of a [`Value`].
+//!
+//! Expressions are close to JSONPath, with the leading `$` optional:
+//!
+//! | Expression         | Selects                                                  |
+//! |--------------------|----------------------------------------------------------|
+//! | `users`, `.users`  | the `users` field of the root object                     |
+//! | `["first name"]`   | a field whose name needs quoting                         |
+//! | `users[0]`         | the first element; negative indexes count from the end   |
+//! | `users[*]`, `.*`   | every element of an array or value of an object          |
+//! | `users[1:3]`       | a slice, as `start:end:step` with each part optional     |
+//! | `..name`           | `name` fields at any depth; `..*` selects every value    |
+//! | `items[?price>10]` | the elements, or object values, a filter accepts         |
+//!
+//! Filters compare paths relative to the element, written `@.price` or just `price`,
+//! against literals (numbers, quoted strings, `true`, `false` and `null`) or other
+//! paths, with `==`, `!=`, `<`, `<=`, `>` and `>=`. They combine with `&&`, `||`, `!`
+//! and parentheses. A path on its own, as in `[?active]`, accepts elements where it
+//! exists and is neither `null` nor `false`. Numbers compare by value and strings by
+//! their characters; ordering a number against a string is always false.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde_toon2::{Value, from_str, query};
+//!
+//! let doc: Value = from_str("items[3]{name,price}:\n  pen,2\n  lamp,35\n  desk,120").unwrap();
+//!
+//! let names = query::select(&doc, "items[?price>10].name").unwrap();
+//! assert_eq!(names, [&Value::from("lamp"), &Value::from("desk")]);
+//!
+//! let last = query::select(&doc, "items[-1].price").unwrap();
+//! assert_eq!(last, [&Value::from(120i64)]);
+//! ```
+
+use crate::error::{Error, ErrorKind, Result};
+use crate::value::Value;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::str::FromStr;
+
+/// A parsed path expression.
+///
+/// Parse once with [`Query::parse`] to run the same expression over many values.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::query::Query;
+/// use serde_toon2::toon;
+///
+/// let query: Query = "..id".parse().unwrap();
+/// let doc = toon!({ "id": 1, "children": [{ "id": 2 }, { "id": 3 }] });
+/// assert_eq!(query.select_cloned(&doc), [toon!(1), toon!(2), toon!(3)]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    /// Applies the selector to the current values.
+    Child(Selector),
+    /// Applies the selector to the current values and all of their descendants.
+    Descendant(Selector),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    Index(i64),
+    Wildcard,
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: i64,
+    },
+    Filter(Filter),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Or(Box<Filter>, Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Truthy(Vec<Step>),
+    Compare(Operand, CompareOp, Operand),
+}
+
+/// One step of a path inside a filter, which selects at most one value.
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Name(String),
+    Index(i64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Path(Vec<Step>),
+    Literal(Value),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Query {
+    /// Parses a path expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`ErrorKind::InvalidQuery`] error naming the position of the first
+    /// problem.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::query::Query;
+    ///
+    /// assert!(Query::parse("users[*].name").is_ok());
+    /// let err = Query::parse("users[*").unwrap_err();
+    /// assert_eq!(err.to_string(), "Expected ']' at position 7 of the query");
+    /// ```
+    pub fn parse(expr: &str) -> Result<Self> {
+        let mut parser = Parser { expr, pos: 0 };
+        let query = parser.query()?;
+        if parser.pos < expr.len() {
+            return Err(parser.error("Unexpected character"));
+        }
+        Ok(query)
+    }
+
+    /// Returns the values the expression selects, in document order.
+    pub fn select<'v>(&self, value: &'v Value) -> Vec<&'v Value> {
+        let mut current = alloc::vec![value];
+        for segment in &self.segments {
+            let mut next = Vec::new();
+            for value in current {
+                match segment {
+                    Segment::Child(selector) => selector.apply(value, &mut next),
+                    Segment::Descendant(selector) => apply_descendants(selector, value, &mut next),
+                }
+            }
+            current = next;
+        }
+        current
+    }
+
+    /// Returns copies of the values the expression selects, in document order.
+    pub fn select_cloned(&self, value: &Value) -> Vec<Value> {
+        self.select(value).into_iter().cloned().collect()
+    }
+
+    /// Returns whether the expression selects at most one value whatever it is run on,
+    /// because it only uses names and indexes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::query::Query;
+    ///
+    /// assert!(Query::parse("users[0].name").unwrap().is_singular());
+    /// assert!(!Query::parse("users[*].name").unwrap().is_singular());
+    /// ```
+    pub fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| {
+            matches!(
+                segment,
+                Segment::Child(Selector::Name(_) | Selector::Index(_))
+            )
+        })
+    }
+}
+
+impl FromStr for Query {
+    type Err = Error;
+
+    fn from_str(expr: &str) -> Result<Self> {
+        Query::parse(expr)
+    }
+}
+
+/// Parses `expr` and returns the values it selects from `value`.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::{query, toon};
+///
+/// let doc = toon!({ "users": [{ "name": "Ada" }, { "name": "Bob" }] });
+/// let names = query::select(&doc, "users[*].name").unwrap();
+/// assert_eq!(names, [&toon!("Ada"), &toon!("Bob")]);
+/// ```
+pub fn select<'v>(value: &'v Value, expr: &str) -> Result<Vec<&'v Value>> {
+    Ok(Query::parse(expr)?.select(value))
+}
+
+/// Parses `expr` and returns copies of the values it selects from `value`.
+pub fn select_cloned(value: &Value, expr: &str) -> Result<Vec<Value>> {
+    Ok(Query::parse(expr)?.select_cloned(value))
+}
+
+fn apply_descendants<'v>(selector: &Selector, value: &'v Value, out: &mut Vec<&'v Value>) {
+    selector.apply(value, out);
+    match value {
+        Value::Array(arr) => arr
+            .iter()
+            .for_each(|item| apply_descendants(selector, item, out)),
+        Value::Object(obj) => obj
+            .values()
+            .for_each(|item| apply_descendants(selector, item, out)),
+        _ => {}
+    }
+}
+
+impl Selector {
+    fn apply<'v>(&self, value: &'v Value, out: &mut Vec<&'v Value>) {
+        match (self, value) {
+            (Selector::Name(name), Value::Object(obj)) => out.extend(obj.get(name)),
+            (Selector::Index(index), Value::Array(arr)) => out.extend(element(arr, *index)),
+            (Selector::Wildcard, Value::Array(arr)) => out.extend(arr),
+            (Selector::Wildcard, Value::Object(obj)) => out.extend(obj.values()),
+            (Selector::Slice { start, end, step }, Value::Array(arr)) => {
+                slice(arr, *start, *end, *step, out)
+            }
+            (Selector::Filter(filter), Value::Array(arr)) => {
+                out.extend(arr.iter().filter(|item| filter.accepts(item)))
+            }
+            (Selector::Filter(filter), Value::Object(obj)) => {
+                out.extend(obj.values().filter(|item| filter.accepts(item)))
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns the element at `index`, counting from the end when it is negative.
+fn element(arr: &[Value], index: i64) -> Option<&Value> {
+    let index = if index < 0 {
+        arr.len().checked_sub(index.unsigned_abs() as usize)?
+    } else {
+        index as usize
+    };
+    arr.get(index)
+}
+
+/// Pushes the elements of a slice with Python's semantics: negative bounds count from
+/// the end, and a negative step walks backwards from the end by default.
+fn slice<'v>(
+    arr: &'v [Value],
+    start: Option<i64>,
+    end: Option<i64>,
+    step: i64,
+    out: &mut Vec<&'v Value>,
+) {
+    let len = arr.len() as i64;
+    let bound = |index: i64, low: i64, high: i64| {
+        let index = if index < 0 { index + len } else { index };
+        index.clamp(low, high)
+    };
+
+    // Steps larger than the array just stop after the first element
+    let stride = usize::try_from(step.unsigned_abs()).unwrap_or(usize::MAX);
+    if step > 0 {
+        let start = start.map_or(0, |i| bound(i, 0, len));
+        let end = end.map_or(len, |i| bound(i, 0, len));
+        out.extend(
+            arr[..end as usize]
+                .iter()
+                .skip(start as usize)
+                .step_by(stride),
+        );
+    } else {
+        let start = start.map_or(len - 1, |i| bound(i, -1, len - 1));
+        let end = end.map_or(-1, |i| bound(i, -1, len - 1));
+        if start > end {
+            let items = &arr[(end + 1) as usize..=start as usize];
+            out.extend(items.iter().rev().step_by(stride));
+        }
+    }
+}
+
+impl Filter {
+    fn accepts(&self, item: &Value) -> bool {
+        match self {
+            Filter::Or(a, b) => a.accepts(item) || b.accepts(item),
+            Filter::And(a, b) => a.accepts(item) && b.accepts(item),
+            Filter::Not(filter) => !filter.accepts(item),
+            Filter::Truthy(path) => !matches!(
+                resolve(item, path),
+                None | Some(Value::Null | Value::Bool(false))
+            ),
+            Filter::Compare(left, op, right) => compare(left.eval(item), *op, right.eval(item)),
+        }
+    }
+}
+
+impl Operand {
+    fn eval<'v>(&'v self, item: &'v Value) -> Option<&'v Value> {
+        match self {
+            Operand::Path(path) => resolve(item, path),
+            Operand::Literal(value) => Some(value),
+        }
+    }
+}
+
+fn resolve<'v>(item: &'v Value, path: &[Step]) -> Option<&'v Value> {
+    path.iter()
+        .try_fold(item, |value, step| match (step, value) {
+            (Step::Name(name), Value::Object(obj)) => obj.get(name),
+            (Step::Index(index), Value::Array(arr)) => element(arr, *index),
+            _ => None,
+        })
+}
+
+/// Compares two operands. A missing value only equals another missing value, and
+/// values of different types are unequal and unordered.
+fn compare(left: Option<&Value>, op: CompareOp, right: Option<&Value>) -> bool {
+    let ordering = match (left, right) {
+        (None, None) => Some(Ordering::Equal),
+        (Some(a @ Value::Number(_)), Some(b @ Value::Number(_)))
+        | (Some(a @ Value::String(_)), Some(b @ Value::String(_))) => Some(a.cmp(b)),
+        (Some(a), Some(b)) if a == b => Some(Ordering::Equal),
+        _ => None,
+    };
+    match op {
+        CompareOp::Eq => ordering == Some(Ordering::Equal),
+        CompareOp::Ne => ordering != Some(Ordering::Equal),
+        CompareOp::Lt => ordering == Some(Ordering::Less),
+        CompareOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        CompareOp::Gt => ordering == Some(Ordering::Greater),
+        CompareOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+    }
+}
+
+/// A recursive descent parser over the expression's bytes. Positions in errors are
+/// byte offsets into the expression.
+struct Parser<'e> {
+    expr: &'e str,
+    pos: usize,
+}
+
+impl<'e> Parser<'e> {
+    fn error(&self, message: &str) -> Error {
+        Error::new(
+            ErrorKind::InvalidQuery,
+            format!("{} at position {} of the query", message, self.pos),
+        )
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.expr.as_bytes().get(self.pos).copied()
+    }
+
+    fn rest(&self) -> &'e str {
+        &self.expr[self.pos..]
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<()> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", token)))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn query(&mut self) -> Result<Query> {
+        let mut segments = Vec::new();
+        self.eat("$");
+        if self.rest() == "." {
+            // `.` on its own selects the whole document, as in jq
+            self.pos += 1;
+            return Ok(Query { segments });
+        }
+        if self.at_name() {
+            segments.push(Segment::Child(Selector::Name(self.name())));
+        }
+
+        loop {
+            if self.eat("..") {
+                let selector = match self.peek() {
+                    Some(b'[') => self.bracket()?,
+                    _ => self.dot_selector()?,
+                };
+                segments.push(Segment::Descendant(selector));
+            } else if self.eat(".") {
+                segments.push(Segment::Child(self.dot_selector()?));
+            } else if self.peek() == Some(b'[') {
+                segments.push(Segment::Child(self.bracket()?));
+            } else {
+                return Ok(Query { segments });
+            }
+        }
+    }
+
+    /// Parses what may follow a dot: a name, a quoted name or `*`.
+    fn dot_selector(&mut self) -> Result<Selector> {
+        match self.peek() {
+            Some(b'*') => {
+                self.pos += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some(b'"' | b'\'') => Ok(Selector::Name(self.string()?)),
+            _ if self.at_name() => Ok(Selector::Name(self.name())),
+            _ => Err(self.error("Expected a name")),
+        }
+    }
+
+    fn bracket(&mut self) -> Result<Selector> {
+        self.expect("[")?;
+        self.skip_whitespace();
+        let selector = match self.peek() {
+            Some(b'*') => {
+                self.pos += 1;
+                Selector::Wildcard
+            }
+            Some(b'"' | b'\'') => Selector::Name(self.string()?),
+            Some(b'?') => {
+                self.pos += 1;
+                Selector::Filter(self.or()?)
+            }
+            _ => self.index_or_slice()?,
+        };
+        self.skip_whitespace();
+        self.expect("]")?;
+        Ok(selector)
+    }
+
+    fn index_or_slice(&mut self) -> Result<Selector> {
+        let start = self.optional_integer()?;
+        self.skip_whitespace();
+        if !self.eat(":") {
+            return start
+                .map(Selector::Index)
+                .ok_or_else(|| self.error("Expected an index, a slice, '*', a name or a filter"));
+        }
+        self.skip_whitespace();
+        let end = self.optional_integer()?;
+        self.skip_whitespace();
+        let step = if self.eat(":") {
+            self.skip_whitespace();
+            self.optional_integer()?.unwrap_or(1)
+        } else {
+            1
+        };
+        if step == 0 {
+            return Err(self.error("Slice step cannot be zero"));
+        }
+        Ok(Selector::Slice { start, end, step })
+    }
+
+    fn optional_integer(&mut self) -> Result<Option<i64>> {
+        let start = self.pos;
+        self.eat("-");
+        while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        match &self.expr[start..self.pos] {
+            "" => Ok(None),
+            digits => digits.parse().map(Some).map_err(|_| {
+                self.pos = start;
+                self.error("Invalid integer")
+            }),
+        }
+    }
+
+    fn at_name(&self) -> bool {
+        self.rest()
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_')
+    }
+
+    /// Parses an unquoted name: letters, digits, `_` and `-`, not starting with a digit
+    /// or `-`.
+    fn name(&mut self) -> String {
+        let start = self.pos;
+        while let Some(c) = self.rest().chars().next()
+            && (c.is_alphanumeric() || c == '_' || c == '-')
+        {
+            self.pos += c.len_utf8();
+        }
+        String::from(&self.expr[start..self.pos])
+    }
+
+    /// Parses a string in single or double quotes, with `\` escaping the next character.
+    fn string(&mut self) -> Result<String> {
+        let quote = self.rest().chars().next().unwrap();
+        let mut out = String::new();
+        let mut chars = self.rest().char_indices().skip(1);
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => out.push('\n'),
+                    Some((_, 't')) => out.push('\t'),
+                    Some((_, 'r')) => out.push('\r'),
+                    Some((_, escaped)) => out.push(escaped),
+                    None => break,
+                },
+                c if c == quote => {
+                    self.pos += i + 1;
+                    return Ok(out);
+                }
+                c => out.push(c),
+            }
+        }
+        Err(self.error("Unterminated string"))
+    }
+
+    fn or(&mut self) -> Result<Filter> {
+        let mut filter = self.and()?;
+        while self.eat_operator("||") {
+            filter = Filter::Or(Box::new(filter), Box::new(self.and()?));
+        }
+        Ok(filter)
+    }
+
+    fn and(&mut self) -> Result<Filter> {
+        let mut filter = self.unary()?;
+        while self.eat_operator("&&") {
+            filter = Filter::And(Box::new(filter), Box::new(self.unary()?));
+        }
+        Ok(filter)
+    }
+
+    fn eat_operator(&mut self, op: &str) -> bool {
+        self.skip_whitespace();
+        self.eat(op)
+    }
+
+    fn unary(&mut self) -> Result<Filter> {
+        self.skip_whitespace();
+        if self.peek() == Some(b'!') && !self.rest().starts_with("!=") {
+            self.pos += 1;
+            return Ok(Filter::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("(") {
+            let filter = self.or()?;
+            self.skip_whitespace();
+            self.expect(")")?;
+            return Ok(filter);
+        }
+
+        let left = self.operand()?;
+        self.skip_whitespace();
+        let Some(op) = self.compare_op() else {
+            return match left {
+                Operand::Path(path) => Ok(Filter::Truthy(path)),
+                Operand::Literal(_) => Err(self.error("Expected a comparison")),
+            };
+        };
+        self.skip_whitespace();
+        let right = self.operand()?;
+        Ok(Filter::Compare(left, op, right))
+    }
+
+    fn compare_op(&mut self) -> Option<CompareOp> {
+        // Two-character operators first, so `<=` is not read as `<`
+        let ops = [
+            ("==", CompareOp::Eq),
+            ("!=", CompareOp::Ne),
+            ("<=", CompareOp::Le),
+            (">=", CompareOp::Ge),
+            ("<", CompareOp::Lt),
+            (">", CompareOp::Gt),
+        ];
+        ops.into_iter()
+            .find(|(token, _)| self.eat(token))
+            .map(|(_, op)| op)
+    }
+
+    fn operand(&mut self) -> Result<Operand> {
+        match self.peek() {
+            Some(b'@') => {
+                self.pos += 1;
+                self.relative_path(Vec::new()).map(Operand::Path)
+            }
+            Some(b'"' | b'\'') => Ok(Operand::Literal(Value::String(self.string()?))),
+            Some(b'-' | b'0'..=b'9') => self.number().map(Operand::Literal),
+            _ if self.at_name() => {
+                let name = self.name();
+                let literal = match name.as_str() {
+                    "true" => Some(Value::Bool(true)),
+                    "false" => Some(Value::Bool(false)),
+                    "null" => Some(Value::Null),
+                    _ => None,
+                };
+                match literal {
+                    Some(value) => Ok(Operand::Literal(value)),
+                    None => self
+                        .relative_path(alloc::vec![Step::Name(name)])
+                        .map(Operand::Path),
+                }
+            }
+            _ => Err(self.error("Expected a path or a value")),
+        }
+    }
+
+    /// Parses the rest of a path inside a filter, which may only use names and indexes.
+    fn relative_path(&mut self, mut steps: Vec<Step>) -> Result<Vec<Step>> {
+        loop {
+            if self.rest().starts_with("..") {
+                return Err(self.error("Paths in filters may only use names and indexes"));
+            } else if self.eat(".") {
+                match self.dot_selector()? {
+                    Selector::Name(name) => steps.push(Step::Name(name)),
+                    _ => return Err(self.error("Paths in filters may only use names and indexes")),
+                }
+            } else if self.peek() == Some(b'[') {
+                let start = self.pos;
+                match self.bracket()? {
+                    Selector::Name(name) => steps.push(Step::Name(name)),
+                    Selector::Index(index) => steps.push(Step::Index(index)),
+                    _ => {
+                        self.pos = start;
+                        return Err(self.error("Paths in filters may only use names and indexes"));
+                    }
+                }
+            } else {
+                return Ok(steps);
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Value> {
+        let start = self.pos;
+        self.eat("-");
+        while self
+            .peek()
+            .is_some_and(|b| b.is_ascii_digit() || matches!(b, b'.' | b'e' | b'E' | b'+' | b'-'))
+        {
+            self.pos += 1;
+        }
+        let text = &self.expr[start..self.pos];
+        let number = text
+            .parse::<i64>()
+            .map(Value::from)
+            .or_else(|_| text.parse::<u64>().map(Value::from))
+            .or_else(|_| text.parse::<f64>().map(Value::from));
+        number.map_err(|_| {
+            self.pos = start;
+            self.error("Invalid number")
+        })
+    }
+}
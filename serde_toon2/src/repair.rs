@@ -0,0 +1,96 @@
+//! Lenient decoding of nearly valid TOON, such as LLM output.
+//!
+//! With [`DecoderOptions::repair`] enabled, the decoder fixes these common mistakes
+//! instead of rejecting the document:
+//!
+//! - **Code fences**: a Markdown fence (` ``` ` or ` ```toon `) on the first line and a
+//!   closing fence on the last line are removed.
+//! - **Array counts**: when an array holds a different number of items than its `[N]`
+//!   header declares, the items that are present are kept.
+//! - **Trailing delimiters**: a delimiter at the end of a tabular row or inline array,
+//!   which would otherwise add an empty value, is dropped.
+//! - **Indentation**: lines are nested by comparing their indentation with the lines
+//!   above them, so 2- and 4-space indentation can be mixed in one document.
+//!
+//! [`from_str_repaired`] returns the decoded value together with every [`Repair`]
+//! applied, ordered by line.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde_toon2::repair::{from_str_repaired, RepairKind};
+//! use serde_toon2::DecoderOptions;
+//!
+//! let toon = "```toon\nusers[3]{id,name}:\n  1,Ada,\n  2,Bob\n```";
+//! let out = from_str_repaired(toon, DecoderOptions::default()).unwrap();
+//!
+//! assert_eq!(out.value.as_object().unwrap()["users"].as_array().unwrap().len(), 2);
+//! assert_eq!(out.repairs[0].kind, RepairKind::CodeFence);
+//! assert_eq!(out.repairs[1].kind, RepairKind::Count { declared: 3, received: 2 });
+//! assert_eq!(out.repairs[2].kind, RepairKind::TrailingDelimiter);
+//! assert_eq!(out.repairs[3].kind, RepairKind::CodeFence);
+//! ```
+//!
+//! [`DecoderOptions::repair`]: crate::DecoderOptions::repair
+
+use crate::de::parse_repaired;
+use crate::error::Result;
+use crate::options::DecoderOptions;
+use crate::value::Value;
+use alloc::vec::Vec;
+
+/// The kind of fix applied to the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairKind {
+    /// A Markdown code fence line was removed.
+    CodeFence,
+    /// An array held a different number of items than its header declared.
+    Count {
+        /// Number of items declared by the `[N]` header.
+        declared: usize,
+        /// Number of items found and kept.
+        received: usize,
+    },
+    /// A delimiter at the end of a row or inline array was dropped.
+    TrailingDelimiter,
+    /// A line's indentation did not match the indent size for its nesting level.
+    ///
+    /// Widths count spaces, or tabs with [`Indent::Tabs`](crate::Indent::Tabs) or when
+    /// [`Indent::Auto`](crate::Indent::Auto) detects tab indentation.
+    Indentation {
+        /// Width of the line's indentation in the input.
+        found: usize,
+        /// Width expected for the level the line was placed at.
+        expected: usize,
+    },
+}
+
+/// A single fix applied while decoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repair {
+    /// Line number the fix applies to. For count repairs, this is the array header.
+    pub line: usize,
+    /// What was fixed.
+    pub kind: RepairKind,
+}
+
+/// Output of [`from_str_repaired`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repaired {
+    /// The decoded value.
+    pub value: Value,
+    /// Fixes applied to the input, ordered by line.
+    pub repairs: Vec<Repair>,
+}
+
+/// Decodes a TOON string with repairs enabled and reports the repairs applied.
+///
+/// `options.repair` is ignored and always treated as `true`.
+pub fn from_str_repaired(s: &str, options: DecoderOptions) -> Result<Repaired> {
+    let options = DecoderOptions {
+        repair: true,
+        ..options
+    };
+    let (value, repairs) = parse_repaired(s, &options)?;
+    Ok(Repaired { value, repairs })
+}
@@ -0,0 +1,116 @@
+//! Byte-level scanning for structural characters outside quoted strings.
+//!
+//! Every structural character in TOON (`:`, delimiters, brackets, braces and quotes)
+//! is ASCII, and ASCII bytes never occur inside a multi-byte UTF-8 sequence, so the
+//! scanner works on bytes and every offset it returns is a valid `str` boundary.
+//! Lines without quotes take a `memchr` fast path.
+
+use alloc::vec::Vec;
+use memchr::{memchr, memchr_iter, memchr2};
+
+/// Bytes reported by [`Structurals`]. Quotes are tracked, not reported.
+static STRUCTURAL: [bool; 256] = {
+    let mut table = [false; 256];
+    table[b':' as usize] = true;
+    table[b',' as usize] = true;
+    table[b'|' as usize] = true;
+    table[b'\t' as usize] = true;
+    table[b'[' as usize] = true;
+    table[b']' as usize] = true;
+    table[b'{' as usize] = true;
+    table[b'}' as usize] = true;
+    table[b'"' as usize] = true;
+    table
+};
+
+/// Iterator over the structural bytes of a line that lie outside quoted strings,
+/// yielding each byte with its offset.
+pub(crate) struct Structurals<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    in_quotes: bool,
+}
+
+impl<'a> Structurals<'a> {
+    pub(crate) fn new(s: &'a str) -> Self {
+        Structurals {
+            bytes: s.as_bytes(),
+            pos: 0,
+            in_quotes: false,
+        }
+    }
+}
+
+impl Iterator for Structurals<'_> {
+    type Item = (usize, u8);
+
+    fn next(&mut self) -> Option<(usize, u8)> {
+        while self.pos < self.bytes.len() {
+            if self.in_quotes {
+                // Jump to the closing quote, skipping escaped characters
+                let offset = memchr2(b'"', b'\\', &self.bytes[self.pos..])?;
+                let i = self.pos + offset;
+                if self.bytes[i] == b'\\' {
+                    self.pos = i + 2;
+                } else {
+                    self.in_quotes = false;
+                    self.pos = i + 1;
+                }
+                continue;
+            }
+
+            let i = self.pos;
+            let byte = self.bytes[i];
+            self.pos += 1;
+            if !STRUCTURAL[byte as usize] {
+                continue;
+            }
+            if byte == b'"' {
+                self.in_quotes = true;
+                continue;
+            }
+            return Some((i, byte));
+        }
+        None
+    }
+}
+
+/// Returns the byte offset of the first `needle` outside quoted strings.
+pub(crate) fn find_unquoted(s: &str, needle: u8) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let candidate = memchr(needle, bytes)?;
+    if memchr(b'"', &bytes[..candidate]).is_none() {
+        return Some(candidate);
+    }
+    Structurals::new(s)
+        .find(|&(_, byte)| byte == needle)
+        .map(|(i, _)| i)
+}
+
+/// Returns the byte offset of the first colon outside quoted strings.
+pub(crate) fn find_colon(s: &str) -> Option<usize> {
+    find_unquoted(s, b':')
+}
+
+/// Splits `s` on `delimiter` outside quoted strings, trimming each part.
+pub(crate) fn split_unquoted(s: &str, delimiter: u8) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+
+    if memchr(b'"', s.as_bytes()).is_none() {
+        for i in memchr_iter(delimiter, s.as_bytes()) {
+            parts.push(s[start..i].trim());
+            start = i + 1;
+        }
+    } else {
+        for (i, byte) in Structurals::new(s) {
+            if byte == delimiter {
+                parts.push(s[start..i].trim());
+                start = i + 1;
+            }
+        }
+    }
+
+    parts.push(s[start..].trim());
+    parts
+}
@@ -1,207 +1,460 @@
+use crate::de::DOCUMENT_SEPARATOR;
 use crate::error::{Error, Result};
-use crate::options::{Delimiter, EncoderOptions};
-use crate::value::{Map, Number, Value};
+use crate::io::{self, Write};
+use crate::options::{Delimiter, EncoderOptions, Indent, KeyFolding, LineEnding};
+use crate::value::{Map, Number, Value, is_integral};
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem;
 use serde::ser;
-use std::io::Write;
 
-pub struct Serializer<W> {
+/// Controls how the serializer writes individual tokens of a TOON document.
+///
+/// The serializer decides the layout of the document (which lines exist and how deep
+/// they are nested) and calls into a formatter for every key, scalar, array header,
+/// delimiter and indentation it writes. Every method has a default implementation that
+/// produces standard TOON, so a custom formatter only overrides the hooks it cares
+/// about and can delegate to [`DefaultFormatter`] for the rest.
+///
+/// Formatters are responsible for keeping the output decodable: a formatter that skips
+/// quoting a string that needs it will produce a document that does not round-trip.
+///
+/// # Examples
+///
+/// An always-quote formatter:
+///
+/// ```
+/// use serde_toon2::ser::{Formatter, Serializer};
+/// use serde_toon2::{Delimiter, EncoderOptions, Value};
+/// use std::io::{self, Write};
+///
+/// struct AlwaysQuote;
+///
+/// impl Formatter for AlwaysQuote {
+///     fn write_string<W: ?Sized + Write>(
+///         &mut self,
+///         writer: &mut W,
+///         value: &str,
+///         _delimiter: Delimiter,
+///     ) -> io::Result<()> {
+///         self.write_quoted_string(writer, value)
+///     }
+/// }
+///
+/// let value = Value::from(vec!["a", "b"]);
+/// let mut buf = Vec::new();
+/// let mut ser = Serializer::with_formatter(&mut buf, EncoderOptions::default(), AlwaysQuote);
+/// ser.serialize_value(&value).unwrap();
+///
+/// assert_eq!(String::from_utf8(buf).unwrap(), "[2]: \"a\",\"b\"");
+/// ```
+pub trait Formatter {
+    /// Writes a `null` value.
+    fn write_null<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b"null")
+    }
+
+    /// Writes a boolean value.
+    fn write_bool<W: ?Sized + Write>(&mut self, writer: &mut W, value: bool) -> io::Result<()> {
+        writer.write_all(if value { b"true" } else { b"false" })
+    }
+
+    /// Writes a number in its normalised TOON form.
+    fn write_number<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        value: &Number,
+    ) -> io::Result<()> {
+        writer.write_all(format_number(value).as_bytes())
+    }
+
+    /// Writes a string value, quoting it only when it would otherwise be ambiguous.
+    ///
+    /// `delimiter` is the delimiter active in the surrounding array or document, which a
+    /// string must be quoted to contain.
+    fn write_string<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        value: &str,
+        delimiter: Delimiter,
+    ) -> io::Result<()> {
+        if needs_quoting(value, delimiter) {
+            self.write_quoted_string(writer, value)
+        } else {
+            writer.write_all(value.as_bytes())
+        }
+    }
+
+    /// Writes a string in double quotes, escaping it as needed.
+    fn write_quoted_string<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        value: &str,
+    ) -> io::Result<()> {
+        writer.write_all(b"\"")?;
+        write_escaped(writer, value)?;
+        writer.write_all(b"\"")
+    }
+
+    /// Writes an object key or tabular field name, without the following colon.
+    fn write_key<W: ?Sized + Write>(&mut self, writer: &mut W, key: &str) -> io::Result<()> {
+        if key_needs_quoting(key) {
+            self.write_quoted_string(writer, key)
+        } else {
+            writer.write_all(key.as_bytes())
+        }
+    }
+
+    /// Writes an array header such as `[3]` or `[2]{id,name}`, without the following colon.
+    ///
+    /// `fields` holds the column names of a tabular array.
+    fn write_array_header<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        len: usize,
+        delimiter: Delimiter,
+        fields: Option<&[String]>,
+    ) -> io::Result<()> {
+        write!(writer, "[{}{}]", len, delimiter.header_marker())?;
+        if let Some(fields) = fields {
+            writer.write_all(b"{")?;
+            for (i, field) in fields.iter().enumerate() {
+                if i > 0 {
+                    self.write_delimiter(writer, delimiter)?;
+                }
+                self.write_key(writer, field)?;
+            }
+            writer.write_all(b"}")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the delimiter between inline array values, tabular cells and field names.
+    fn write_delimiter<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        delimiter: Delimiter,
+    ) -> io::Result<()> {
+        writer.write_all(delimiter.as_str().as_bytes())
+    }
+
+    /// Writes the `- ` marker that starts an item of an expanded list.
+    fn write_list_marker<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b"- ")
+    }
+
+    /// Writes the indentation for a line nested `depth` levels deep.
+    ///
+    /// `indent` is [`EncoderOptions::indent`].
+    fn write_indent<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        depth: usize,
+        indent: Indent,
+    ) -> io::Result<()> {
+        const SPACES: &[u8] = b"                                ";
+        const TABS: &[u8] = b"\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t";
+        let (chars, mut remaining) = match indent {
+            Indent::Spaces(width) => (SPACES, depth * width),
+            Indent::Tabs => (TABS, depth),
+            Indent::Auto => (SPACES, depth * 2),
+        };
+        while remaining > 0 {
+            let n = remaining.min(chars.len());
+            writer.write_all(&chars[..n])?;
+            remaining -= n;
+        }
+        Ok(())
+    }
+
+    /// Writes the `---` line content that separates documents in a stream.
+    fn write_document_separator<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(DOCUMENT_SEPARATOR.as_bytes())
+    }
+
+    /// Writes a line break.
+    ///
+    /// `line_ending` is [`EncoderOptions::line_ending`].
+    fn write_newline<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        line_ending: LineEnding,
+    ) -> io::Result<()> {
+        writer.write_all(line_ending.as_str().as_bytes())
+    }
+}
+
+/// The formatter used by default, producing standard TOON.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultFormatter;
+
+impl Formatter for DefaultFormatter {}
+
+/// A TOON serializer.
+///
+/// Object fields and scalars are written to `W` as soon as they are serialized. Array
+/// elements are collected into [`Value`]s while the array can still be written inline,
+/// as an array of arrays or as a table, since any later element can rule those out.
+/// Once an element makes the array a list, the elements so far and every later one are
+/// written as they come. Arrays of unknown length are collected until they end. Key
+/// folding and key sorting need the whole document, so with [`KeyFolding::Safe`] or
+/// [`EncoderOptions::sort_keys`] the document is buffered and written once complete.
+pub struct Serializer<W, F = DefaultFormatter> {
     writer: W,
+    formatter: F,
     options: EncoderOptions,
     depth: usize,
     document_delimiter: Delimiter,
-    top_level_keys: std::collections::HashSet<String>,
+    top_level_keys: BTreeSet<String>,
+    /// Key of the object field whose value is serialized next.
+    pending_key: Option<String>,
+    /// Whether a line has been started, so the next line needs a line break first.
+    has_output: bool,
+    /// Number of documents written with [`Serializer::write_document`].
+    documents: usize,
 }
 
 impl<W: Write> Serializer<W> {
     pub fn new(writer: W, options: EncoderOptions) -> Self {
+        Serializer::with_formatter(writer, options, DefaultFormatter)
+    }
+}
+
+impl<W: Write, F: Formatter> Serializer<W, F> {
+    /// Creates a serializer that writes tokens through a custom [`Formatter`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Serialize;
+    /// use serde_toon2::ser::{Formatter, Serializer};
+    /// use serde_toon2::{EncoderOptions, Indent};
+    /// use std::io::{self, Write};
+    ///
+    /// struct Tabs;
+    ///
+    /// impl Formatter for Tabs {
+    ///     fn write_indent<W: ?Sized + Write>(
+    ///         &mut self,
+    ///         writer: &mut W,
+    ///         depth: usize,
+    ///         _indent: Indent,
+    ///     ) -> io::Result<()> {
+    ///         (0..depth).try_for_each(|_| writer.write_all(b"\t"))
+    ///     }
+    /// }
+    ///
+    /// #[derive(Serialize)]
+    /// struct User {
+    ///     name: String,
+    /// }
+    ///
+    /// #[derive(Serialize)]
+    /// struct Doc {
+    ///     user: User,
+    /// }
+    ///
+    /// let doc = Doc {
+    ///     user: User { name: "Ada".to_string() },
+    /// };
+    ///
+    /// let mut buf = Vec::new();
+    /// let mut ser = Serializer::with_formatter(&mut buf, EncoderOptions::default(), Tabs);
+    /// doc.serialize(&mut ser).unwrap();
+    ///
+    /// assert_eq!(String::from_utf8(buf).unwrap(), "user:\n\tname: Ada");
+    /// ```
+    pub fn with_formatter(writer: W, options: EncoderOptions, formatter: F) -> Self {
         let document_delimiter = options.delimiter;
         Serializer {
             writer,
+            formatter,
             options,
             depth: 0,
             document_delimiter,
-            top_level_keys: std::collections::HashSet::new(),
+            top_level_keys: BTreeSet::new(),
+            pending_key: None,
+            has_output: false,
+            documents: 0,
         }
     }
 
-    fn indent(&self) -> String {
-        " ".repeat(self.depth * self.options.indent)
+    /// Returns a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
     }
 
-    fn needs_quoting(&self, s: &str, active_delimiter: Delimiter) -> bool {
-        if s.is_empty() {
-            return true;
-        }
-
-        if s.starts_with(|c: char| c.is_whitespace()) || s.ends_with(|c: char| c.is_whitespace()) {
-            return true;
-        }
-
-        if s == "true" || s == "false" || s == "null" {
-            return true;
-        }
-
-        if s.starts_with('-') {
-            return true;
-        }
+    /// Unwraps the serializer, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
 
-        if s.contains(active_delimiter.as_char())
-            || s.contains(':')
-            || s.contains('"')
-            || s.contains('\\')
-            || s.contains('[')
-            || s.contains(']')
-            || s.contains('{')
-            || s.contains('}')
-            || s.contains('\n')
-            || s.contains('\r')
-            || s.contains('\t')
-        {
-            return true;
-        }
+    fn is_streaming(&self) -> bool {
+        self.options.key_folding == KeyFolding::Off && !self.options.sort_keys
+    }
 
-        if Self::looks_like_number(s) {
-            return true;
+    /// Starts a new line at the current depth.
+    fn begin_line(&mut self) -> Result<()> {
+        if self.has_output {
+            self.write_newline()?;
         }
+        self.has_output = true;
+        self.write_indent()
+    }
 
-        if Self::has_leading_zeros(s) {
-            return true;
+    /// Writes a scalar in the current position, preceded by its key inside an object.
+    fn emit_scalar(&mut self, write: impl FnOnce(&mut F, &mut W) -> io::Result<()>) -> Result<()> {
+        if let Some(key) = self.pending_key.take() {
+            self.begin_line()?;
+            self.write_key(&key)?;
+            self.writer.write_all(b": ")?;
         }
-
-        false
+        write(&mut self.formatter, &mut self.writer)?;
+        self.has_output = true;
+        Ok(())
     }
 
-    #[inline]
-    fn looks_like_number(s: &str) -> bool {
-        let bytes = s.as_bytes();
-        if bytes.is_empty() {
-            return false;
+    /// Writes a buffered value in the current position.
+    fn emit_value(&mut self, value: &Value) -> Result<()> {
+        if let Some(key) = self.pending_key.take() {
+            self.begin_line()?;
+            self.serialize_entry(value, Some(&key), self.document_delimiter)?;
+        } else {
+            self.serialize_value(value)?;
         }
+        self.has_output = true;
+        Ok(())
+    }
 
-        let mut i = 0;
-
-        // Optional leading minus
-        if bytes[i] == b'-' {
-            i += 1;
-            if i >= bytes.len() {
-                return false;
+    /// Writes the `key:` line opening a nested object, returning the depth it added.
+    fn open_object(&mut self) -> Result<usize> {
+        match self.pending_key.take() {
+            Some(key) => {
+                self.begin_line()?;
+                self.write_key(&key)?;
+                self.writer.write_all(b":")?;
+                self.depth += 1;
+                Ok(1)
             }
+            None => Ok(0),
         }
+    }
 
-        // Must have at least one digit
-        if !bytes[i].is_ascii_digit() {
-            return false;
-        }
-
-        // Integer part
-        while i < bytes.len() && bytes[i].is_ascii_digit() {
-            i += 1;
-        }
-
-        if i >= bytes.len() {
-            return true; // Just an integer
-        }
-
-        // Optional decimal part
-        if bytes[i] == b'.' {
-            i += 1;
-            if i >= bytes.len() {
-                return false; // Trailing dot
-            }
-            if !bytes[i].is_ascii_digit() {
-                return false; // No digits after dot
-            }
-            while i < bytes.len() && bytes[i].is_ascii_digit() {
-                i += 1;
-            }
-        }
+    fn write_indent(&mut self) -> Result<()> {
+        self.formatter
+            .write_indent(&mut self.writer, self.depth, self.options.indent)?;
+        Ok(())
+    }
 
-        if i >= bytes.len() {
-            return true; // Number with decimal
-        }
+    fn write_newline(&mut self) -> Result<()> {
+        self.formatter
+            .write_newline(&mut self.writer, self.options.line_ending)?;
+        Ok(())
+    }
 
-        // Optional exponent
-        if bytes[i] == b'e' || bytes[i] == b'E' {
-            i += 1;
-            if i >= bytes.len() {
-                return false;
-            }
-            // Optional sign
-            if bytes[i] == b'+' || bytes[i] == b'-' {
-                i += 1;
-                if i >= bytes.len() {
-                    return false;
-                }
-            }
-            // Must have at least one digit
-            if !bytes[i].is_ascii_digit() {
-                return false;
-            }
-            while i < bytes.len() && bytes[i].is_ascii_digit() {
-                i += 1;
-            }
-        }
+    fn write_list_marker(&mut self) -> Result<()> {
+        self.formatter.write_list_marker(&mut self.writer)?;
+        Ok(())
+    }
 
-        i == bytes.len()
+    fn write_key(&mut self, key: &str) -> Result<()> {
+        self.formatter.write_key(&mut self.writer, key)?;
+        Ok(())
     }
 
-    #[inline]
-    fn has_leading_zeros(s: &str) -> bool {
-        let bytes = s.as_bytes();
-        bytes.len() >= 2 && bytes[0] == b'0' && bytes[1].is_ascii_digit()
+    fn write_delimiter(&mut self, delimiter: Delimiter) -> Result<()> {
+        self.formatter
+            .write_delimiter(&mut self.writer, delimiter)?;
+        Ok(())
     }
 
-    fn escape_string(&self, s: &str) -> String {
-        let mut result = String::new();
-        for ch in s.chars() {
-            match ch {
-                '\\' => result.push_str("\\\\"),
-                '"' => result.push_str("\\\""),
-                '\n' => result.push_str("\\n"),
-                '\r' => result.push_str("\\r"),
-                '\t' => result.push_str("\\t"),
-                _ => result.push(ch),
+    fn write_primitive(&mut self, value: &Value, active_delimiter: Delimiter) -> Result<()> {
+        match value {
+            Value::Null => self.formatter.write_null(&mut self.writer)?,
+            Value::Bool(b) => self.formatter.write_bool(&mut self.writer, *b)?,
+            Value::Number(n) => self.formatter.write_number(&mut self.writer, n)?,
+            Value::String(s) => {
+                self.formatter
+                    .write_string(&mut self.writer, s, active_delimiter)?
             }
+            _ => unreachable!(),
         }
-        result
+        Ok(())
     }
 
-    fn write_string(&mut self, s: &str, active_delimiter: Delimiter) -> Result<()> {
-        if self.needs_quoting(s, active_delimiter) {
-            write!(self.writer, "\"{}\"", self.escape_string(s))?;
-        } else {
-            write!(self.writer, "{}", s)?;
+    /// Writes `key` (when present) followed by an array header and its colon.
+    fn write_array_header(
+        &mut self,
+        key: Option<&str>,
+        len: usize,
+        active_delimiter: Delimiter,
+        fields: Option<&[String]>,
+    ) -> Result<()> {
+        if let Some(k) = key {
+            self.write_key(k)?;
         }
+        self.formatter
+            .write_array_header(&mut self.writer, len, active_delimiter, fields)?;
+        self.writer.write_all(b":")?;
         Ok(())
     }
 
-    fn format_number(&self, num: &Number) -> String {
-        match num {
-            Number::I64(n) => n.to_string(),
-            Number::U64(n) => n.to_string(),
-            Number::F64(f) => {
-                if f.is_nan() || f.is_infinite() {
-                    return "null".to_string();
-                }
-                if *f == -0.0 {
-                    return "0".to_string();
-                }
-                if f.fract() == 0.0 {
-                    format!("{:.0}", f)
-                } else {
-                    let mut s = f.to_string();
-                    if s.contains('e') || s.contains('E') {
-                        let val = *f;
-                        s = format!("{:.17}", val);
-                        s = s.trim_end_matches('0').trim_end_matches('.').to_string();
-                    }
-                    s
-                }
-            }
+    /// Writes `value` as the next document of a multi-document stream.
+    ///
+    /// Every document after the first is preceded by a `---` separator line. Read the
+    /// stream back with [`StreamDeserializer`](crate::de::StreamDeserializer).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::ser::Serializer;
+    /// use serde_toon2::EncoderOptions;
+    /// use std::collections::BTreeMap;
+    ///
+    /// let mut buf = Vec::new();
+    /// let mut ser = Serializer::new(&mut buf, EncoderOptions::default());
+    /// for id in 1..=2 {
+    ///     ser.write_document(&BTreeMap::from([("id", id)])).unwrap();
+    /// }
+    /// ser.end().unwrap();
+    ///
+    /// assert_eq!(String::from_utf8(buf).unwrap(), "id: 1\n---\nid: 2");
+    /// ```
+    pub fn write_document<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<()> {
+        if self.documents > 0 {
+            self.write_newline()?;
+            self.formatter.write_document_separator(&mut self.writer)?;
+            self.write_newline()?;
+        }
+        self.depth = 0;
+        self.pending_key = None;
+        self.has_output = false;
+
+        value.serialize(&mut *self)?;
+        self.documents += 1;
+        Ok(())
+    }
+
+    /// Ends the output, writing the final line break if
+    /// [`EncoderOptions::trailing_newline`] is set.
+    ///
+    /// The `to_*` functions call this after serializing; call it yourself when driving
+    /// a `Serializer` directly.
+    pub fn end(&mut self) -> Result<()> {
+        if self.options.trailing_newline {
+            self.write_newline()?;
         }
+        Ok(())
     }
 
     pub fn serialize_value(&mut self, value: &Value) -> Result<()> {
+        if self.options.sort_keys {
+            let sorted = sort_keys(value);
+            return self.serialize_value_with_key(&sorted, None, self.document_delimiter);
+        }
         self.serialize_value_with_key(value, None, self.document_delimiter)
     }
 
@@ -210,72 +463,31 @@ impl<W: Write> Serializer<W> {
         value: &Value,
         key: Option<&str>,
         active_delimiter: Delimiter,
+    ) -> Result<()> {
+        if key.is_some() {
+            self.write_indent()?;
+        }
+        self.serialize_entry(value, key, active_delimiter)
+    }
+
+    /// Writes a value and its key on the current line, without leading indentation.
+    fn serialize_entry(
+        &mut self,
+        value: &Value,
+        key: Option<&str>,
+        active_delimiter: Delimiter,
     ) -> Result<()> {
         match value {
-            Value::Null => {
-                if let Some(k) = key {
-                    let quoted_key = if self.key_needs_quoting(k) {
-                        format!("\"{}\"", self.escape_string(k))
-                    } else {
-                        k.to_string()
-                    };
-                    write!(self.writer, "{}{}: null", self.indent(), quoted_key)?;
-                } else {
-                    write!(self.writer, "null")?;
-                }
-            }
-            Value::Bool(b) => {
-                if let Some(k) = key {
-                    let quoted_key = if self.key_needs_quoting(k) {
-                        format!("\"{}\"", self.escape_string(k))
-                    } else {
-                        k.to_string()
-                    };
-                    write!(self.writer, "{}{}: {}", self.indent(), quoted_key, b)?;
-                } else {
-                    write!(self.writer, "{}", b)?;
-                }
-            }
-            Value::Number(n) => {
-                let formatted = self.format_number(n);
-                if let Some(k) = key {
-                    let quoted_key = if self.key_needs_quoting(k) {
-                        format!("\"{}\"", self.escape_string(k))
-                    } else {
-                        k.to_string()
-                    };
-                    write!(
-                        self.writer,
-                        "{}{}: {}",
-                        self.indent(),
-                        quoted_key,
-                        formatted
-                    )?;
-                } else {
-                    write!(self.writer, "{}", formatted)?;
-                }
-            }
-            Value::String(s) => {
+            Value::Array(arr) => self.serialize_array(arr, key, active_delimiter),
+            Value::Object(obj) => self.serialize_object(obj, key),
+            _ => {
                 if let Some(k) = key {
-                    let quoted_key = if self.key_needs_quoting(k) {
-                        format!("\"{}\"", self.escape_string(k))
-                    } else {
-                        k.to_string()
-                    };
-                    write!(self.writer, "{}{}: ", self.indent(), quoted_key)?;
-                    self.write_string(s, active_delimiter)?;
-                } else {
-                    self.write_string(s, active_delimiter)?;
+                    self.write_key(k)?;
+                    self.writer.write_all(b": ")?;
                 }
-            }
-            Value::Array(arr) => {
-                self.serialize_array(arr, key, active_delimiter)?;
-            }
-            Value::Object(obj) => {
-                self.serialize_object(obj, key)?;
+                self.write_primitive(value, active_delimiter)
             }
         }
-        Ok(())
     }
 
     fn serialize_array(
@@ -284,24 +496,10 @@ impl<W: Write> Serializer<W> {
         key: Option<&str>,
         parent_delimiter: Delimiter,
     ) -> Result<()> {
-        let len = arr.len();
         let active_delimiter = parent_delimiter;
 
         if arr.is_empty() {
-            let header_delim = active_delimiter.header_marker();
-            if let Some(k) = key {
-                write!(
-                    self.writer,
-                    "{}{}[{}{}]:",
-                    self.indent(),
-                    k,
-                    len,
-                    header_delim
-                )?;
-            } else {
-                write!(self.writer, "[{}{}]:", len, header_delim)?;
-            }
-            return Ok(());
+            return self.write_array_header(key, 0, active_delimiter, None);
         }
 
         if self.is_primitive_array(arr) {
@@ -361,9 +559,8 @@ impl<W: Write> Serializer<W> {
                     }
 
                     if let Some(ref expected_fields) = fields {
-                        let keys_set: std::collections::HashSet<_> = item_keys.iter().collect();
-                        let expected_set: std::collections::HashSet<_> =
-                            expected_fields.iter().collect();
+                        let keys_set: BTreeSet<_> = item_keys.iter().collect();
+                        let expected_set: BTreeSet<_> = expected_fields.iter().collect();
                         if keys_set != expected_set {
                             return Some((false, vec![]));
                         }
@@ -391,49 +588,17 @@ impl<W: Write> Serializer<W> {
         key: Option<&str>,
         active_delimiter: Delimiter,
     ) -> Result<()> {
-        let len = arr.len();
-        let header_delim = active_delimiter.header_marker();
-
+        self.write_array_header(key, arr.len(), active_delimiter, None)?;
         if arr.is_empty() {
-            if let Some(k) = key {
-                write!(
-                    self.writer,
-                    "{}{}[{}{}]:",
-                    self.indent(),
-                    k,
-                    len,
-                    header_delim
-                )?;
-            } else {
-                write!(self.writer, "[{}{}]:", len, header_delim)?;
-            }
             return Ok(());
         }
 
-        if let Some(k) = key {
-            write!(
-                self.writer,
-                "{}{}[{}{}]: ",
-                self.indent(),
-                k,
-                len,
-                header_delim
-            )?;
-        } else {
-            write!(self.writer, "[{}{}]: ", len, header_delim)?;
-        }
-
+        self.writer.write_all(b" ")?;
         for (i, val) in arr.iter().enumerate() {
             if i > 0 {
-                write!(self.writer, "{}", active_delimiter.as_str())?;
-            }
-            match val {
-                Value::Null => write!(self.writer, "null")?,
-                Value::Bool(b) => write!(self.writer, "{}", b)?,
-                Value::Number(n) => write!(self.writer, "{}", self.format_number(n))?,
-                Value::String(s) => self.write_string(s, active_delimiter)?,
-                _ => unreachable!(),
+                self.write_delimiter(active_delimiter)?;
             }
+            self.write_primitive(val, active_delimiter)?;
         }
 
         Ok(())
@@ -445,26 +610,14 @@ impl<W: Write> Serializer<W> {
         key: Option<&str>,
         active_delimiter: Delimiter,
     ) -> Result<()> {
-        let len = arr.len();
-        let header_delim = active_delimiter.header_marker();
-
-        if let Some(k) = key {
-            write!(
-                self.writer,
-                "{}{}[{}{}]:",
-                self.indent(),
-                k,
-                len,
-                header_delim
-            )?;
-        } else {
-            write!(self.writer, "[{}{}]:", len, header_delim)?;
-        }
+        self.write_array_header(key, arr.len(), active_delimiter, None)?;
 
         self.depth += 1;
         for inner_arr in arr {
             if let Value::Array(inner) = inner_arr {
-                write!(self.writer, "\n{}- ", self.indent())?;
+                self.write_newline()?;
+                self.write_indent()?;
+                self.write_list_marker()?;
                 self.serialize_primitive_array(inner, None, active_delimiter)?;
             }
         }
@@ -480,50 +633,19 @@ impl<W: Write> Serializer<W> {
         fields: &[String],
         active_delimiter: Delimiter,
     ) -> Result<()> {
-        let len = arr.len();
-        let header_delim = active_delimiter.header_marker();
-
-        if let Some(k) = key {
-            write!(
-                self.writer,
-                "{}{}[{}{}]{{",
-                self.indent(),
-                k,
-                len,
-                header_delim
-            )?;
-        } else {
-            write!(self.writer, "[{}{}]{{", len, header_delim)?;
-        }
-
-        for (i, field) in fields.iter().enumerate() {
-            if i > 0 {
-                write!(self.writer, "{}", active_delimiter.as_str())?;
-            }
-            if self.key_needs_quoting(field) {
-                write!(self.writer, "\"{}\"", self.escape_string(field))?;
-            } else {
-                write!(self.writer, "{}", field)?;
-            }
-        }
-        write!(self.writer, "}}:")?;
+        self.write_array_header(key, arr.len(), active_delimiter, Some(fields))?;
 
         self.depth += 1;
         for obj in arr {
             if let Value::Object(map) = obj {
-                write!(self.writer, "\n{}", self.indent())?;
+                self.write_newline()?;
+                self.write_indent()?;
                 for (i, field_name) in fields.iter().enumerate() {
                     if i > 0 {
-                        write!(self.writer, "{}", active_delimiter.as_str())?;
+                        self.write_delimiter(active_delimiter)?;
                     }
                     if let Some(val) = map.get(field_name) {
-                        match val {
-                            Value::Null => write!(self.writer, "null")?,
-                            Value::Bool(b) => write!(self.writer, "{}", b)?,
-                            Value::Number(n) => write!(self.writer, "{}", self.format_number(n))?,
-                            Value::String(s) => self.write_string(s, active_delimiter)?,
-                            _ => unreachable!(),
-                        }
+                        self.write_primitive(val, active_delimiter)?;
                     }
                 }
             }
@@ -533,351 +655,108 @@ impl<W: Write> Serializer<W> {
         Ok(())
     }
 
-    fn serialize_mixed_array(
+    /// Writes the header line of a tabular array that is written row by row.
+    ///
+    /// An empty `fields` writes a plain header, as for an empty array.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn write_table_header(
         &mut self,
-        arr: &[Value],
         key: Option<&str>,
-        active_delimiter: Delimiter,
+        len: usize,
+        fields: &[String],
     ) -> Result<()> {
-        let len = arr.len();
-        let header_delim = active_delimiter.header_marker();
+        let fields = (!fields.is_empty()).then_some(fields);
+        self.write_array_header(key, len, self.document_delimiter, fields)?;
+        self.has_output = true;
+        Ok(())
+    }
 
-        if let Some(k) = key {
-            write!(
-                self.writer,
-                "{}{}[{}{}]:",
-                self.indent(),
-                k,
-                len,
-                header_delim
-            )?;
-        } else {
-            write!(self.writer, "[{}{}]:", len, header_delim)?;
-        }
+    /// Writes one row of a tabular array on a new line, below its header.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn write_table_row(
+        &mut self,
+        row: &Map<String, Value>,
+        fields: &[String],
+    ) -> Result<()> {
+        if row.len() != fields.len() {
+            return Err(Error::new(
+                crate::error::ErrorKind::WidthMismatch,
+                format!("Expected {} fields, got {}", fields.len(), row.len()),
+            ));
+        }
+        let cells = fields
+            .iter()
+            .map(|field| match row.get(field) {
+                Some(Value::Array(_) | Value::Object(_)) => Err(Error::custom(format!(
+                    "Field `{}` must be a primitive in a tabular row",
+                    field
+                ))),
+                Some(value) => Ok(value),
+                None => Err(Error::custom(format!("Missing field `{}` in row", field))),
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-        self.depth += 1;
-        for item in arr {
-            write!(self.writer, "\n{}- ", self.indent())?;
-            match item {
-                Value::Null => write!(self.writer, "null")?,
-                Value::Bool(b) => write!(self.writer, "{}", b)?,
-                Value::Number(n) => write!(self.writer, "{}", self.format_number(n))?,
-                Value::String(s) => {
-                    self.write_string(s, active_delimiter)?;
-                }
-                Value::Array(inner) => {
-                    self.serialize_primitive_array(inner, None, active_delimiter)?;
-                }
-                Value::Object(obj) => {
-                    self.serialize_object_as_list_item(obj, active_delimiter)?;
-                }
+        let delimiter = self.document_delimiter;
+        self.depth = 1;
+        self.write_newline()?;
+        self.write_indent()?;
+        for (i, value) in cells.into_iter().enumerate() {
+            if i > 0 {
+                self.write_delimiter(delimiter)?;
             }
+            self.write_primitive(value, delimiter)?;
         }
-        self.depth -= 1;
-
+        self.depth = 0;
         Ok(())
     }
 
-    fn serialize_object_as_list_item(
+    fn serialize_mixed_array(
         &mut self,
-        obj: &Map<String, Value>,
+        arr: &[Value],
+        key: Option<&str>,
         active_delimiter: Delimiter,
     ) -> Result<()> {
-        if obj.is_empty() {
-            return Ok(());
-        }
+        self.write_array_header(key, arr.len(), active_delimiter, None)?;
 
-        let mut first = true;
-        for (key, value) in obj {
-            if first {
-                let quoted_key = if self.key_needs_quoting(key) {
-                    format!("\"{}\"", self.escape_string(key))
-                } else {
-                    key.clone()
-                };
-
-                match value {
-                    Value::Null => write!(self.writer, "{}: null", quoted_key)?,
-                    Value::Bool(b) => write!(self.writer, "{}: {}", quoted_key, b)?,
-                    Value::Number(n) => {
-                        write!(self.writer, "{}: {}", quoted_key, self.format_number(n))?
-                    }
-                    Value::String(s) => {
-                        write!(self.writer, "{}: ", quoted_key)?;
-                        self.write_string(s, self.document_delimiter)?;
-                    }
-                    Value::Array(arr) => {
-                        if self.is_primitive_array(arr) {
-                            let len = arr.len();
-                            let header_delim = active_delimiter.header_marker();
-                            if arr.is_empty() {
-                                write!(self.writer, "{}[{}{}]:", quoted_key, len, header_delim)?;
-                            } else {
-                                write!(self.writer, "{}[{}{}]: ", quoted_key, len, header_delim)?;
-                                for (i, val) in arr.iter().enumerate() {
-                                    if i > 0 {
-                                        write!(self.writer, "{}", active_delimiter.as_str())?;
-                                    }
-                                    match val {
-                                        Value::Null => write!(self.writer, "null")?,
-                                        Value::Bool(b) => write!(self.writer, "{}", b)?,
-                                        Value::Number(n) => {
-                                            write!(self.writer, "{}", self.format_number(n))?
-                                        }
-                                        Value::String(s) => {
-                                            self.write_string(s, active_delimiter)?
-                                        }
-                                        _ => unreachable!(),
-                                    }
-                                }
-                            }
-                        } else if self.is_array_of_arrays(arr) {
-                            let len = arr.len();
-                            let header_delim = active_delimiter.header_marker();
-                            write!(self.writer, "{}[{}{}]:", quoted_key, len, header_delim)?;
-                            self.depth += 1;
-                            for inner_arr in arr {
-                                if let Value::Array(inner) = inner_arr {
-                                    write!(self.writer, "\n{}- ", self.indent())?;
-                                    self.serialize_primitive_array(inner, None, active_delimiter)?;
-                                }
-                            }
-                            self.depth -= 1;
-                        } else if let Some((is_tabular, fields)) = self.detect_tabular(arr) {
-                            if is_tabular {
-                                let len = arr.len();
-                                let header_delim = active_delimiter.header_marker();
-                                write!(self.writer, "{}[{}{}]{{", quoted_key, len, header_delim)?;
-                                for (i, field) in fields.iter().enumerate() {
-                                    if i > 0 {
-                                        write!(self.writer, "{}", active_delimiter.as_str())?;
-                                    }
-                                    if self.key_needs_quoting(field) {
-                                        write!(self.writer, "\"{}\"", self.escape_string(field))?;
-                                    } else {
-                                        write!(self.writer, "{}", field)?;
-                                    }
-                                }
-                                write!(self.writer, "}}:")?;
-                                self.depth += 1;
-                                for obj in arr {
-                                    if let Value::Object(map) = obj {
-                                        write!(self.writer, "\n{}", self.indent())?;
-                                        for (i, field_name) in fields.iter().enumerate() {
-                                            if i > 0 {
-                                                write!(
-                                                    self.writer,
-                                                    "{}",
-                                                    active_delimiter.as_str()
-                                                )?;
-                                            }
-                                            if let Some(val) = map.get(field_name) {
-                                                match val {
-                                                    Value::Null => write!(self.writer, "null")?,
-                                                    Value::Bool(b) => write!(self.writer, "{}", b)?,
-                                                    Value::Number(n) => write!(
-                                                        self.writer,
-                                                        "{}",
-                                                        self.format_number(n)
-                                                    )?,
-                                                    Value::String(s) => {
-                                                        self.write_string(s, active_delimiter)?
-                                                    }
-                                                    _ => unreachable!(),
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                                self.depth -= 1;
-                            } else {
-                                let len = arr.len();
-                                let header_delim = active_delimiter.header_marker();
-                                write!(self.writer, "{}[{}{}]:", quoted_key, len, header_delim)?;
-                                self.depth += 1;
-                                for item in arr {
-                                    write!(self.writer, "\n{}- ", self.indent())?;
-                                    match item {
-                                        Value::Null => write!(self.writer, "null")?,
-                                        Value::Bool(b) => write!(self.writer, "{}", b)?,
-                                        Value::Number(n) => {
-                                            write!(self.writer, "{}", self.format_number(n))?
-                                        }
-                                        Value::String(s) => {
-                                            self.write_string(s, active_delimiter)?;
-                                        }
-                                        Value::Array(inner) => {
-                                            self.serialize_primitive_array(
-                                                inner,
-                                                None,
-                                                active_delimiter,
-                                            )?;
-                                        }
-                                        Value::Object(obj) => {
-                                            self.serialize_object_as_list_item(
-                                                obj,
-                                                active_delimiter,
-                                            )?;
-                                        }
-                                    }
-                                }
-                                self.depth -= 1;
-                            }
-                        } else {
-                            let len = arr.len();
-                            let header_delim = active_delimiter.header_marker();
-                            write!(self.writer, "{}[{}{}]:", quoted_key, len, header_delim)?;
-                            self.depth += 1;
-                            for item in arr {
-                                write!(self.writer, "\n{}- ", self.indent())?;
-                                match item {
-                                    Value::Null => write!(self.writer, "null")?,
-                                    Value::Bool(b) => write!(self.writer, "{}", b)?,
-                                    Value::Number(n) => {
-                                        write!(self.writer, "{}", self.format_number(n))?
-                                    }
-                                    Value::String(s) => {
-                                        self.write_string(s, active_delimiter)?;
-                                    }
-                                    Value::Array(inner) => {
-                                        self.serialize_primitive_array(
-                                            inner,
-                                            None,
-                                            active_delimiter,
-                                        )?;
-                                    }
-                                    Value::Object(obj) => {
-                                        self.serialize_object_as_list_item(obj, active_delimiter)?;
-                                    }
-                                }
-                            }
-                            self.depth -= 1;
-                        }
-                    }
-                    Value::Object(nested) => {
-                        write!(self.writer, "{}:", quoted_key)?;
-                        self.depth += 1;
-                        for (nested_key, nested_val) in nested {
-                            writeln!(self.writer)?;
-                            self.serialize_value_with_key(
-                                nested_val,
-                                Some(nested_key),
-                                self.document_delimiter,
-                            )?;
-                        }
-                        self.depth -= 1;
-                    }
-                }
-                first = false;
-            } else {
-                let quoted_key = if self.key_needs_quoting(key) {
-                    format!("\"{}\"", self.escape_string(key))
-                } else {
-                    key.clone()
-                };
-
-                let extra_indent = " ".repeat(self.options.indent);
-                let field_indent = format!("{}{}", self.indent(), extra_indent);
-
-                match value {
-                    Value::Null => write!(self.writer, "\n{}{}: null", field_indent, quoted_key)?,
-                    Value::Bool(b) => {
-                        write!(self.writer, "\n{}{}: {}", field_indent, quoted_key, b)?
-                    }
-                    Value::Number(n) => write!(
-                        self.writer,
-                        "\n{}{}: {}",
-                        field_indent,
-                        quoted_key,
-                        self.format_number(n)
-                    )?,
-                    Value::String(s) => {
-                        write!(self.writer, "\n{}{}: ", field_indent, quoted_key)?;
-                        self.write_string(s, self.document_delimiter)?;
-                    }
-                    Value::Array(arr) => {
-                        writeln!(self.writer)?;
-                        self.depth += 1;
-                        self.serialize_array(arr, Some(key), active_delimiter)?;
-                        self.depth -= 1;
-                    }
-                    Value::Object(nested) => {
-                        write!(self.writer, "\n{}{}:", field_indent, quoted_key)?;
-                        self.depth += 2;
-                        for (nested_key, nested_val) in nested {
-                            writeln!(self.writer)?;
-                            self.serialize_value_with_key(
-                                nested_val,
-                                Some(nested_key),
-                                self.document_delimiter,
-                            )?;
-                        }
-                        self.depth -= 2;
-                    }
-                }
-            }
+        self.depth += 1;
+        for item in arr {
+            self.serialize_list_item(item, active_delimiter)?;
         }
+        self.depth -= 1;
 
         Ok(())
     }
 
-    fn key_needs_quoting(&self, key: &str) -> bool {
-        if key.is_empty() {
-            return true;
-        }
-
-        if !Self::is_valid_unquoted_key(key) {
-            return true;
+    /// Writes `item` on a new `- ` line of a list.
+    fn serialize_list_item(&mut self, item: &Value, active_delimiter: Delimiter) -> Result<()> {
+        self.write_newline()?;
+        self.write_indent()?;
+        self.write_list_marker()?;
+        match item {
+            Value::Array(inner) => self.serialize_primitive_array(inner, None, active_delimiter),
+            Value::Object(obj) => self.serialize_object_as_list_item(obj, active_delimiter),
+            _ => self.write_primitive(item, active_delimiter),
         }
-
-        if key.contains('\n')
-            || key.contains('\r')
-            || key.contains('\t')
-            || key.contains('\\')
-            || key.contains('"')
-        {
-            return true;
-        }
-
-        false
     }
 
-    #[inline]
-    fn is_valid_unquoted_key(s: &str) -> bool {
-        let bytes = s.as_bytes();
-        if bytes.is_empty() {
-            return false;
-        }
-
-        // First character must be A-Za-z_
-        let first = bytes[0];
-        if !(first.is_ascii_alphabetic() || first == b'_') {
-            return false;
-        }
-
-        // Fast path for remaining characters using lookup table
-        static VALID_KEY_CHARS: [bool; 256] = {
-            let mut table = [false; 256];
-            let mut i = 0;
-            while i < 256 {
-                table[i] = (i >= b'A' as usize && i <= b'Z' as usize)
-                    || (i >= b'a' as usize && i <= b'z' as usize)
-                    || (i >= b'0' as usize && i <= b'9' as usize)
-                    || i == b'_' as usize
-                    || i == b'.' as usize;
-                i += 1;
-            }
-            table
-        };
-
-        // Check remaining bytes using lookup table
-        for &byte in &bytes[1..] {
-            if !VALID_KEY_CHARS[byte as usize] {
-                return false;
+    fn serialize_object_as_list_item(
+        &mut self,
+        obj: &Map<String, Value>,
+        active_delimiter: Delimiter,
+    ) -> Result<()> {
+        for (i, (key, value)) in obj.iter().enumerate() {
+            if i == 0 {
+                // The first field shares the line with the list marker
+                self.serialize_entry(value, Some(key), active_delimiter)?;
+            } else {
+                self.write_newline()?;
+                self.depth += 1;
+                self.serialize_value_with_key(value, Some(key), active_delimiter)?;
+                self.depth -= 1;
             }
         }
 
-        true
+        Ok(())
     }
 
     fn try_fold_object(
@@ -885,8 +764,6 @@ impl<W: Write> Serializer<W> {
         obj: &Map<String, Value>,
         num_segments: usize,
     ) -> Option<(String, Value)> {
-        use crate::options::KeyFolding;
-
         // Key folding must be enabled
         if self.options.key_folding != KeyFolding::Safe {
             return None;
@@ -900,7 +777,7 @@ impl<W: Write> Serializer<W> {
         let (key, value) = obj.iter().next().unwrap();
 
         // In safe mode, don't fold keys that need quoting
-        if self.key_needs_quoting(key) {
+        if key_needs_quoting(key) {
             return None;
         }
 
@@ -932,29 +809,13 @@ impl<W: Write> Serializer<W> {
     }
 
     fn serialize_object(&mut self, obj: &Map<String, Value>, key: Option<&str>) -> Result<()> {
-        if obj.is_empty() {
-            if let Some(k) = key {
-                let quoted_key = if self.key_needs_quoting(k) {
-                    format!("\"{}\"", self.escape_string(k))
-                } else {
-                    k.to_string()
-                };
-                write!(self.writer, "{}{}:", self.indent(), quoted_key)?;
-            }
-            return Ok(());
-        }
-
         if let Some(k) = key {
-            let quoted_key = if self.key_needs_quoting(k) {
-                format!("\"{}\"", self.escape_string(k))
-            } else {
-                k.to_string()
-            };
-            write!(self.writer, "{}{}:", self.indent(), quoted_key)?;
+            self.write_key(k)?;
+            self.writer.write_all(b":")?;
             self.depth += 1;
 
             for (obj_key, obj_val) in obj {
-                writeln!(self.writer)?;
+                self.write_newline()?;
                 self.serialize_value_with_key(obj_val, Some(obj_key), self.document_delimiter)?;
             }
             self.depth -= 1;
@@ -964,13 +825,13 @@ impl<W: Write> Serializer<W> {
 
             for (i, (obj_key, obj_val)) in obj.iter().enumerate() {
                 if i > 0 {
-                    writeln!(self.writer)?;
+                    self.write_newline()?;
                 }
 
                 // Try to fold if it's an object
                 if let Value::Object(nested_obj) = obj_val {
                     // In safe mode, don't fold if the parent key needs quoting
-                    if !self.key_needs_quoting(obj_key) {
+                    if !key_needs_quoting(obj_key) {
                         // Start with 1 to account for the current key (obj_key)
                         if let Some((folded_path, final_value)) =
                             self.try_fold_object(nested_obj, 1)
@@ -997,6 +858,277 @@ impl<W: Write> Serializer<W> {
     }
 }
 
+fn is_primitive(value: &Value) -> bool {
+    !matches!(value, Value::Array(_) | Value::Object(_))
+}
+
+/// Whether `item` can be written in the same inline, array-of-arrays or tabular layout
+/// as `first`. An array whose elements do not all fit its first one is a list.
+fn fits_layout(first: &Value, item: &Value) -> bool {
+    match (first, item) {
+        (Value::Array(first), Value::Array(item)) => {
+            first.iter().all(is_primitive) && item.iter().all(is_primitive)
+        }
+        (Value::Object(first), Value::Object(item)) => {
+            first.len() == item.len()
+                && first.values().all(is_primitive)
+                && item
+                    .iter()
+                    .all(|(k, v)| is_primitive(v) && first.contains_key(k))
+        }
+        (first, item) => is_primitive(first) && is_primitive(item),
+    }
+}
+
+fn needs_quoting(s: &str, active_delimiter: Delimiter) -> bool {
+    if s.is_empty() {
+        return true;
+    }
+
+    if s.starts_with(|c: char| c.is_whitespace()) || s.ends_with(|c: char| c.is_whitespace()) {
+        return true;
+    }
+
+    if s == "true" || s == "false" || s == "null" {
+        return true;
+    }
+
+    if s.starts_with('-') {
+        return true;
+    }
+
+    if s.contains(active_delimiter.as_char())
+        || s.contains(':')
+        || s.contains('"')
+        || s.contains('\\')
+        || s.contains('[')
+        || s.contains(']')
+        || s.contains('{')
+        || s.contains('}')
+        || s.contains('\n')
+        || s.contains('\r')
+        || s.contains('\t')
+    {
+        return true;
+    }
+
+    if looks_like_number(s) {
+        return true;
+    }
+
+    if has_leading_zeros(s) {
+        return true;
+    }
+
+    false
+}
+
+#[inline]
+fn looks_like_number(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    if bytes.is_empty() {
+        return false;
+    }
+
+    let mut i = 0;
+
+    // Optional leading minus
+    if bytes[i] == b'-' {
+        i += 1;
+        if i >= bytes.len() {
+            return false;
+        }
+    }
+
+    // Must have at least one digit
+    if !bytes[i].is_ascii_digit() {
+        return false;
+    }
+
+    // Integer part
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        i += 1;
+    }
+
+    if i >= bytes.len() {
+        return true; // Just an integer
+    }
+
+    // Optional decimal part
+    if bytes[i] == b'.' {
+        i += 1;
+        if i >= bytes.len() {
+            return false; // Trailing dot
+        }
+        if !bytes[i].is_ascii_digit() {
+            return false; // No digits after dot
+        }
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+    }
+
+    if i >= bytes.len() {
+        return true; // Number with decimal
+    }
+
+    // Optional exponent
+    if bytes[i] == b'e' || bytes[i] == b'E' {
+        i += 1;
+        if i >= bytes.len() {
+            return false;
+        }
+        // Optional sign
+        if bytes[i] == b'+' || bytes[i] == b'-' {
+            i += 1;
+            if i >= bytes.len() {
+                return false;
+            }
+        }
+        // Must have at least one digit
+        if !bytes[i].is_ascii_digit() {
+            return false;
+        }
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+    }
+
+    i == bytes.len()
+}
+
+#[inline]
+fn has_leading_zeros(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    bytes.len() >= 2 && bytes[0] == b'0' && bytes[1].is_ascii_digit()
+}
+
+/// Writes `s` with backslashes, quotes and control characters escaped.
+fn write_escaped<W: ?Sized + Write>(writer: &mut W, s: &str) -> io::Result<()> {
+    let bytes = s.as_bytes();
+    let mut start = 0;
+    for (i, &byte) in bytes.iter().enumerate() {
+        let escape: &[u8] = match byte {
+            b'\\' => b"\\\\",
+            b'"' => b"\\\"",
+            b'\n' => b"\\n",
+            b'\r' => b"\\r",
+            b'\t' => b"\\t",
+            _ => continue,
+        };
+        writer.write_all(&bytes[start..i])?;
+        writer.write_all(escape)?;
+        start = i + 1;
+    }
+    writer.write_all(&bytes[start..])
+}
+
+fn key_needs_quoting(key: &str) -> bool {
+    if key.is_empty() {
+        return true;
+    }
+
+    if !is_valid_unquoted_key(key) {
+        return true;
+    }
+
+    if key.contains('\n')
+        || key.contains('\r')
+        || key.contains('\t')
+        || key.contains('\\')
+        || key.contains('"')
+    {
+        return true;
+    }
+
+    false
+}
+
+#[inline]
+fn is_valid_unquoted_key(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    if bytes.is_empty() {
+        return false;
+    }
+
+    // First character must be A-Za-z_
+    let first = bytes[0];
+    if !(first.is_ascii_alphabetic() || first == b'_') {
+        return false;
+    }
+
+    // Fast path for remaining characters using lookup table
+    static VALID_KEY_CHARS: [bool; 256] = {
+        let mut table = [false; 256];
+        let mut i = 0;
+        while i < 256 {
+            table[i] = (i >= b'A' as usize && i <= b'Z' as usize)
+                || (i >= b'a' as usize && i <= b'z' as usize)
+                || (i >= b'0' as usize && i <= b'9' as usize)
+                || i == b'_' as usize
+                || i == b'.' as usize;
+            i += 1;
+        }
+        table
+    };
+
+    // Check remaining bytes using lookup table
+    for &byte in &bytes[1..] {
+        if !VALID_KEY_CHARS[byte as usize] {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Formats a number in its normalised TOON form.
+///
+/// Integral floats are written without a fractional part, `-0.0` as `0`, and
+/// non-finite floats as `null`.
+pub(crate) fn format_number(num: &Number) -> String {
+    match num {
+        Number::I64(n) => n.to_string(),
+        Number::U64(n) => n.to_string(),
+        Number::F64(f) => {
+            if f.is_nan() || f.is_infinite() {
+                return "null".to_string();
+            }
+            if *f == -0.0 {
+                return "0".to_string();
+            }
+            if is_integral(*f) {
+                format!("{:.0}", f)
+            } else {
+                let mut s = f.to_string();
+                if s.contains('e') || s.contains('E') {
+                    let val = *f;
+                    s = format!("{:.17}", val);
+                    s = s.trim_end_matches('0').trim_end_matches('.').to_string();
+                }
+                s
+            }
+        }
+    }
+}
+
+fn sort_keys(value: &Value) -> Value {
+    match value {
+        Value::Array(arr) => Value::Array(arr.iter().map(sort_keys).collect()),
+        Value::Object(obj) => {
+            let mut entries: Vec<(&String, &Value)> = obj.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.clone(), sort_keys(v)))
+                    .collect(),
+            )
+        }
+        other => other.clone(),
+    }
+}
+
 /// Serializes a value to a TOON string using default options.
 ///
 /// # Examples
@@ -1044,8 +1176,7 @@ pub fn to_string_with_options<T: ser::Serialize>(
     value: &T,
     options: EncoderOptions,
 ) -> Result<String> {
-    let mut buf = Vec::new();
-    to_writer_with_options(&mut buf, value, options)?;
+    let buf = to_vec_with_options(value, options)?;
     String::from_utf8(buf).map_err(|e| Error::custom(e.to_string()))
 }
 
@@ -1070,11 +1201,11 @@ pub fn to_vec<T: ser::Serialize>(value: &T) -> Result<Vec<u8>> {
 /// # Examples
 ///
 /// ```
-/// use serde_toon2::{to_vec_with_options, EncoderOptions};
+/// use serde_toon2::{to_vec_with_options, EncoderOptions, Indent};
 ///
 /// let data = vec![1, 2, 3];
 /// let opts = EncoderOptions {
-///     indent: 4,
+///     indent: Indent::Spaces(4),
 ///     ..Default::default()
 /// };
 /// let bytes = to_vec_with_options(&data, opts).unwrap();
@@ -1084,9 +1215,10 @@ pub fn to_vec_with_options<T: ser::Serialize>(
     value: &T,
     options: EncoderOptions,
 ) -> Result<Vec<u8>> {
-    let mut buf = Vec::new();
-    to_writer_with_options(&mut buf, value, options)?;
-    Ok(buf)
+    let mut serializer = Serializer::new(Vec::new(), options);
+    value.serialize(&mut serializer)?;
+    serializer.end()?;
+    Ok(serializer.into_inner())
 }
 
 /// Serializes a value to TOON format and writes it to the given writer using default options.
@@ -1104,6 +1236,7 @@ pub fn to_vec_with_options<T: ser::Serialize>(
 /// let toon = String::from_utf8(buffer.into_inner()).unwrap();
 /// assert_eq!(toon, "[3]: x,y,z");
 /// ```
+#[cfg(feature = "std")]
 pub fn to_writer<W: Write, T: ser::Serialize>(writer: W, value: &T) -> Result<()> {
     to_writer_with_options(writer, value, EncoderOptions::default())
 }
@@ -1128,6 +1261,7 @@ pub fn to_writer<W: Write, T: ser::Serialize>(writer: W, value: &T) -> Result<()
 /// let toon = String::from_utf8(buffer.into_inner()).unwrap();
 /// assert!(toon.contains("\t"));
 /// ```
+#[cfg(feature = "std")]
 pub fn to_writer_with_options<W: Write, T: ser::Serialize>(
     writer: W,
     value: &T,
@@ -1135,23 +1269,23 @@ pub fn to_writer_with_options<W: Write, T: ser::Serialize>(
 ) -> Result<()> {
     let mut serializer = Serializer::new(writer, options);
     value.serialize(&mut serializer)?;
-    Ok(())
+    serializer.end()
 }
 
-impl<'a, W: Write> ser::Serializer for &'a mut Serializer<W> {
+impl<'a, W: Write, F: Formatter> ser::Serializer for &'a mut Serializer<W, F> {
     type Ok = ();
     type Error = Error;
 
-    type SerializeSeq = SeqSerializer<'a, W>;
-    type SerializeTuple = SeqSerializer<'a, W>;
-    type SerializeTupleStruct = SeqSerializer<'a, W>;
-    type SerializeTupleVariant = SeqSerializer<'a, W>;
-    type SerializeMap = MapSerializer<'a, W>;
-    type SerializeStruct = MapSerializer<'a, W>;
-    type SerializeStructVariant = MapSerializer<'a, W>;
+    type SerializeSeq = SeqSerializer<'a, W, F>;
+    type SerializeTuple = SeqSerializer<'a, W, F>;
+    type SerializeTupleStruct = SeqSerializer<'a, W, F>;
+    type SerializeTupleVariant = SeqSerializer<'a, W, F>;
+    type SerializeMap = MapSerializer<'a, W, F>;
+    type SerializeStruct = MapSerializer<'a, W, F>;
+    type SerializeStructVariant = MapSerializer<'a, W, F>;
 
     fn serialize_bool(self, v: bool) -> Result<()> {
-        self.serialize_value(&Value::Bool(v))
+        self.emit_scalar(|f, w| f.write_bool(w, v))
     }
 
     fn serialize_i8(self, v: i8) -> Result<()> {
@@ -1167,7 +1301,7 @@ impl<'a, W: Write> ser::Serializer for &'a mut Serializer<W> {
     }
 
     fn serialize_i64(self, v: i64) -> Result<()> {
-        self.serialize_value(&Value::Number(Number::I64(v)))
+        self.emit_scalar(|f, w| f.write_number(w, &Number::I64(v)))
     }
 
     fn serialize_u8(self, v: u8) -> Result<()> {
@@ -1183,7 +1317,7 @@ impl<'a, W: Write> ser::Serializer for &'a mut Serializer<W> {
     }
 
     fn serialize_u64(self, v: u64) -> Result<()> {
-        self.serialize_value(&Value::Number(Number::U64(v)))
+        self.emit_scalar(|f, w| f.write_number(w, &Number::U64(v)))
     }
 
     fn serialize_f32(self, v: f32) -> Result<()> {
@@ -1191,15 +1325,16 @@ impl<'a, W: Write> ser::Serializer for &'a mut Serializer<W> {
     }
 
     fn serialize_f64(self, v: f64) -> Result<()> {
-        self.serialize_value(&Value::Number(Number::F64(v)))
+        self.emit_scalar(|f, w| f.write_number(w, &Number::F64(v)))
     }
 
     fn serialize_char(self, v: char) -> Result<()> {
-        self.serialize_str(&v.to_string())
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
     }
 
     fn serialize_str(self, v: &str) -> Result<()> {
-        self.serialize_value(&Value::String(v.to_string()))
+        let delimiter = self.document_delimiter;
+        self.emit_scalar(|f, w| f.write_string(w, v, delimiter))
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<()> {
@@ -1220,7 +1355,7 @@ impl<'a, W: Write> ser::Serializer for &'a mut Serializer<W> {
     }
 
     fn serialize_unit(self) -> Result<()> {
-        self.serialize_value(&Value::Null)
+        self.emit_scalar(|f, w| f.write_null(w))
     }
 
     fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
@@ -1257,10 +1392,13 @@ impl<'a, W: Write> ser::Serializer for &'a mut Serializer<W> {
         map.end()
     }
 
-    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
         Ok(SeqSerializer {
             serializer: self,
+            len,
             elements: Vec::new(),
+            is_list: false,
+            written: 0,
         })
     }
 
@@ -1285,15 +1423,31 @@ impl<'a, W: Write> ser::Serializer for &'a mut Serializer<W> {
     ) -> Result<Self::SerializeTupleVariant> {
         Ok(SeqSerializer {
             serializer: self,
+            len: None,
             elements: vec![Value::String(variant.to_string())],
+            is_list: false,
+            written: 0,
         })
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        if !self.is_streaming() {
+            return Ok(MapSerializer {
+                serializer: self,
+                entries: Some(Map::default()),
+                variant: None,
+                current_key: None,
+                depth: 0,
+            });
+        }
+
+        let depth = self.open_object()?;
         Ok(MapSerializer {
             serializer: self,
-            entries: Map::new(),
+            entries: None,
+            variant: None,
             current_key: None,
+            depth,
         })
     }
 
@@ -1308,36 +1462,124 @@ impl<'a, W: Write> ser::Serializer for &'a mut Serializer<W> {
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
+        if !self.is_streaming() {
+            return Ok(MapSerializer {
+                serializer: self,
+                entries: Some(Map::default()),
+                variant: Some(variant),
+                current_key: None,
+                depth: 0,
+            });
+        }
+
+        let outer = self.open_object()?;
+        self.pending_key = Some(variant.to_string());
+        let inner = self.open_object()?;
         Ok(MapSerializer {
             serializer: self,
-            entries: Map::new(),
-            current_key: Some(variant.to_string()),
+            entries: None,
+            variant: None,
+            current_key: None,
+            depth: outer + inner,
         })
     }
 }
 
-pub struct SeqSerializer<'a, W> {
-    serializer: &'a mut Serializer<W>,
+pub struct SeqSerializer<'a, W, F = DefaultFormatter> {
+    serializer: &'a mut Serializer<W, F>,
+    /// Number of elements announced by the sequence, which the header needs up front.
+    len: Option<usize>,
+    /// Elements collected until the array turns out to be a list.
     elements: Vec<Value>,
+    /// Whether the list header has been written, so elements are written as they come.
+    is_list: bool,
+    /// Number of elements written under the list header.
+    written: usize,
+}
+
+impl<'a, W: Write, F: Formatter> SeqSerializer<'a, W, F> {
+    /// Writes the list header and the elements so far once the last element makes the
+    /// array a list.
+    fn start_list(&mut self) -> Result<()> {
+        let Some(len) = self.len else {
+            return Ok(());
+        };
+        if !self.serializer.is_streaming() {
+            return Ok(());
+        }
+        let (Some(first), Some(last)) = (self.elements.first(), self.elements.last()) else {
+            return Ok(());
+        };
+        if fits_layout(first, last) {
+            return Ok(());
+        }
+
+        let serializer = &mut *self.serializer;
+        let key = serializer.pending_key.take();
+        if key.is_some() {
+            serializer.begin_line()?;
+        }
+        let delimiter = serializer.document_delimiter;
+        serializer.write_array_header(key.as_deref(), len, delimiter, None)?;
+        serializer.has_output = true;
+        serializer.depth += 1;
+        self.is_list = true;
+
+        for element in mem::take(&mut self.elements) {
+            self.write_element(&element)?;
+        }
+        Ok(())
+    }
+
+    /// Writes an element on its own line of the list.
+    fn write_element(&mut self, element: &Value) -> Result<()> {
+        if self.len.is_some_and(|len| self.written >= len) {
+            return Err(self.count_mismatch(format!("at least {}", self.written + 1)));
+        }
+        let delimiter = self.serializer.document_delimiter;
+        self.serializer.serialize_list_item(element, delimiter)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    fn count_mismatch(&self, received: String) -> Error {
+        Error::new(
+            crate::error::ErrorKind::CountMismatch,
+            format!(
+                "Sequence declared {} elements, got {}",
+                self.len.unwrap_or_default(),
+                received
+            ),
+        )
+    }
 }
 
-impl<'a, W: Write> ser::SerializeSeq for SeqSerializer<'a, W> {
+impl<'a, W: Write, F: Formatter> ser::SerializeSeq for SeqSerializer<'a, W, F> {
     type Ok = ();
     type Error = Error;
 
     fn serialize_element<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<()> {
         let val = to_value(value)?;
+        if self.is_list {
+            return self.write_element(&val);
+        }
         self.elements.push(val);
-        Ok(())
+        self.start_list()
     }
 
     fn end(self) -> Result<()> {
-        self.serializer
-            .serialize_value(&Value::Array(self.elements))
+        if !self.is_list {
+            return self.serializer.emit_value(&Value::Array(self.elements));
+        }
+        if self.len != Some(self.written) {
+            return Err(self.count_mismatch(self.written.to_string()));
+        }
+        self.serializer.depth -= 1;
+        Ok(())
     }
 }
 
-impl<'a, W: Write> ser::SerializeTuple for SeqSerializer<'a, W> {
+impl<'a, W: Write, F: Formatter> ser::SerializeTuple for SeqSerializer<'a, W, F> {
     type Ok = ();
     type Error = Error;
 
@@ -1350,7 +1592,7 @@ impl<'a, W: Write> ser::SerializeTuple for SeqSerializer<'a, W> {
     }
 }
 
-impl<'a, W: Write> ser::SerializeTupleStruct for SeqSerializer<'a, W> {
+impl<'a, W: Write, F: Formatter> ser::SerializeTupleStruct for SeqSerializer<'a, W, F> {
     type Ok = ();
     type Error = Error;
 
@@ -1363,7 +1605,7 @@ impl<'a, W: Write> ser::SerializeTupleStruct for SeqSerializer<'a, W> {
     }
 }
 
-impl<'a, W: Write> ser::SerializeTupleVariant for SeqSerializer<'a, W> {
+impl<'a, W: Write, F: Formatter> ser::SerializeTupleVariant for SeqSerializer<'a, W, F> {
     type Ok = ();
     type Error = Error;
 
@@ -1376,28 +1618,23 @@ impl<'a, W: Write> ser::SerializeTupleVariant for SeqSerializer<'a, W> {
     }
 }
 
-pub struct MapSerializer<'a, W> {
-    serializer: &'a mut Serializer<W>,
-    entries: Map<String, Value>,
+pub struct MapSerializer<'a, W, F = DefaultFormatter> {
+    serializer: &'a mut Serializer<W, F>,
+    /// Entries collected while the document is buffered; `None` while streaming.
+    entries: Option<Map<String, Value>>,
+    /// Variant name wrapping the entries of a buffered struct variant.
+    variant: Option<&'static str>,
     current_key: Option<String>,
+    /// Depth added by the `key:` lines this map opened while streaming.
+    depth: usize,
 }
 
-impl<'a, W: Write> ser::SerializeMap for MapSerializer<'a, W> {
+impl<'a, W: Write, F: Formatter> ser::SerializeMap for MapSerializer<'a, W, F> {
     type Ok = ();
     type Error = Error;
 
     fn serialize_key<T: ?Sized + ser::Serialize>(&mut self, key: &T) -> Result<()> {
-        let key_val = to_value(key)?;
-        let key_str = match key_val {
-            Value::String(s) => s,
-            Value::Number(Number::I64(n)) => n.to_string(),
-            Value::Number(Number::U64(n)) => n.to_string(),
-            Value::Number(Number::F64(n)) => n.to_string(),
-            Value::Bool(b) => b.to_string(),
-            Value::Null => "null".to_string(),
-            _ => return Err(Error::custom("map keys must be strings or primitives")),
-        };
-        self.current_key = Some(key_str);
+        self.current_key = Some(map_key(key)?);
         Ok(())
     }
 
@@ -1406,24 +1643,35 @@ impl<'a, W: Write> ser::SerializeMap for MapSerializer<'a, W> {
             .current_key
             .take()
             .ok_or_else(|| Error::custom("serialize_value called without key"))?;
-        let val = to_value(value)?;
-        self.entries.insert(key, val);
+        match &mut self.entries {
+            Some(entries) => {
+                entries.insert(key, to_value(value)?);
+            }
+            None => {
+                self.serializer.pending_key = Some(key);
+                value.serialize(&mut *self.serializer)?;
+            }
+        }
         Ok(())
     }
 
     fn end(self) -> Result<()> {
-        if let Some(variant_key) = self.current_key {
-            let mut outer_map = Map::new();
-            outer_map.insert(variant_key, Value::Object(self.entries));
-            self.serializer.serialize_value(&Value::Object(outer_map))
+        let Some(entries) = self.entries else {
+            self.serializer.depth -= self.depth;
+            return Ok(());
+        };
+
+        if let Some(variant) = self.variant {
+            let mut outer_map = Map::default();
+            outer_map.insert(variant.to_string(), Value::Object(entries));
+            self.serializer.emit_value(&Value::Object(outer_map))
         } else {
-            self.serializer
-                .serialize_value(&Value::Object(self.entries))
+            self.serializer.emit_value(&Value::Object(entries))
         }
     }
 }
 
-impl<'a, W: Write> ser::SerializeStruct for MapSerializer<'a, W> {
+impl<'a, W: Write, F: Formatter> ser::SerializeStruct for MapSerializer<'a, W, F> {
     type Ok = ();
     type Error = Error;
 
@@ -1432,9 +1680,8 @@ impl<'a, W: Write> ser::SerializeStruct for MapSerializer<'a, W> {
         key: &'static str,
         value: &T,
     ) -> Result<()> {
-        let val = to_value(value)?;
-        self.entries.insert(key.to_string(), val);
-        Ok(())
+        self.current_key = Some(key.to_string());
+        ser::SerializeMap::serialize_value(self, value)
     }
 
     fn end(self) -> Result<()> {
@@ -1442,7 +1689,7 @@ impl<'a, W: Write> ser::SerializeStruct for MapSerializer<'a, W> {
     }
 }
 
-impl<'a, W: Write> ser::SerializeStructVariant for MapSerializer<'a, W> {
+impl<'a, W: Write, F: Formatter> ser::SerializeStructVariant for MapSerializer<'a, W, F> {
     type Ok = ();
     type Error = Error;
 
@@ -1459,7 +1706,43 @@ impl<'a, W: Write> ser::SerializeStructVariant for MapSerializer<'a, W> {
     }
 }
 
-fn to_value<T: ser::Serialize + ?Sized>(value: &T) -> Result<Value> {
+/// Converts a map key to the string used as its TOON key.
+fn map_key<T: ser::Serialize + ?Sized>(key: &T) -> Result<String> {
+    match to_value(key)? {
+        Value::String(s) => Ok(s),
+        Value::Number(Number::I64(n)) => Ok(n.to_string()),
+        Value::Number(Number::U64(n)) => Ok(n.to_string()),
+        Value::Number(Number::F64(n)) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        Value::Null => Ok("null".to_string()),
+        _ => Err(Error::custom("map keys must be strings or primitives")),
+    }
+}
+
+/// Converts any serializable value into a [`Value`].
+///
+/// Map keys are converted to strings as when encoding, and fail to convert if they are
+/// not primitives.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Serialize;
+/// use serde_toon2::{to_value, Value};
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+///     admin: bool,
+/// }
+///
+/// let value = to_value(&User { name: "Ada".into(), admin: true }).unwrap();
+/// let user = value.as_object().unwrap();
+///
+/// assert_eq!(user["name"], Value::from("Ada"));
+/// assert_eq!(user["admin"], Value::Bool(true));
+/// ```
+pub fn to_value<T: ser::Serialize + ?Sized>(value: &T) -> Result<Value> {
     struct ValueSerializer;
 
     impl ser::Serializer for ValueSerializer {
@@ -1574,7 +1857,7 @@ fn to_value<T: ser::Serialize + ?Sized>(value: &T) -> Result<Value> {
             variant: &'static str,
             value: &T,
         ) -> Result<Value> {
-            let mut map = Map::new();
+            let mut map = Map::default();
             map.insert(variant.to_string(), to_value(value)?);
             Ok(Value::Object(map))
         }
@@ -1611,7 +1894,7 @@ fn to_value<T: ser::Serialize + ?Sized>(value: &T) -> Result<Value> {
 
         fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
             Ok(ValueMapSerializer {
-                entries: Map::new(),
+                entries: Map::default(),
                 current_key: None,
                 variant_key: None,
             })
@@ -1633,7 +1916,7 @@ fn to_value<T: ser::Serialize + ?Sized>(value: &T) -> Result<Value> {
             _len: usize,
         ) -> Result<Self::SerializeStructVariant> {
             Ok(ValueMapSerializer {
-                entries: Map::new(),
+                entries: Map::default(),
                 current_key: None,
                 variant_key: Some(variant.to_string()),
             })
@@ -1708,17 +1991,7 @@ fn to_value<T: ser::Serialize + ?Sized>(value: &T) -> Result<Value> {
         type Error = Error;
 
         fn serialize_key<T: ?Sized + ser::Serialize>(&mut self, key: &T) -> Result<()> {
-            let key_val = to_value(key)?;
-            let key_str = match key_val {
-                Value::String(s) => s,
-                Value::Number(Number::I64(n)) => n.to_string(),
-                Value::Number(Number::U64(n)) => n.to_string(),
-                Value::Number(Number::F64(n)) => n.to_string(),
-                Value::Bool(b) => b.to_string(),
-                Value::Null => "null".to_string(),
-                _ => return Err(Error::custom("map keys must be strings or primitives")),
-            };
-            self.current_key = Some(key_str);
+            self.current_key = Some(map_key(key)?);
             Ok(())
         }
 
@@ -1733,7 +2006,7 @@ fn to_value<T: ser::Serialize + ?Sized>(value: &T) -> Result<Value> {
 
         fn end(self) -> Result<Value> {
             if let Some(variant_key) = self.variant_key {
-                let mut outer_map = Map::new();
+                let mut outer_map = Map::default();
                 outer_map.insert(variant_key, Value::Object(self.entries));
                 Ok(Value::Object(outer_map))
             } else {
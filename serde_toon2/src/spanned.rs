@@ -0,0 +1,758 @@
+//! Values that remember where they were read from.
+//!
+//! A [`Spanned<T>`] field deserializes like a plain `T`, and also records the byte
+//! range, line and column of the value in the source text. This lets validation code
+//! that runs after decoding point at the offending input:
+//!
+//! ```
+//! use serde::Deserialize;
+//! use serde_toon2::{Spanned, from_str};
+//!
+//! #[derive(Deserialize)]
+//! struct Server {
+//!     host: String,
+//!     port: Spanned<u32>,
+//! }
+//!
+//! let input = "host: localhost\nport: 99999";
+//! let server: Server = from_str(input).unwrap();
+//!
+//! let port = &server.port;
+//! assert!(*port.get_ref() > 65535);
+//! assert_eq!((port.line(), port.column()), (2, 7));
+//! assert_eq!(&input[port.span()], "99999");
+//! ```
+//!
+//! Spans are only available when decoding TOON text with [`from_str`](crate::from_str)
+//! and the functions built on it. Deserializing a `Spanned` from a [`Value`] fails, as
+//! the value no longer knows where it came from.
+//!
+//! Serde buffers the input of `#[serde(flatten)]` fields and of untagged and internally
+//! tagged enums before handing it on, which drops the locations, so a `Spanned` inside
+//! one of those fails to deserialize. Keep `Spanned` fields out of flattened structs,
+//! and wrap such an enum as a whole, as in `Spanned<MyUntaggedEnum>`.
+//!
+//! A scalar's span covers its text as written, including any quotes. An object or an
+//! array that spans several lines covers those lines, from its first field or item to
+//! the end of its last one; an inline array covers its values. An object or array with
+//! nothing in it is an empty span at the end of its key's line.
+//!
+//! [`Value`]: crate::Value
+
+use crate::de::Tree;
+use crate::error::{Error, Result};
+use crate::value::{Map, Number, Value};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::cell::OnceCell;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::ops::Range;
+use serde::de::{self, IntoDeserializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, forward_to_deserialize_any};
+
+/// The struct name [`Spanned`] asks for. The TOON deserializer answers it with a map of
+/// the fields below instead of the value itself.
+pub(crate) const NAME: &str = "$__serde_toon2_private_Spanned";
+const START: &str = "$__serde_toon2_private_start";
+const END: &str = "$__serde_toon2_private_end";
+const LINE: &str = "$__serde_toon2_private_line";
+const COLUMN: &str = "$__serde_toon2_private_column";
+const VALUE: &str = "$__serde_toon2_private_value";
+const FIELDS: &[&str] = &[START, END, LINE, COLUMN, VALUE];
+
+/// Reported when a `Spanned` is deserialized from something that does not know where
+/// its values were read from.
+const NO_LOCATIONS: &str = "Spanned values can only be deserialized from TOON text";
+
+/// The error for a `Spanned` deserialized from a [`Value`](crate::Value).
+pub(crate) fn not_from_value() -> Error {
+    Error::custom(NO_LOCATIONS)
+}
+
+/// A deserialized value together with its location in the source text.
+///
+/// See the [module documentation](self) for details. Comparisons and hashing only look
+/// at the value, and it serializes as the value alone.
+#[derive(Clone, Debug)]
+pub struct Spanned<T> {
+    span: Range<usize>,
+    line: usize,
+    column: usize,
+    value: T,
+}
+
+impl<T> Spanned<T> {
+    /// Returns the byte range of the value in the source text.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the 1-based line the value starts on.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the 1-based column, in characters, the value starts at.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Returns a reference to the value.
+    pub fn get_ref(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns a mutable reference to the value.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+
+    /// Consumes the `Spanned`, returning the value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: PartialEq> PartialEq for Spanned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq> Eq for Spanned<T> {}
+
+impl<T: PartialOrd> PartialOrd for Spanned<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T: Ord> Ord for Spanned<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<T: Hash> Hash for Spanned<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+impl<T: Serialize> Serialize for Spanned<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Spanned<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> core::result::Result<Self, D::Error> {
+        deserializer.deserialize_struct(NAME, FIELDS, SpannedVisitor(PhantomData))
+    }
+}
+
+struct SpannedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> de::Visitor<'de> for SpannedVisitor<T> {
+    type Value = Spanned<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a spanned value")
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> core::result::Result<Spanned<T>, A::Error> {
+        let start = next_field(&mut map, START)?;
+        let end = next_field(&mut map, END)?;
+        Ok(Spanned {
+            span: start..end,
+            line: next_field(&mut map, LINE)?,
+            column: next_field(&mut map, COLUMN)?,
+            value: next_field(&mut map, VALUE)?,
+        })
+    }
+}
+
+fn next_field<'de, A, V>(map: &mut A, field: &'static str) -> core::result::Result<V, A::Error>
+where
+    A: de::MapAccess<'de>,
+    V: Deserialize<'de>,
+{
+    match map.next_key::<Cow<'_, str>>()? {
+        Some(key) if key == field => map.next_value(),
+        _ => Err(de::Error::custom(NO_LOCATIONS)),
+    }
+}
+
+/// A document tree that keeps the location of every value, which `from_str` decodes
+/// into before deserializing the target type, so any [`Spanned`] in it can be answered.
+///
+/// Spans are kept as addresses into the input until [`Source::locate`] turns them into
+/// offsets. The parser sets one on every value it reads; objects that path expansion
+/// creates have none and cover their children instead.
+pub(crate) struct Node<'a> {
+    kind: Kind<'a>,
+    span: Option<(usize, usize)>,
+}
+
+enum Kind<'a> {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(Cow<'a, str>),
+    Array(Vec<Node<'a>>),
+    Object(Map<Cow<'a, str>, Node<'a>>),
+}
+
+/// One step on the way from a node to one of its descendants.
+#[derive(Clone, Copy)]
+pub(crate) enum Step<'n> {
+    Key(&'n str),
+    Index(usize),
+}
+
+impl<'a> Node<'a> {
+    fn new(kind: Kind<'a>) -> Self {
+        Node { kind, span: None }
+    }
+
+    /// Converts the tree into a [`Value`], dropping the locations.
+    pub(crate) fn into_value(self) -> Value {
+        match self.kind {
+            Kind::Null => Value::Null,
+            Kind::Bool(b) => Value::Bool(b),
+            Kind::Number(n) => Value::Number(n),
+            Kind::String(s) => Value::String(s.into_owned()),
+            Kind::Array(items) => Value::Array(items.into_iter().map(Node::into_value).collect()),
+            Kind::Object(obj) => Value::Object(
+                obj.into_iter()
+                    .map(|(key, value)| (key.into_owned(), value.into_value()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Returns the path to the scalar read last from the input, if it starts at or after
+    /// the address `start`.
+    pub(crate) fn last_scalar_from(&self, start: usize) -> Option<Vec<Step<'_>>> {
+        let mut found = None;
+        self.find_last_scalar(start, &mut Vec::new(), &mut found);
+        found.map(|(_, path)| path)
+    }
+
+    fn find_last_scalar<'n>(
+        &'n self,
+        start: usize,
+        path: &mut Vec<Step<'n>>,
+        found: &mut Option<(usize, Vec<Step<'n>>)>,
+    ) {
+        match &self.kind {
+            Kind::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    path.push(Step::Index(i));
+                    item.find_last_scalar(start, path, found);
+                    path.pop();
+                }
+            }
+            Kind::Object(obj) => {
+                for (key, value) in obj {
+                    path.push(Step::Key(key));
+                    value.find_last_scalar(start, path, found);
+                    path.pop();
+                }
+            }
+            _ => {
+                if let Some((at, _)) = self.span
+                    && at >= start
+                    && found.as_ref().is_none_or(|&(last, _)| at >= last)
+                {
+                    *found = Some((at, path.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// The node's span, or for nodes without one the smallest span covering its children.
+fn span_of(node: &Node<'_>) -> Option<(usize, usize)> {
+    if node.span.is_some() {
+        return node.span;
+    }
+    let children: &mut dyn Iterator<Item = &Node<'_>> = match &node.kind {
+        Kind::Array(arr) => &mut arr.iter(),
+        Kind::Object(obj) => &mut obj.values(),
+        _ => return None,
+    };
+    children
+        .filter_map(span_of)
+        .reduce(|(start, end), (s, e)| (start.min(s), end.max(e)))
+}
+
+impl<'a> Tree<'a> for Node<'a> {
+    type Key = Cow<'a, str>;
+
+    fn key(key: Cow<'a, str>) -> Cow<'a, str> {
+        key
+    }
+
+    fn null() -> Self {
+        Node::new(Kind::Null)
+    }
+
+    fn bool(b: bool) -> Self {
+        Node::new(Kind::Bool(b))
+    }
+
+    fn number(n: Number) -> Self {
+        Node::new(Kind::Number(n))
+    }
+
+    fn string(s: Cow<'a, str>) -> Self {
+        Node::new(Kind::String(s))
+    }
+
+    fn array(items: Vec<Self>) -> Self {
+        Node::new(Kind::Array(items))
+    }
+
+    fn object(obj: Map<Cow<'a, str>, Self>) -> Self {
+        Node::new(Kind::Object(obj))
+    }
+
+    fn into_object(self) -> core::result::Result<Map<Cow<'a, str>, Self>, Self> {
+        match self.kind {
+            Kind::Object(obj) => Ok(obj),
+            kind => Err(Node { kind, ..self }),
+        }
+    }
+
+    fn into_array(self) -> core::result::Result<Vec<Self>, Self> {
+        match self.kind {
+            Kind::Array(arr) => Ok(arr),
+            kind => Err(Node { kind, ..self }),
+        }
+    }
+
+    fn as_object_mut(&mut self) -> Option<&mut Map<Cow<'a, str>, Self>> {
+        match &mut self.kind {
+            Kind::Object(obj) => Some(obj),
+            _ => None,
+        }
+    }
+
+    fn set_span(&mut self, first: &'a str, last: &'a str) {
+        let start = first.as_ptr() as usize;
+        let end = last.as_ptr() as usize + last.len();
+        self.span = Some((start, end));
+    }
+}
+
+/// Maps the addresses a [`Node`] keeps to offsets, lines and columns of the input.
+pub(crate) struct Source<'a> {
+    input: &'a str,
+    /// Byte offset of the start of each line, found on the first lookup so documents
+    /// without a `Spanned` never pay for it.
+    line_starts: OnceCell<Vec<usize>>,
+}
+
+impl<'a> Source<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        Source {
+            input,
+            line_starts: OnceCell::new(),
+        }
+    }
+
+    fn line_starts(&self) -> &[usize] {
+        self.line_starts.get_or_init(|| {
+            // A byte order mark is not part of the first line's columns
+            let first = if self.input.starts_with('\u{feff}') {
+                3
+            } else {
+                0
+            };
+            core::iter::once(first)
+                .chain(self.input.match_indices('\n').map(|(i, _)| i + 1))
+                .collect()
+        })
+    }
+
+    fn locate(&self, (start, end): (usize, usize)) -> (Range<usize>, usize, usize) {
+        let base = self.input.as_ptr() as usize;
+        let span = start - base..end - base;
+        let line_starts = self.line_starts();
+        let line = line_starts.partition_point(|&s| s <= span.start).max(1);
+        let line_start = line_starts[line - 1].min(span.start);
+        let column = self.input[line_start..span.start].chars().count() + 1;
+        (span, line, column)
+    }
+}
+
+/// Deserializes a [`Node`], answering [`Spanned`] fields with the node's location.
+pub(crate) struct NodeDeserializer<'a, 's> {
+    node: Node<'a>,
+    source: &'s Source<'a>,
+    /// Location of the closest enclosing node that has one, for nodes without their own.
+    outer: Option<(usize, usize)>,
+}
+
+impl<'a, 's> NodeDeserializer<'a, 's> {
+    pub(crate) fn new(node: Node<'a>, source: &'s Source<'a>) -> Self {
+        NodeDeserializer {
+            node,
+            source,
+            outer: None,
+        }
+    }
+}
+
+/// Describes a node for `invalid_type` errors.
+fn unexpected<'k>(kind: &'k Kind<'_>) -> de::Unexpected<'k> {
+    match kind {
+        Kind::Null => de::Unexpected::Unit,
+        Kind::Bool(b) => de::Unexpected::Bool(*b),
+        Kind::Number(Number::I64(n)) => de::Unexpected::Signed(*n),
+        Kind::Number(Number::U64(n)) => de::Unexpected::Unsigned(*n),
+        Kind::Number(Number::F64(n)) => de::Unexpected::Float(*n),
+        Kind::String(s) => de::Unexpected::Str(s),
+        Kind::Array(_) => de::Unexpected::Seq,
+        Kind::Object(_) => de::Unexpected::Map,
+    }
+}
+
+impl<'a> de::Deserializer<'a> for NodeDeserializer<'a, '_> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'a>,
+    {
+        let outer = self.node.span.or(self.outer);
+        match self.node.kind {
+            Kind::Null => visitor.visit_unit(),
+            Kind::Bool(b) => visitor.visit_bool(b),
+            Kind::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    visitor.visit_i64(i)
+                } else if let Some(u) = n.as_u64() {
+                    visitor.visit_u64(u)
+                } else {
+                    visitor.visit_f64(n.as_f64())
+                }
+            }
+            Kind::String(Cow::Borrowed(s)) => visitor.visit_borrowed_str(s),
+            Kind::String(Cow::Owned(s)) => visitor.visit_string(s),
+            Kind::Array(arr) => visitor.visit_seq(NodeSeq {
+                iter: arr.into_iter(),
+                source: self.source,
+                outer,
+            }),
+            Kind::Object(obj) => visitor.visit_map(NodeMap {
+                iter: obj.into_iter(),
+                value: None,
+                source: self.source,
+                outer,
+            }),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'a>,
+    {
+        match self.node.kind {
+            Kind::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'a>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'a>,
+    {
+        if name != NAME {
+            return self.deserialize_any(visitor);
+        }
+        let (span, line, column) = match span_of(&self.node).or(self.outer) {
+            Some(span) => self.source.locate(span),
+            None => (0..0, 1, 1),
+        };
+        visitor.visit_map(SpannedAccess {
+            fields: [span.start, span.end, line, column].into_iter(),
+            value: Some(self),
+        })
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'a>,
+    {
+        // Variants are read in the same shapes as from a `Value`
+        let source = self.source;
+        let outer = self.node.span.or(self.outer);
+        let fields = |node| NodeDeserializer {
+            node,
+            source,
+            outer,
+        };
+        let (variant, value) = match self.node.kind {
+            Kind::String(variant) => (variant, None),
+            Kind::Object(obj) if obj.len() == 1 => {
+                let (variant, value) = obj.into_iter().next().unwrap();
+                (variant, Some(fields(value)))
+            }
+            Kind::Array(mut arr)
+                if matches!(arr.first().map(|n| &n.kind), Some(Kind::String(_))) =>
+            {
+                let Kind::String(variant) = arr.remove(0).kind else {
+                    unreachable!()
+                };
+                (variant, Some(fields(Node::array(arr))))
+            }
+            kind => {
+                return Err(de::Error::invalid_type(
+                    unexpected(&kind),
+                    &"a string or an object with a single key",
+                ));
+            }
+        };
+        visitor.visit_enum(NodeEnum { variant, value })
+    }
+
+    forward_to_deserialize_any! {
+        <W: Visitor<'a>>
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map identifier ignored_any
+    }
+}
+
+/// Deserializes an object key, borrowing it from the input when it was not escaped.
+struct KeyDeserializer<'a>(Cow<'a, str>);
+
+impl<'a> de::Deserializer<'a> for KeyDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'a>,
+    {
+        match self.0 {
+            Cow::Borrowed(key) => visitor.visit_borrowed_str(key),
+            Cow::Owned(key) => visitor.visit_string(key),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        <W: Visitor<'a>>
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct NodeSeq<'a, 's> {
+    iter: alloc::vec::IntoIter<Node<'a>>,
+    source: &'s Source<'a>,
+    outer: Option<(usize, usize)>,
+}
+
+impl<'a> de::SeqAccess<'a> for NodeSeq<'a, '_> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'a>,
+    {
+        match self.iter.next() {
+            Some(node) => seed
+                .deserialize(NodeDeserializer {
+                    node,
+                    source: self.source,
+                    outer: self.outer,
+                })
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct NodeMap<'a, 's> {
+    iter: indexmap::map::IntoIter<Cow<'a, str>, Node<'a>>,
+    value: Option<Node<'a>>,
+    source: &'s Source<'a>,
+    outer: Option<(usize, usize)>,
+}
+
+impl<'a> de::MapAccess<'a> for NodeMap<'a, '_> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'a>,
+    {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(KeyDeserializer(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'a>,
+    {
+        match self.value.take() {
+            Some(node) => seed.deserialize(NodeDeserializer {
+                node,
+                source: self.source,
+                outer: self.outer,
+            }),
+            None => Err(Error::custom("Value is missing")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// Hands a [`Spanned`] its start, end, line and column, then the value itself.
+struct SpannedAccess<'a, 's> {
+    fields: core::array::IntoIter<usize, 4>,
+    value: Option<NodeDeserializer<'a, 's>>,
+}
+
+impl<'a> de::MapAccess<'a> for SpannedAccess<'a, '_> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'a>,
+    {
+        let key = match self.fields.len() {
+            4 => START,
+            3 => END,
+            2 => LINE,
+            1 => COLUMN,
+            _ if self.value.is_some() => VALUE,
+            _ => return Ok(None),
+        };
+        seed.deserialize(de::value::BorrowedStrDeserializer::new(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'a>,
+    {
+        if let Some(field) = self.fields.next() {
+            return seed.deserialize(field.into_deserializer());
+        }
+        match self.value.take() {
+            Some(value) => seed.deserialize(value),
+            None => Err(Error::custom("Value is missing")),
+        }
+    }
+}
+
+struct NodeEnum<'a, 's> {
+    variant: Cow<'a, str>,
+    value: Option<NodeDeserializer<'a, 's>>,
+}
+
+impl<'a, 's> de::EnumAccess<'a> for NodeEnum<'a, 's> {
+    type Error = Error;
+    type Variant = NodeVariant<'a, 's>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, NodeVariant<'a, 's>)>
+    where
+        V: de::DeserializeSeed<'a>,
+    {
+        let variant = seed.deserialize(KeyDeserializer(self.variant))?;
+        Ok((variant, NodeVariant(self.value)))
+    }
+}
+
+struct NodeVariant<'a, 's>(Option<NodeDeserializer<'a, 's>>);
+
+impl<'a> de::VariantAccess<'a> for NodeVariant<'a, '_> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        match self.0 {
+            None => Ok(()),
+            Some(value) => match value.node.kind {
+                Kind::Null => Ok(()),
+                kind => Err(de::Error::invalid_type(unexpected(&kind), &"unit variant")),
+            },
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: de::DeserializeSeed<'a>,
+    {
+        match self.0 {
+            Some(value) => seed.deserialize(value),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'a>,
+    {
+        match self.0 {
+            Some(value) => de::Deserializer::deserialize_any(value, visitor),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'a>,
+    {
+        match self.0 {
+            Some(value) => de::Deserializer::deserialize_any(value, visitor),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+}
@@ -0,0 +1,362 @@
+//! Token and size statistics for comparing TOON against JSON.
+//!
+//! The [`compare`] function encodes a [`Value`] as TOON, compact JSON and pretty JSON
+//! and reports the size of each rendering in bytes and estimated tokens, together with
+//! a breakdown for every top-level key of an object.
+//!
+//! Token counts are produced by a [`Tokenizer`]. The built-in [`ApproxTokenizer`] is a
+//! fast heuristic that needs no vocabulary files; plug in a real tokenizer through
+//! [`compare_with`] when exact counts matter.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde_toon2::{from_str, stats, EncoderOptions, Value};
+//!
+//! let value: Value = from_str("users[2]{id,name}:\n  1,Ada\n  2,Bob").unwrap();
+//! let report = stats::compare(&value, &EncoderOptions::default()).unwrap();
+//!
+//! assert!(report.toon.bytes < report.json_compact.bytes);
+//! assert!(report.toon.tokens < report.json_pretty.tokens);
+//! assert_eq!(report.keys[0].key, "users");
+//! ```
+
+use crate::error::Result;
+use crate::options::EncoderOptions;
+use crate::ser::to_string_with_options;
+use crate::value::{Map, Number, Value};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Counts the tokens a language model would see for a piece of text.
+///
+/// Implemented for [`ApproxTokenizer`] and for any `Fn(&str) -> usize`, so an exact
+/// tokenizer from another crate can be passed as a closure.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::stats::Tokenizer;
+///
+/// let words = |text: &str| text.split_whitespace().count();
+/// assert_eq!(words.count_tokens("one two three"), 3);
+/// ```
+pub trait Tokenizer {
+    /// Returns the number of tokens in `text`.
+    fn count_tokens(&self, text: &str) -> usize;
+}
+
+impl<F: Fn(&str) -> usize> Tokenizer for F {
+    fn count_tokens(&self, text: &str) -> usize {
+        self(text)
+    }
+}
+
+/// A fast, dependency-free token estimator.
+///
+/// The estimate follows the behaviour of common byte-pair encodings closely enough to
+/// compare formats against each other:
+///
+/// - runs of ASCII letters count one token per 4 characters
+/// - runs of digits count one token per 3 digits
+/// - every punctuation character, newline and non-ASCII character counts as one token
+/// - a single space is merged into the following word; longer runs of spaces or tabs
+///   count as one token
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::stats::{ApproxTokenizer, Tokenizer};
+///
+/// assert_eq!(ApproxTokenizer.count_tokens("name: Ada"), 3);
+/// assert_eq!(ApproxTokenizer.count_tokens("{\"name\":\"Ada\"}"), 9);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApproxTokenizer;
+
+impl Tokenizer for ApproxTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        let bytes = text.as_bytes();
+        let mut tokens = 0;
+        let mut i = 0;
+
+        while i < bytes.len() {
+            let b = bytes[i];
+            if b.is_ascii_alphabetic() || b == b'_' {
+                let start = i;
+                while i < bytes.len() && (bytes[i].is_ascii_alphabetic() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                tokens += (i - start).div_ceil(4);
+            } else if b.is_ascii_digit() {
+                let start = i;
+                while i < bytes.len() && bytes[i].is_ascii_digit() {
+                    i += 1;
+                }
+                tokens += (i - start).div_ceil(3);
+            } else if b == b' ' || b == b'\t' {
+                let start = i;
+                while i < bytes.len() && (bytes[i] == b' ' || bytes[i] == b'\t') {
+                    i += 1;
+                }
+                if i - start > 1 {
+                    tokens += 1;
+                }
+            } else if b.is_ascii() {
+                tokens += 1;
+                i += 1;
+            } else {
+                // Multi-byte UTF-8 sequence: count one token per character
+                tokens += 1;
+                i += 1;
+                while i < bytes.len() && (bytes[i] & 0xC0) == 0x80 {
+                    i += 1;
+                }
+            }
+        }
+
+        tokens
+    }
+}
+
+/// Size of one rendering of a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Measurement {
+    /// Length of the rendering in bytes.
+    pub bytes: usize,
+    /// Estimated number of tokens in the rendering.
+    pub tokens: usize,
+}
+
+impl Measurement {
+    /// Measures `text` with the given tokenizer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::stats::{ApproxTokenizer, Measurement};
+    ///
+    /// let m = Measurement::of("id: 1", &ApproxTokenizer);
+    /// assert_eq!(m.bytes, 5);
+    /// ```
+    pub fn of<T: Tokenizer + ?Sized>(text: &str, tokenizer: &T) -> Self {
+        Measurement {
+            bytes: text.len(),
+            tokens: tokenizer.count_tokens(text),
+        }
+    }
+
+    /// Returns the percentage of tokens saved compared to `baseline`.
+    ///
+    /// Negative values mean this rendering is larger than the baseline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::stats::Measurement;
+    ///
+    /// let toon = Measurement { bytes: 60, tokens: 30 };
+    /// let json = Measurement { bytes: 100, tokens: 40 };
+    /// assert_eq!(toon.token_savings(&json), 25.0);
+    /// ```
+    pub fn token_savings(&self, baseline: &Measurement) -> f64 {
+        if baseline.tokens == 0 {
+            return 0.0;
+        }
+        (1.0 - self.tokens as f64 / baseline.tokens as f64) * 100.0
+    }
+}
+
+/// Sizes of a single top-level entry in each format.
+///
+/// Each entry is measured as a one-key document, so the per-key figures include the
+/// framing of their format and do not sum exactly to the document totals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyStats {
+    /// The top-level key.
+    pub key: String,
+    /// The entry encoded as TOON.
+    pub toon: Measurement,
+    /// The entry encoded as compact JSON.
+    pub json_compact: Measurement,
+    /// The entry encoded as pretty-printed JSON.
+    pub json_pretty: Measurement,
+}
+
+/// Result of comparing TOON with JSON for one value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    /// The value encoded as TOON.
+    pub toon: Measurement,
+    /// The value encoded as compact JSON.
+    pub json_compact: Measurement,
+    /// The value encoded as pretty-printed JSON (two-space indentation).
+    pub json_pretty: Measurement,
+    /// Per-key breakdown; empty unless the value is an object.
+    pub keys: Vec<KeyStats>,
+}
+
+/// Compares TOON with compact and pretty JSON using the [`ApproxTokenizer`].
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::{stats, EncoderOptions, Value};
+///
+/// let value = Value::from(vec![1i64, 2, 3]);
+/// let report = stats::compare(&value, &EncoderOptions::default()).unwrap();
+///
+/// assert_eq!(report.toon.bytes, "[3]: 1,2,3".len());
+/// assert_eq!(report.json_compact.bytes, "[1,2,3]".len());
+/// assert!(report.keys.is_empty());
+/// ```
+pub fn compare(value: &Value, options: &EncoderOptions) -> Result<Comparison> {
+    compare_with(value, options, &ApproxTokenizer)
+}
+
+/// Compares TOON with compact and pretty JSON using a custom tokenizer.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::{stats, EncoderOptions, Value};
+///
+/// let chars = |text: &str| text.chars().count();
+/// let value = Value::from("hello");
+/// let report = stats::compare_with(&value, &EncoderOptions::default(), &chars).unwrap();
+///
+/// assert_eq!(report.toon.tokens, 5);
+/// assert_eq!(report.json_compact.tokens, 7);
+/// ```
+pub fn compare_with<T: Tokenizer + ?Sized>(
+    value: &Value,
+    options: &EncoderOptions,
+    tokenizer: &T,
+) -> Result<Comparison> {
+    let (toon, json_compact, json_pretty) = measure_all(value, options, tokenizer)?;
+
+    let mut keys = Vec::new();
+    if let Value::Object(obj) = value {
+        for (key, val) in obj {
+            let mut entry = Map::default();
+            entry.insert(key.clone(), val.clone());
+            let (toon, json_compact, json_pretty) =
+                measure_all(&Value::Object(entry), options, tokenizer)?;
+            keys.push(KeyStats {
+                key: key.clone(),
+                toon,
+                json_compact,
+                json_pretty,
+            });
+        }
+    }
+
+    Ok(Comparison {
+        toon,
+        json_compact,
+        json_pretty,
+        keys,
+    })
+}
+
+fn measure_all<T: Tokenizer + ?Sized>(
+    value: &Value,
+    options: &EncoderOptions,
+    tokenizer: &T,
+) -> Result<(Measurement, Measurement, Measurement)> {
+    let toon = to_string_with_options(value, options.clone())?;
+
+    let mut compact = String::new();
+    write_json(&mut compact, value, None, 0);
+    let mut pretty = String::new();
+    write_json(&mut pretty, value, Some(2), 0);
+
+    Ok((
+        Measurement::of(&toon, tokenizer),
+        Measurement::of(&compact, tokenizer),
+        Measurement::of(&pretty, tokenizer),
+    ))
+}
+
+fn write_json(out: &mut String, value: &Value, indent: Option<usize>, depth: usize) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => write_json_number(out, n),
+        Value::String(s) => write_json_string(out, s),
+        Value::Array(arr) => {
+            if arr.is_empty() {
+                out.push_str("[]");
+                return;
+            }
+            out.push('[');
+            for (i, item) in arr.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_json_newline(out, indent, depth + 1);
+                write_json(out, item, indent, depth + 1);
+            }
+            write_json_newline(out, indent, depth);
+            out.push(']');
+        }
+        Value::Object(obj) => {
+            if obj.is_empty() {
+                out.push_str("{}");
+                return;
+            }
+            out.push('{');
+            for (i, (key, val)) in obj.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_json_newline(out, indent, depth + 1);
+                write_json_string(out, key);
+                out.push(':');
+                if indent.is_some() {
+                    out.push(' ');
+                }
+                write_json(out, val, indent, depth + 1);
+            }
+            write_json_newline(out, indent, depth);
+            out.push('}');
+        }
+    }
+}
+
+fn write_json_newline(out: &mut String, indent: Option<usize>, depth: usize) {
+    if let Some(width) = indent {
+        out.push('\n');
+        for _ in 0..width * depth {
+            out.push(' ');
+        }
+    }
+}
+
+fn write_json_number(out: &mut String, n: &Number) {
+    match n {
+        Number::I64(i) => out.push_str(&i.to_string()),
+        Number::U64(u) => out.push_str(&u.to_string()),
+        Number::F64(f) if f.is_finite() => out.push_str(&format!("{:?}", f)),
+        Number::F64(_) => out.push_str("null"),
+    }
+}
+
+fn write_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0C}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
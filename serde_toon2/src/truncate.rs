@@ -0,0 +1,332 @@
+//! Size-limited encoding for tight context windows.
+//!
+//! [`to_string_truncated`] caps arrays to their first rows and shortens long strings
+//! before encoding. Array headers always reflect the rows that were kept, so the output
+//! still decodes. When a token budget is set, the limits are halved step by step until
+//! the estimated size of the output fits.
+//!
+//! Every cut is reported as an [`Elision`] with the JSON Pointer (RFC 6901) of the
+//! array or string that was shortened.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde_toon2::truncate::{to_string_truncated, TruncateOptions};
+//! use serde_toon2::{EncoderOptions, Value};
+//!
+//! let value = Value::from(vec![1i64, 2, 3, 4, 5]);
+//! let opts = TruncateOptions {
+//!     max_rows: Some(2),
+//!     ..Default::default()
+//! };
+//!
+//! let out = to_string_truncated(&value, &EncoderOptions::default(), &opts).unwrap();
+//! assert_eq!(out.toon, "[2]: 1,2");
+//! assert_eq!(out.elisions[0].path, "");
+//! ```
+
+use crate::error::Result;
+use crate::options::EncoderOptions;
+use crate::ser::to_string_with_options;
+use crate::stats::{ApproxTokenizer, Tokenizer};
+use crate::value::{Map, Value, push_pointer_token};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Smallest number of rows kept when shrinking to fit a token budget.
+const MIN_ROWS: usize = 1;
+
+/// Smallest string length, in characters, kept when shrinking to fit a token budget.
+const MIN_STRING_LEN: usize = 8;
+
+/// Limits applied by [`to_string_truncated`].
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::truncate::TruncateOptions;
+///
+/// let opts = TruncateOptions {
+///     max_rows: Some(20),
+///     max_string_len: Some(200),
+///     token_budget: Some(4000),
+///     marker: "…".to_string(),
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub struct TruncateOptions {
+    /// Maximum number of elements kept in any array.
+    ///
+    /// Default: `None` (no limit)
+    pub max_rows: Option<usize>,
+
+    /// Maximum number of characters kept in any string value, excluding the marker.
+    ///
+    /// Default: `None` (no limit)
+    pub max_string_len: Option<usize>,
+
+    /// Estimated token count the output should fit in.
+    ///
+    /// Default: `None` (no budget)
+    pub token_budget: Option<usize>,
+
+    /// Text appended to shortened strings.
+    ///
+    /// Default: `"…"`
+    pub marker: String,
+}
+
+impl Default for TruncateOptions {
+    fn default() -> Self {
+        TruncateOptions {
+            max_rows: None,
+            max_string_len: None,
+            token_budget: None,
+            marker: "…".to_string(),
+        }
+    }
+}
+
+/// What was removed at a particular location.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElisionKind {
+    /// An array was cut down to its first `kept` of `total` elements.
+    Rows {
+        /// Number of elements kept.
+        kept: usize,
+        /// Number of elements in the original array.
+        total: usize,
+    },
+    /// A string was cut down to its first `kept` of `total` characters.
+    String {
+        /// Number of characters kept.
+        kept: usize,
+        /// Number of characters in the original string.
+        total: usize,
+    },
+}
+
+/// A single cut made while truncating.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Elision {
+    /// JSON Pointer to the shortened array or string in the original value.
+    pub path: String,
+    /// What was removed.
+    pub kind: ElisionKind,
+}
+
+/// Output of [`to_string_truncated`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Truncated {
+    /// The encoded TOON document.
+    pub toon: String,
+    /// Estimated number of tokens in `toon`.
+    pub tokens: usize,
+    /// Every cut made to produce `toon`, in document order.
+    pub elisions: Vec<Elision>,
+    /// Whether `toon` fits the token budget; always `true` without a budget.
+    pub fits_budget: bool,
+}
+
+/// Applies row and string limits to a value.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::truncate::{truncate, ElisionKind};
+/// use serde_toon2::Value;
+///
+/// let value = Value::from("abcdefghij");
+/// let (short, elisions) = truncate(&value, None, Some(4), "...");
+///
+/// assert_eq!(short, Value::from("abcd..."));
+/// assert_eq!(elisions[0].kind, ElisionKind::String { kept: 4, total: 10 });
+/// ```
+pub fn truncate(
+    value: &Value,
+    max_rows: Option<usize>,
+    max_string_len: Option<usize>,
+    marker: &str,
+) -> (Value, Vec<Elision>) {
+    let mut elisions = Vec::new();
+    let mut path = String::new();
+    let result = truncate_at(
+        value,
+        max_rows,
+        max_string_len,
+        marker,
+        &mut path,
+        &mut elisions,
+    );
+    (result, elisions)
+}
+
+/// Encodes a value as TOON within the given limits, estimating tokens with the
+/// [`ApproxTokenizer`].
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::truncate::{to_string_truncated, TruncateOptions};
+/// use serde_toon2::{from_str, EncoderOptions, Value};
+///
+/// let rows: Vec<Value> = (0..100i64).map(Value::from).collect();
+/// let value = Value::Array(rows);
+/// let opts = TruncateOptions {
+///     token_budget: Some(40),
+///     ..Default::default()
+/// };
+///
+/// let out = to_string_truncated(&value, &EncoderOptions::default(), &opts).unwrap();
+/// assert!(out.fits_budget);
+/// assert!(out.tokens <= 40);
+///
+/// // The header matches the rows that were kept
+/// let decoded: Value = from_str(&out.toon).unwrap();
+/// assert!(decoded.as_array().unwrap().len() < 100);
+/// ```
+pub fn to_string_truncated(
+    value: &Value,
+    encoder: &EncoderOptions,
+    options: &TruncateOptions,
+) -> Result<Truncated> {
+    to_string_truncated_with(value, encoder, options, &ApproxTokenizer)
+}
+
+/// Encodes a value as TOON within the given limits, estimating tokens with a custom
+/// tokenizer.
+pub fn to_string_truncated_with<T: Tokenizer + ?Sized>(
+    value: &Value,
+    encoder: &EncoderOptions,
+    options: &TruncateOptions,
+    tokenizer: &T,
+) -> Result<Truncated> {
+    let mut max_rows = options.max_rows;
+    let mut max_string_len = options.max_string_len;
+
+    loop {
+        let (truncated, elisions) = truncate(value, max_rows, max_string_len, &options.marker);
+        let toon = to_string_with_options(&truncated, encoder.clone())?;
+        let tokens = tokenizer.count_tokens(&toon);
+
+        let budget = match options.token_budget {
+            Some(budget) if tokens > budget => budget,
+            _ => {
+                return Ok(Truncated {
+                    toon,
+                    tokens,
+                    elisions,
+                    fits_budget: true,
+                });
+            }
+        };
+
+        let next_rows = shrink(max_rows, longest_array(value), MIN_ROWS);
+        let next_string_len = shrink(max_string_len, longest_string(value), MIN_STRING_LEN);
+
+        if next_rows == max_rows && next_string_len == max_string_len {
+            return Ok(Truncated {
+                toon,
+                tokens,
+                elisions,
+                fits_budget: tokens <= budget,
+            });
+        }
+
+        max_rows = next_rows;
+        max_string_len = next_string_len;
+    }
+}
+
+/// Halves a limit, starting from the largest size present in the value.
+fn shrink(limit: Option<usize>, largest: usize, floor: usize) -> Option<usize> {
+    let current = limit.unwrap_or(largest).min(largest);
+    if current <= floor {
+        return limit;
+    }
+    Some((current / 2).max(floor))
+}
+
+fn truncate_at(
+    value: &Value,
+    max_rows: Option<usize>,
+    max_string_len: Option<usize>,
+    marker: &str,
+    path: &mut String,
+    elisions: &mut Vec<Elision>,
+) -> Value {
+    match value {
+        Value::String(s) => match max_string_len {
+            Some(limit) if s.chars().count() > limit => {
+                let total = s.chars().count();
+                let mut short: String = s.chars().take(limit).collect();
+                short.push_str(marker);
+                elisions.push(Elision {
+                    path: path.clone(),
+                    kind: ElisionKind::String { kept: limit, total },
+                });
+                Value::String(short)
+            }
+            _ => value.clone(),
+        },
+        Value::Array(arr) => {
+            let kept = max_rows.map_or(arr.len(), |limit| limit.min(arr.len()));
+            if kept < arr.len() {
+                elisions.push(Elision {
+                    path: path.clone(),
+                    kind: ElisionKind::Rows {
+                        kept,
+                        total: arr.len(),
+                    },
+                });
+            }
+
+            let mut items = Vec::with_capacity(kept);
+            for (i, item) in arr.iter().take(kept).enumerate() {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&i.to_string());
+                items.push(truncate_at(
+                    item,
+                    max_rows,
+                    max_string_len,
+                    marker,
+                    path,
+                    elisions,
+                ));
+                path.truncate(len);
+            }
+            Value::Array(items)
+        }
+        Value::Object(obj) => {
+            let mut result = Map::default();
+            for (key, val) in obj {
+                let len = path.len();
+                path.push('/');
+                push_pointer_token(path, key);
+                let truncated = truncate_at(val, max_rows, max_string_len, marker, path, elisions);
+                path.truncate(len);
+                result.insert(key.clone(), truncated);
+            }
+            Value::Object(result)
+        }
+        _ => value.clone(),
+    }
+}
+
+fn longest_array(value: &Value) -> usize {
+    match value {
+        Value::Array(arr) => arr.iter().map(longest_array).fold(arr.len(), usize::max),
+        Value::Object(obj) => obj.values().map(longest_array).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+fn longest_string(value: &Value) -> usize {
+    match value {
+        Value::String(s) => s.chars().count(),
+        Value::Array(arr) => arr.iter().map(longest_string).max().unwrap_or(0),
+        Value::Object(obj) => obj.values().map(longest_string).max().unwrap_or(0),
+        _ => 0,
+    }
+}
@@ -2,15 +2,19 @@
 //!
 //! The [`Value`] enum represents any valid TOON value, similar to `serde_json::Value`.
 
-use indexmap::IndexMap;
+use crate::error::Error;
+use crate::ser::format_number;
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::str::FromStr;
+use core::{fmt, mem, ops};
 use serde::{Deserialize, Serialize};
-use std::fmt;
 
-/// An order-preserving map type used for TOON objects.
-///
-/// Uses [`IndexMap`] to preserve the insertion order of keys, which is important
-/// for maintaining consistent serialization output.
-pub type Map<K, V> = IndexMap<K, V>;
+pub use crate::map::Map;
+pub use indexmap::map::Entry;
 
 /// Represents any valid TOON value.
 ///
@@ -31,7 +35,15 @@ pub type Map<K, V> = IndexMap<K, V>;
 /// map.insert("age".to_string(), Value::Number(Number::U64(42)));
 /// let obj = Value::Object(map);
 /// ```
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+///
+/// # Equality, ordering and hashing
+///
+/// `Value` implements `Eq`, `Ord` and `Hash`, so it can be used as a `HashSet` or
+/// `BTreeMap` key. Numbers compare by numeric value as described on [`Number`]. Values
+/// of different kinds are ordered `Null < Bool < Number < String < Array < Object`.
+/// Arrays compare element by element, and objects compare as their entries sorted by
+/// key, so key order does not affect equality, ordering or hashes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(untagged)]
 pub enum Value {
     /// Represents a null value.
@@ -65,7 +77,28 @@ pub enum Value {
 /// assert_eq!(uint.as_u64(), Some(42));
 /// assert_eq!(float.as_f64(), 3.14);
 /// ```
-#[derive(Debug, Clone, PartialEq)]
+///
+/// # Equality, ordering and hashing
+///
+/// Numbers compare by numeric value, whatever their variant, with a total order:
+///
+/// - `I64`, `U64` and `F64` values are compared exactly, so `I64(1)`, `U64(1)` and
+///   `F64(1.0)` are equal, while `I64(2^53 + 1)` is greater than `F64(2^53)`.
+/// - `-0.0` equals `0.0`.
+/// - NaN equals itself and is greater than every other number, including infinity.
+///
+/// `Hash` agrees with this equality, so `Number` can be used as a `HashSet` or
+/// `BTreeMap` key.
+///
+/// ```
+/// use serde_toon2::Number;
+///
+/// assert_eq!(Number::I64(1), Number::F64(1.0));
+/// assert_eq!(Number::F64(-0.0), Number::U64(0));
+/// assert!(Number::F64(f64::INFINITY) < Number::F64(f64::NAN));
+/// assert!(Number::U64(u64::MAX) > Number::I64(-1));
+/// ```
+#[derive(Debug, Clone)]
 pub enum Number {
     /// A signed 64-bit integer.
     I64(i64),
@@ -95,7 +128,7 @@ impl Number {
             Number::I64(n) => Some(*n),
             Number::U64(n) => i64::try_from(*n).ok(),
             Number::F64(n) => {
-                if n.fract() == 0.0 && *n >= i64::MIN as f64 && *n <= i64::MAX as f64 {
+                if is_integral(*n) && *n >= i64::MIN as f64 && *n <= i64::MAX as f64 {
                     Some(*n as i64)
                 } else {
                     None
@@ -124,7 +157,7 @@ impl Number {
             Number::I64(n) => u64::try_from(*n).ok(),
             Number::U64(n) => Some(*n),
             Number::F64(n) => {
-                if n.fract() == 0.0 && *n >= 0.0 && *n <= u64::MAX as f64 {
+                if is_integral(*n) && *n >= 0.0 && *n <= u64::MAX as f64 {
                     Some(*n as u64)
                 } else {
                     None
@@ -198,6 +231,96 @@ impl Number {
     pub fn is_f64(&self) -> bool {
         matches!(self, Number::F64(_))
     }
+
+    /// Returns an integer as `i128`, or a float as is.
+    fn as_i128(&self) -> core::result::Result<i128, f64> {
+        match self {
+            Number::I64(n) => Ok(*n as i128),
+            Number::U64(n) => Ok(*n as i128),
+            Number::F64(f) => Err(*f),
+        }
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Number {}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Number {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.as_i128(), other.as_i128()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            (Ok(a), Err(b)) => cmp_int_float(a, b),
+            (Err(a), Ok(b)) => cmp_int_float(b, a).reverse(),
+            (Err(a), Err(b)) => match (a.is_nan(), b.is_nan()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                // Neither is NaN, and -0.0 compares equal to 0.0
+                (false, false) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            },
+        }
+    }
+}
+
+impl Hash for Number {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Numbers equal to an integer hash as that integer, whatever their variant
+        match self {
+            Number::I64(n) => hash_int(*n as i128, state),
+            Number::U64(n) => hash_int(*n as i128, state),
+            Number::F64(f) if f.is_nan() => state.write_u8(2),
+            Number::F64(f) if is_integral(*f) && (I64_MIN..U64_END).contains(f) => {
+                hash_int(*f as i128, state)
+            }
+            Number::F64(f) => {
+                state.write_u8(1);
+                state.write_u64(f.to_bits());
+            }
+        }
+    }
+}
+
+fn hash_int<H: Hasher>(n: i128, state: &mut H) {
+    state.write_u8(0);
+    state.write_i128(n);
+}
+
+/// `i64::MIN` as a float.
+const I64_MIN: f64 = -9_223_372_036_854_775_808.0;
+/// One past `u64::MAX` as a float, 2^64.
+const U64_END: f64 = 18_446_744_073_709_551_616.0;
+
+/// Compares an integer in the `i64` or `u64` range with a float, exactly.
+fn cmp_int_float(int: i128, float: f64) -> Ordering {
+    if float.is_nan() || float >= U64_END {
+        return Ordering::Less;
+    }
+    if float < I64_MIN {
+        return Ordering::Greater;
+    }
+    // Truncation is exact and representable in both types within this range
+    let truncated = float as i128;
+    int.cmp(&truncated).then_with(|| {
+        let fraction = float - truncated as f64;
+        if fraction > 0.0 {
+            Ordering::Less
+        } else if fraction < 0.0 {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    })
 }
 
 impl Serialize for Number {
@@ -244,6 +367,87 @@ impl<'de> Deserialize<'de> for Number {
     }
 }
 
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct ValueVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for ValueVisitor {
+            type Value = Value;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("any valid TOON value")
+            }
+
+            fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+                Ok(Value::Bool(value))
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+                Ok(Value::Number(Number::I64(value)))
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+                Ok(Value::Number(Number::U64(value)))
+            }
+
+            fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+                Ok(Value::Number(Number::F64(value)))
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Value, E> {
+                Ok(Value::String(value.to_string()))
+            }
+
+            fn visit_string<E>(self, value: String) -> Result<Value, E> {
+                Ok(Value::String(value))
+            }
+
+            fn visit_none<E>(self) -> Result<Value, E> {
+                Ok(Value::Null)
+            }
+
+            fn visit_some<D>(self, deserializer: D) -> Result<Value, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                Deserialize::deserialize(deserializer)
+            }
+
+            fn visit_unit<E>(self) -> Result<Value, E> {
+                Ok(Value::Null)
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                // Cap the preallocation so a bogus size hint cannot exhaust memory.
+                let mut vec = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+                while let Some(element) = seq.next_element()? {
+                    vec.push(element);
+                }
+                Ok(Value::Array(vec))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let mut object = Map::default();
+                while let Some((key, value)) = map.next_entry::<String, Value>()? {
+                    object.insert(key, value);
+                }
+                Ok(Value::Object(object))
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
 impl Value {
     /// Returns `true` if this value is null.
     ///
@@ -397,6 +601,336 @@ impl Value {
             _ => None,
         }
     }
+
+    /// If this value is an array, returns a mutable reference to it. Otherwise returns
+    /// `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::toon;
+    ///
+    /// let mut v = toon!(["a"]);
+    /// v.as_array_mut().unwrap().push(toon!("b"));
+    /// assert_eq!(v, toon!(["a", "b"]));
+    /// ```
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Value>> {
+        match self {
+            Value::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    /// If this value is an object, returns a mutable reference to it. Otherwise returns
+    /// `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::toon;
+    ///
+    /// let mut v = toon!({ "a": 1 });
+    /// v.as_object_mut().unwrap().shift_remove("a");
+    /// assert_eq!(v, toon!({}));
+    /// ```
+    pub fn as_object_mut(&mut self) -> Option<&mut Map<String, Value>> {
+        match self {
+            Value::Object(obj) => Some(obj),
+            _ => None,
+        }
+    }
+
+    /// Returns the element at an array index or the value for an object key, or `None`
+    /// if there is none.
+    ///
+    /// Unlike indexing with `value[index]`, which returns [`Value::Null`] for anything
+    /// missing, this tells a missing entry apart from a `null` one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::toon;
+    ///
+    /// let v = toon!({ "users": [{ "name": "Ada" }], "manager": null });
+    ///
+    /// assert_eq!(v.get("users").and_then(|users| users.get(0)), Some(&toon!({ "name": "Ada" })));
+    /// assert_eq!(v.get("manager"), Some(&toon!(null)));
+    /// assert_eq!(v.get("missing"), None);
+    /// assert_eq!(v.get(0), None);
+    /// ```
+    pub fn get<I: Index>(&self, index: I) -> Option<&Value> {
+        index.index_into(self)
+    }
+
+    /// Mutable version of [`get`](Value::get).
+    pub fn get_mut<I: Index>(&mut self, index: I) -> Option<&mut Value> {
+        index.index_into_mut(self)
+    }
+
+    /// Looks up a value by a JSON Pointer (RFC 6901), such as `/users/0/name`.
+    ///
+    /// Tokens are separated by `/`, with `~1` standing for `/` and `~0` for `~` within a
+    /// token. The empty pointer refers to the whole value. Returns `None` if the pointer
+    /// is malformed or leads nowhere.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::toon;
+    ///
+    /// let v = toon!({ "users": [{ "name": "Ada" }], "a/b": 1 });
+    ///
+    /// assert_eq!(v.pointer("/users/0/name"), Some(&toon!("Ada")));
+    /// assert_eq!(v.pointer("/a~1b"), Some(&toon!(1)));
+    /// assert_eq!(v.pointer("/users/1"), None);
+    /// assert_eq!(v.pointer(""), Some(&v));
+    /// ```
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .map(unescape_pointer_token)
+            .try_fold(self, |target, token| match target {
+                Value::Object(obj) => obj.get(&*token),
+                Value::Array(arr) => parse_pointer_index(&token).and_then(|i| arr.get(i)),
+                _ => None,
+            })
+    }
+
+    /// Mutable version of [`pointer`](Value::pointer).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::toon;
+    ///
+    /// let mut v = toon!({ "users": [{ "name": "Ada" }] });
+    /// *v.pointer_mut("/users/0/name").unwrap() = toon!("Bob");
+    /// assert_eq!(v["users"][0]["name"], "Bob");
+    /// ```
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .map(unescape_pointer_token)
+            .try_fold(self, |target, token| match target {
+                Value::Object(obj) => obj.get_mut(&*token),
+                Value::Array(arr) => parse_pointer_index(&token).and_then(|i| arr.get_mut(i)),
+                _ => None,
+            })
+    }
+
+    /// Takes the value out, leaving [`Value::Null`] in its place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::toon;
+    ///
+    /// let mut v = toon!({ "rows": [1, 2] });
+    /// let rows = v["rows"].take();
+    ///
+    /// assert_eq!(rows, toon!([1, 2]));
+    /// assert_eq!(v, toon!({ "rows": null }));
+    /// ```
+    pub fn take(&mut self) -> Value {
+        mem::replace(self, Value::Null)
+    }
+
+    /// Gets the entry for `key` in an object, for in-place insertion or update.
+    ///
+    /// A `null` value is first replaced with an empty object. Returns `None` for any
+    /// other value that is not an object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::{toon, Value};
+    ///
+    /// let mut v = Value::Null;
+    /// v.entry("hits").unwrap().or_insert(toon!(0));
+    /// *v.entry("hits").unwrap().or_insert(toon!(0)) = toon!(1);
+    /// v.entry("tags").unwrap().or_insert_with(|| toon!([]));
+    ///
+    /// assert_eq!(v, toon!({ "hits": 1, "tags": [] }));
+    /// assert!(toon!([1]).entry("hits").is_none());
+    /// ```
+    pub fn entry<S: Into<String>>(&mut self, key: S) -> Option<Entry<'_, String, Value>> {
+        if self.is_null() {
+            *self = Value::Object(Map::default());
+        }
+        match self {
+            Value::Object(obj) => Some(obj.entry(key.into())),
+            _ => None,
+        }
+    }
+
+    /// Position of the value's kind in the order between kinds.
+    fn rank(&self) -> u8 {
+        match self {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Number(_) => 2,
+            Value::String(_) => 3,
+            Value::Array(_) => 4,
+            Value::Object(_) => 5,
+        }
+    }
+
+    /// Describes the kind of value, for panic messages.
+    fn kind_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "a boolean",
+            Value::Number(_) => "a number",
+            Value::String(_) => "a string",
+            Value::Array(_) => "an array",
+            Value::Object(_) => "an object",
+        }
+    }
+
+    /// Returns a hash of this value's content that is stable across platforms,
+    /// processes and crate versions.
+    ///
+    /// Object keys are hashed in sorted order and numbers in their normalised form, so
+    /// values that encode to the same TOON with [`EncoderOptions::canonical`] have the
+    /// same hash. This makes the hash suitable as a cache key.
+    ///
+    /// The hash is 128-bit FNV-1a over the following encoding, with lengths written as
+    /// little-endian `u64`:
+    ///
+    /// - null (including non-finite numbers): `0x00`
+    /// - `false`: `0x01`, `true`: `0x02`
+    /// - number: `0x03`, length, normalised decimal text (`1.0` as `1`, `-0.0` as `0`)
+    /// - string: `0x04`, length, UTF-8 bytes
+    /// - array: `0x05`, element count, elements
+    /// - object: `0x06`, entry count, then each key (length, UTF-8 bytes) and value,
+    ///   sorted by key bytes
+    ///
+    /// [`EncoderOptions::canonical`]: crate::EncoderOptions::canonical
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_toon2::{from_str, Value};
+    ///
+    /// let a: Value = from_str("id: 1\nname: Ada").unwrap();
+    /// let b: Value = from_str("name: Ada\nid: 1.0").unwrap();
+    /// let c: Value = from_str("id: 2\nname: Ada").unwrap();
+    ///
+    /// assert_eq!(a.content_hash(), b.content_hash());
+    /// assert_ne!(a.content_hash(), c.content_hash());
+    /// ```
+    pub fn content_hash(&self) -> u128 {
+        let mut hasher = Fnv128::new();
+        self.hash_content(&mut hasher);
+        hasher.finish()
+    }
+
+    fn hash_content(&self, hasher: &mut Fnv128) {
+        match self {
+            Value::Null => hasher.write(&[0x00]),
+            Value::Bool(false) => hasher.write(&[0x01]),
+            Value::Bool(true) => hasher.write(&[0x02]),
+            Value::Number(Number::F64(f)) if !f.is_finite() => hasher.write(&[0x00]),
+            Value::Number(n) => {
+                hasher.write(&[0x03]);
+                hasher.write_str(&format_number(n));
+            }
+            Value::String(s) => {
+                hasher.write(&[0x04]);
+                hasher.write_str(s);
+            }
+            Value::Array(arr) => {
+                hasher.write(&[0x05]);
+                hasher.write_len(arr.len());
+                for item in arr {
+                    item.hash_content(hasher);
+                }
+            }
+            Value::Object(obj) => {
+                hasher.write(&[0x06]);
+                hasher.write_len(obj.len());
+                for (key, val) in sorted_entries(obj) {
+                    hasher.write_str(key);
+                    val.hash_content(hasher);
+                }
+            }
+        }
+    }
+}
+
+/// Returns whether a float has no fractional part. False for NaN and infinities.
+///
+/// Equivalent to `f.fract() == 0.0`, which needs `std`.
+pub(crate) fn is_integral(f: f64) -> bool {
+    f % 1.0 == 0.0
+}
+
+/// Appends `key` to a JSON Pointer (RFC 6901), escaping `~` and `/`.
+pub(crate) fn push_pointer_token(path: &mut String, key: &str) {
+    for ch in key.chars() {
+        match ch {
+            '~' => path.push_str("~0"),
+            '/' => path.push_str("~1"),
+            _ => path.push(ch),
+        }
+    }
+}
+
+/// Decodes one JSON Pointer token, replacing `~1` with `/` and `~0` with `~`.
+pub(crate) fn unescape_pointer_token(token: &str) -> Cow<'_, str> {
+    if token.contains('~') {
+        Cow::Owned(token.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+/// Parses an array index token, which RFC 6901 allows only without leading zeros.
+pub(crate) fn parse_pointer_index(token: &str) -> Option<usize> {
+    if token.starts_with('+') || (token.starts_with('0') && token.len() > 1) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+/// 128-bit FNV-1a, used for [`Value::content_hash`].
+struct Fnv128(u128);
+
+impl Fnv128 {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    fn new() -> Self {
+        Fnv128(Self::OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u128;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_len(&mut self, len: usize) {
+        self.write(&(len as u64).to_le_bytes());
+    }
+
+    fn write_str(&mut self, s: &str) {
+        self.write_len(s.len());
+        self.write(s.as_bytes());
+    }
+
+    fn finish(&self) -> u128 {
+        self.0
+    }
 }
 
 impl From<bool> for Value {
@@ -446,3 +980,317 @@ impl From<Map<String, Value>> for Value {
         Value::Object(m)
     }
 }
+
+impl Eq for Value {}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Number(a), Value::Number(b)) => a.cmp(b),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::Array(a), Value::Array(b)) => a.cmp(b),
+            (Value::Object(a), Value::Object(b)) => sorted_entries(a).cmp(&sorted_entries(b)),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u8(self.rank());
+        match self {
+            Value::Null => {}
+            Value::Bool(b) => b.hash(state),
+            Value::Number(n) => n.hash(state),
+            Value::String(s) => s.hash(state),
+            Value::Array(arr) => arr.hash(state),
+            Value::Object(obj) => sorted_entries(obj).hash(state),
+        }
+    }
+}
+
+/// Returns an object's entries sorted by key.
+fn sorted_entries(obj: &Map<String, Value>) -> Vec<(&String, &Value)> {
+    let mut entries: Vec<(&String, &Value)> = obj.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+/// Renders the value as TOON, as [`to_string`](crate::to_string) does.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::toon;
+///
+/// let v = toon!({ "id": 1, "tags": ["a", "b"] });
+/// assert_eq!(v.to_string(), "id: 1\ntags[2]: a,b");
+/// ```
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let toon = crate::ser::to_string(self).map_err(|_| fmt::Error)?;
+        f.write_str(&toon)
+    }
+}
+
+/// Parses TOON, as [`from_str`](crate::from_str) does.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::Value;
+///
+/// let v: Value = "name: Ada\nage: 42".parse().unwrap();
+/// assert_eq!(v["name"], "Ada");
+/// assert_eq!(v["age"], 42);
+/// ```
+impl FromStr for Value {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Value, Error> {
+        crate::de::from_str(s)
+    }
+}
+
+/// A type that can index into a [`Value`]: `usize` for arrays, and `str` or `String`
+/// for objects.
+///
+/// Used by [`Value::get`], [`Value::get_mut`] and the `[]` operator. This trait is
+/// sealed and cannot be implemented outside this crate.
+pub trait Index: private::Sealed {
+    /// Returns the indexed value, or `None` if it is missing.
+    #[doc(hidden)]
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value>;
+
+    /// Mutable version of `index_into`.
+    #[doc(hidden)]
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value>;
+
+    /// Returns the indexed value, inserting `null` for a missing object key.
+    ///
+    /// Panics if the index does not apply to the value.
+    #[doc(hidden)]
+    fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value;
+}
+
+impl Index for usize {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        match v {
+            Value::Array(arr) => arr.get(*self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        match v {
+            Value::Array(arr) => arr.get_mut(*self),
+            _ => None,
+        }
+    }
+
+    fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
+        match v {
+            Value::Array(arr) => {
+                let len = arr.len();
+                arr.get_mut(*self).unwrap_or_else(|| {
+                    panic!("cannot access index {} of array of length {}", self, len)
+                })
+            }
+            _ => panic!("cannot access index {} of {}", self, v.kind_name()),
+        }
+    }
+}
+
+impl Index for str {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        match v {
+            Value::Object(obj) => obj.get(self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        match v {
+            Value::Object(obj) => obj.get_mut(self),
+            _ => None,
+        }
+    }
+
+    fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
+        if v.is_null() {
+            *v = Value::Object(Map::default());
+        }
+        match v {
+            Value::Object(obj) => obj.entry(self.to_string()).or_insert(Value::Null),
+            _ => panic!("cannot access key {:?} of {}", self, v.kind_name()),
+        }
+    }
+}
+
+impl Index for String {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        self.as_str().index_into(v)
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        self.as_str().index_into_mut(v)
+    }
+
+    fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
+        self.as_str().index_or_insert(v)
+    }
+}
+
+impl<T: Index + ?Sized> Index for &T {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        (**self).index_into(v)
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        (**self).index_into_mut(v)
+    }
+
+    fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
+        (**self).index_or_insert(v)
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for usize {}
+    impl Sealed for str {}
+    impl Sealed for alloc::string::String {}
+    impl<T: Sealed + ?Sized> Sealed for &T {}
+}
+
+/// Indexes into an array or object.
+///
+/// Returns [`Value::Null`] if the index is missing or does not apply to the value, so
+/// lookups can be chained without checks.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::toon;
+///
+/// let v = toon!({ "users": [{ "name": "Ada" }] });
+///
+/// assert_eq!(v["users"][0]["name"], "Ada");
+/// assert!(v["users"][5]["name"].is_null());
+/// ```
+impl<I: Index> ops::Index<I> for Value {
+    type Output = Value;
+
+    fn index(&self, index: I) -> &Value {
+        static NULL: Value = Value::Null;
+        index.index_into(self).unwrap_or(&NULL)
+    }
+}
+
+/// Mutably indexes into an array or object.
+///
+/// Indexing a missing object key inserts `null` under it, and indexing `null` with a
+/// key first turns it into an empty object, so nested objects can be built by
+/// assignment.
+///
+/// # Panics
+///
+/// Panics if an array index is out of bounds, or if the index does not apply to the
+/// value, such as a key into an array.
+///
+/// # Examples
+///
+/// ```
+/// use serde_toon2::{toon, Value};
+///
+/// let mut v = Value::Null;
+/// v["config"]["retries"] = toon!(3);
+/// v["config"]["hosts"] = toon!(["a"]);
+/// v["config"]["hosts"][0] = toon!("b");
+///
+/// assert_eq!(v, toon!({ "config": { "retries": 3, "hosts": ["b"] } }));
+/// ```
+impl<I: Index> ops::IndexMut<I> for Value {
+    fn index_mut(&mut self, index: I) -> &mut Value {
+        index.index_or_insert(self)
+    }
+}
+
+/// Compares number values with primitives through `as_i64`, `as_u64` or `as_f64`.
+macro_rules! impl_partial_eq {
+    ($method:ident as $target:ty => $($ty:ty),*) => {
+        $(
+            impl PartialEq<$ty> for Value {
+                fn eq(&self, other: &$ty) -> bool {
+                    self.$method().is_some_and(|v| v == *other as $target)
+                }
+            }
+
+            impl PartialEq<Value> for $ty {
+                fn eq(&self, other: &Value) -> bool {
+                    other == self
+                }
+            }
+        )*
+    };
+}
+
+impl_partial_eq!(as_i64 as i64 => i8, i16, i32, i64, isize);
+impl_partial_eq!(as_u64 as u64 => u8, u16, u32, u64, usize);
+impl_partial_eq!(as_f64 as f64 => f32, f64);
+
+impl PartialEq<bool> for Value {
+    fn eq(&self, other: &bool) -> bool {
+        self.as_bool() == Some(*other)
+    }
+}
+
+impl PartialEq<Value> for bool {
+    fn eq(&self, other: &Value) -> bool {
+        other == self
+    }
+}
+
+impl PartialEq<str> for Value {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == Some(other)
+    }
+}
+
+impl PartialEq<&str> for Value {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == Some(*other)
+    }
+}
+
+impl PartialEq<String> for Value {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == Some(other.as_str())
+    }
+}
+
+impl PartialEq<Value> for str {
+    fn eq(&self, other: &Value) -> bool {
+        other == self
+    }
+}
+
+impl PartialEq<Value> for &str {
+    fn eq(&self, other: &Value) -> bool {
+        other == self
+    }
+}
+
+impl PartialEq<Value> for String {
+    fn eq(&self, other: &Value) -> bool {
+        other == self
+    }
+}
@@ -0,0 +1,232 @@
+#![cfg(feature = "tokio")]
+
+use serde::{Deserialize, Serialize};
+use serde_toon2::async_io::{
+    AsyncTableReader, AsyncTableWriter, from_async_read_with_options, to_async_write_with_options,
+};
+use serde_toon2::error::ErrorKind;
+use serde_toon2::{
+    DecoderOptions, Delimiter, EncoderOptions, from_async_read, to_async_write, to_string,
+};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct User {
+    id: u32,
+    name: String,
+    active: bool,
+}
+
+fn users(count: u32) -> Vec<User> {
+    (1..=count)
+        .map(|id| User {
+            id,
+            name: format!("user-{}", id),
+            active: id % 2 == 0,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_round_trip_document() {
+    let data = users(3);
+    let mut out = Vec::new();
+    to_async_write(&mut out, &data).await.unwrap();
+    assert_eq!(
+        String::from_utf8(out.clone()).unwrap(),
+        to_string(&data).unwrap()
+    );
+
+    let back: Vec<User> = from_async_read(&out[..]).await.unwrap();
+    assert_eq!(back, data);
+}
+
+#[tokio::test]
+async fn test_write_with_options() {
+    let options = EncoderOptions {
+        delimiter: Delimiter::Pipe,
+        trailing_newline: true,
+        ..EncoderOptions::default()
+    };
+    let mut out = Vec::new();
+    to_async_write_with_options(&mut out, &users(1), options)
+        .await
+        .unwrap();
+    assert_eq!(out, b"[1|]{id|name|active}:\n  1|user-1|false\n");
+}
+
+#[tokio::test]
+async fn test_read_error_has_location() {
+    let err = from_async_read_with_options::<_, Vec<User>>(
+        "[2]{id,name,active}:\n  1,a,true".as_bytes(),
+        DecoderOptions::default(),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::CountMismatch));
+}
+
+#[tokio::test]
+async fn test_table_writer_matches_encoder() {
+    let data = users(4);
+    let mut writer = AsyncTableWriter::new(Vec::new(), Some("users"), 4, EncoderOptions::default());
+    for user in &data {
+        writer.write_row(user).await.unwrap();
+    }
+    let out = writer.finish().await.unwrap();
+
+    #[derive(Serialize)]
+    struct Doc<'a> {
+        users: &'a [User],
+    }
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        to_string(&Doc { users: &data }).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_table_round_trip() {
+    let data = users(50);
+    let options = EncoderOptions {
+        delimiter: Delimiter::Tab,
+        ..EncoderOptions::default()
+    };
+    let mut writer = AsyncTableWriter::new(Vec::new(), None, data.len(), options);
+    for user in &data {
+        writer.write_row(user).await.unwrap();
+    }
+    let out = writer.finish().await.unwrap();
+
+    let mut reader = AsyncTableReader::<_, User>::new(&out[..], DecoderOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(reader.key(), None);
+    assert_eq!(reader.declared_rows(), 50);
+    assert_eq!(reader.fields(), ["id", "name", "active"]);
+
+    let mut back = Vec::new();
+    while let Some(user) = reader.next_row().await.unwrap() {
+        back.push(user);
+    }
+    assert_eq!(back, data);
+}
+
+#[tokio::test]
+async fn test_empty_table() {
+    let writer = AsyncTableWriter::new(Vec::new(), Some("users"), 0, EncoderOptions::default());
+    let out = writer.finish().await.unwrap();
+    assert_eq!(out, b"users[0]:");
+
+    let mut reader = AsyncTableReader::<_, User>::new(&out[..], DecoderOptions::default())
+        .await
+        .unwrap();
+    assert!(reader.next_row().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_writer_rejects_wrong_count() {
+    let mut writer = AsyncTableWriter::new(Vec::new(), None, 1, EncoderOptions::default());
+    writer.write_row(&users(1)[0]).await.unwrap();
+    let err = writer.write_row(&users(1)[0]).await.unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::CountMismatch));
+
+    let writer = AsyncTableWriter::new(Vec::new(), None, 2, EncoderOptions::default());
+    let err = writer.finish().await.unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::CountMismatch));
+}
+
+#[tokio::test]
+async fn test_writer_rejects_mismatched_rows() {
+    #[derive(Serialize)]
+    struct Other {
+        id: u32,
+    }
+
+    let mut writer = AsyncTableWriter::new(Vec::new(), None, 2, EncoderOptions::default());
+    writer.write_row(&users(1)[0]).await.unwrap();
+    let err = writer.write_row(&Other { id: 2 }).await.unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::WidthMismatch));
+
+    let mut writer = AsyncTableWriter::new(Vec::new(), None, 1, EncoderOptions::default());
+    let err = writer.write_row(&vec![1, 2]).await.unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Custom(_)));
+}
+
+#[tokio::test]
+async fn test_reader_count_mismatch() {
+    let input = "rows[3]{id,name,active}:\n  1,a,true\n  2,b,false\n";
+    let mut reader = AsyncTableReader::<_, User>::new(input.as_bytes(), DecoderOptions::default())
+        .await
+        .unwrap();
+    reader.next_row().await.unwrap();
+    reader.next_row().await.unwrap();
+    let err = reader.next_row().await.unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::CountMismatch));
+    assert_eq!(err.line(), Some(1));
+
+    let options = DecoderOptions {
+        repair: true,
+        ..DecoderOptions::default()
+    };
+    let mut reader = AsyncTableReader::<_, User>::new(input.as_bytes(), options)
+        .await
+        .unwrap();
+    let mut count = 0;
+    while reader.next_row().await.unwrap().is_some() {
+        count += 1;
+    }
+    assert_eq!(count, 2);
+}
+
+#[tokio::test]
+async fn test_reader_rejects_extra_rows() {
+    let input = "[1]{id,name,active}:\n  1,a,true\n  2,b,false";
+    let mut reader = AsyncTableReader::<_, User>::new(input.as_bytes(), DecoderOptions::default())
+        .await
+        .unwrap();
+    reader.next_row().await.unwrap();
+    let err = reader.next_row().await.unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::CountMismatch));
+}
+
+#[tokio::test]
+async fn test_reader_errors() {
+    let err = AsyncTableReader::<_, User>::new("name: Ada".as_bytes(), DecoderOptions::default())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.kind(), ErrorKind::InvalidHeader));
+
+    let input = "[2]{id,name,active}:\n  1,a,true\n\n  2,b,false";
+    let strict = DecoderOptions {
+        strict: true,
+        ..DecoderOptions::default()
+    };
+    let mut reader = AsyncTableReader::<_, User>::new(input.as_bytes(), strict)
+        .await
+        .unwrap();
+    reader.next_row().await.unwrap();
+    let err = reader.next_row().await.unwrap_err();
+    assert_eq!(err.line(), Some(4));
+
+    let mut reader = AsyncTableReader::<_, User>::new(input.as_bytes(), DecoderOptions::default())
+        .await
+        .unwrap();
+    reader.next_row().await.unwrap();
+    assert!(reader.next_row().await.unwrap().is_some());
+
+    let input = "[1]{id,name,active}:\n  1,a\n";
+    let mut reader = AsyncTableReader::<_, User>::new(input.as_bytes(), DecoderOptions::default())
+        .await
+        .unwrap();
+    let err = reader.next_row().await.unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::WidthMismatch));
+    assert_eq!(err.line(), Some(2));
+
+    let input = "[1]{id,name,active}:\nnext: 1\n";
+    let mut reader = AsyncTableReader::<_, User>::new(input.as_bytes(), DecoderOptions::default())
+        .await
+        .unwrap();
+    let err = reader.next_row().await.unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::InvalidSyntax));
+}
@@ -0,0 +1,109 @@
+use serde_toon2::{
+    DecoderOptions, PathExpansion, Value, ValueRef, from_str, from_str_borrowed,
+    from_str_borrowed_with_options, to_string,
+};
+use std::borrow::Cow;
+
+fn is_borrowed(value: &ValueRef) -> bool {
+    matches!(value, ValueRef::String(Cow::Borrowed(_)))
+}
+
+#[test]
+fn test_strings_borrow_from_input() {
+    let input = "name: Ada\nquote: \"a, b\"\nescaped: \"line\\nbreak\"\ntags[2]: x,\"y z\"";
+    let doc = from_str_borrowed(input).unwrap();
+
+    assert!(is_borrowed(&doc["name"]));
+    assert!(is_borrowed(&doc["quote"]));
+    assert_eq!(doc["quote"], "a, b");
+    assert!(is_borrowed(&doc["tags"][0]));
+    assert!(is_borrowed(&doc["tags"][1]));
+
+    assert!(matches!(doc["escaped"], ValueRef::String(Cow::Owned(_))));
+    assert_eq!(doc["escaped"], "line\nbreak");
+
+    for key in doc.as_object().unwrap().keys() {
+        assert!(matches!(key, Cow::Borrowed(_)));
+    }
+}
+
+#[test]
+fn test_tabular_rows_borrow_fields_and_values() {
+    let input =
+        "logs[3]{level,message}:\n  info,started\n  warn,\"slow, retrying\"\n  error,failed";
+    let doc = from_str_borrowed(input).unwrap();
+
+    let logs = doc["logs"].as_array().unwrap();
+    assert_eq!(logs.len(), 3);
+    for log in logs {
+        for (key, value) in log.as_object().unwrap() {
+            assert!(matches!(key, Cow::Borrowed(_)));
+            assert!(is_borrowed(value));
+        }
+    }
+    assert_eq!(logs[1]["message"], "slow, retrying");
+}
+
+#[test]
+fn test_matches_owned_decoding() {
+    let input = "\
+id: 1
+user:
+  name: Ada
+  roles[2]: admin,ops
+  active: true
+  score: -1.5
+items[2]:
+  - sku: A1
+    qty: 2
+  - [2]: x,y
+empty:
+nothing: null";
+
+    let owned: Value = from_str(input).unwrap();
+    let borrowed = from_str_borrowed(input).unwrap();
+    assert_eq!(borrowed.clone().into_owned(), owned);
+    assert_eq!(Value::from(borrowed.clone()), owned);
+    assert_eq!(to_string(&borrowed).unwrap(), to_string(&owned).unwrap());
+
+    assert_eq!(borrowed["id"].as_u64(), Some(1));
+    assert_eq!(borrowed["user"]["active"].as_bool(), Some(true));
+    assert_eq!(borrowed["user"]["score"].as_f64(), Some(-1.5));
+    assert!(borrowed["nothing"].is_null());
+    assert_eq!(borrowed.pointer("/items/0/sku").unwrap(), "A1");
+    assert_eq!(borrowed.pointer("/items/1/1").unwrap(), "y");
+    assert!(borrowed["missing"]["deeper"].is_null());
+    assert!(borrowed.get("missing").is_none());
+}
+
+#[test]
+fn test_options_apply() {
+    let options = DecoderOptions {
+        expand_paths: PathExpansion::Safe,
+        ..DecoderOptions::default()
+    };
+    let doc = from_str_borrowed_with_options("a.b: 1\na.c: x\n\"d.e\": 2", options).unwrap();
+    assert_eq!(doc["a"]["b"].as_u64(), Some(1));
+    assert_eq!(doc["a"]["c"], "x");
+    assert_eq!(doc["d.e"].as_u64(), Some(2));
+
+    let strict = DecoderOptions {
+        strict: true,
+        ..DecoderOptions::default()
+    };
+    assert!(from_str_borrowed_with_options("a:\n   b: 1", strict).is_err());
+}
+
+#[test]
+fn test_errors_match_owned_decoding() {
+    for input in [
+        "tags[3]: a,b",
+        "a: \"open",
+        "a: \"bad \\q\"",
+        "rows[1]{a,b}:\n  1",
+    ] {
+        let owned = from_str::<Value>(input).unwrap_err();
+        let borrowed = from_str_borrowed(input).unwrap_err();
+        assert_eq!(borrowed.to_string(), owned.to_string());
+    }
+}
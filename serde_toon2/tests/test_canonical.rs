@@ -0,0 +1,66 @@
+use serde_toon2::{EncoderOptions, Value, from_str, to_string_with_options};
+
+#[test]
+fn test_canonical_sorts_nested_keys() {
+    let value: Value = from_str("z: 1\na:\n  y: true\n  b: null").unwrap();
+
+    let toon = to_string_with_options(&value, EncoderOptions::canonical()).unwrap();
+
+    assert_eq!(toon, "a:\n  b: null\n  y: true\nz: 1");
+}
+
+#[test]
+fn test_canonical_tabular_field_order() {
+    let a: Value = from_str("rows[2]:\n  - b: 1\n    a: 2\n  - a: 3\n    b: 4").unwrap();
+    let b: Value = from_str("rows[2]{a,b}:\n  2,1\n  3,4").unwrap();
+
+    let a = to_string_with_options(&a, EncoderOptions::canonical()).unwrap();
+    let b = to_string_with_options(&b, EncoderOptions::canonical()).unwrap();
+
+    assert_eq!(a, "rows[2]{a,b}:\n  2,1\n  3,4");
+    assert_eq!(a, b);
+}
+
+#[test]
+fn test_canonical_numbers() {
+    let value: Value = from_str("n[3]: 1.0,-0.0,2.50").unwrap();
+
+    let toon = to_string_with_options(&value, EncoderOptions::canonical()).unwrap();
+
+    assert_eq!(toon, "n[3]: 1,0,2.5");
+}
+
+#[test]
+fn test_content_hash_ignores_key_order_and_number_form() {
+    let a: Value = from_str("id: 1\ntags[2]: x,y\nscore: 0.5").unwrap();
+    let b: Value = from_str("score: 0.50\ntags[2]: x,y\nid: 1.0").unwrap();
+
+    assert_eq!(a.content_hash(), b.content_hash());
+}
+
+#[test]
+fn test_content_hash_distinguishes_types() {
+    assert_ne!(
+        Value::from("1").content_hash(),
+        Value::from(1i64).content_hash()
+    );
+    assert_ne!(
+        Value::Array(vec![]).content_hash(),
+        Value::Object(Default::default()).content_hash()
+    );
+    assert_ne!(
+        from_str::<Value>("a[2]: x,y").unwrap().content_hash(),
+        from_str::<Value>("a[2]: y,x").unwrap().content_hash()
+    );
+}
+
+#[test]
+fn test_content_hash_is_pinned() {
+    // The hash is part of the public contract and must never change
+    assert_eq!(
+        Value::Null.content_hash(),
+        0xd228cb69101a8caf78912b704e4a147f
+    );
+    let value: Value = from_str("id: 1\nname: Ada").unwrap();
+    assert_eq!(value.content_hash(), 0xc6a127e6047567fbe22f521381cdf031);
+}
@@ -0,0 +1,125 @@
+#![cfg(any(feature = "json", feature = "yaml", feature = "toml"))]
+
+use serde_toon2::error::ErrorKind;
+use serde_toon2::{Value, toon};
+
+fn sample() -> Value {
+    toon!({
+        "zeta": 1,
+        "alpha": [true, "x", -2, 1.5],
+        "nested": { "b": "y", "a": [] },
+    })
+}
+
+fn keys(value: &Value) -> Vec<&str> {
+    value
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect()
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json_round_trip() {
+    let json = serde_json::Value::try_from(sample()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "zeta": 1,
+            "alpha": [true, "x", -2, 1.5],
+            "nested": { "b": "y", "a": [] },
+        })
+    );
+
+    let value = Value::from(json);
+    assert_eq!(value, sample());
+    assert_eq!(keys(&value), ["zeta", "alpha", "nested"]);
+    assert_eq!(keys(&value["nested"]), ["b", "a"]);
+
+    let big = Value::from(serde_json::json!(u64::MAX));
+    assert_eq!(big.as_u64(), Some(u64::MAX));
+    assert!(Value::from(serde_json::Value::Null).is_null());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json_rejects_non_finite() {
+    let err = serde_json::Value::try_from(toon!({ "a/b": [(f64::INFINITY)] })).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Unrepresentable));
+    assert_eq!(
+        err.to_string(),
+        "inf cannot be represented in JSON at /a~1b/0"
+    );
+
+    let err = serde_json::Value::try_from(toon!(f64::NAN)).unwrap_err();
+    assert_eq!(err.to_string(), "NaN cannot be represented in JSON");
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn test_yaml_round_trip() {
+    let yaml = serde_yaml::Value::from(sample());
+    assert_eq!(
+        serde_yaml::to_string(&yaml).unwrap(),
+        "zeta: 1\nalpha:\n- true\n- x\n- -2\n- 1.5\nnested:\n  b: y\n  a: []\n"
+    );
+
+    let value = Value::try_from(yaml).unwrap();
+    assert_eq!(value, sample());
+    assert_eq!(keys(&value["nested"]), ["b", "a"]);
+
+    let yaml: serde_yaml::Value = serde_yaml::from_str("x: .nan\ny: ~").unwrap();
+    let value = Value::try_from(yaml).unwrap();
+    assert!(value["x"].as_f64().unwrap().is_nan());
+    assert!(value["y"].is_null());
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn test_yaml_rejects_tags_and_non_string_keys() {
+    let yaml: serde_yaml::Value = serde_yaml::from_str("items:\n- !Custom 1").unwrap();
+    let err = Value::try_from(yaml).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Unrepresentable));
+    assert_eq!(
+        err.to_string(),
+        "YAML tag !Custom cannot be represented in TOON at /items/0"
+    );
+
+    let yaml: serde_yaml::Value = serde_yaml::from_str("1: one").unwrap();
+    let err = Value::try_from(yaml).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Unrepresentable));
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_toml_round_trip() {
+    let table: toml::Value = sample().try_into().unwrap();
+    assert_eq!(
+        toml::to_string(&table).unwrap(),
+        "zeta = 1\nalpha = [true, \"x\", -2, 1.5]\n\n[nested]\nb = \"y\"\na = []\n"
+    );
+
+    let value = Value::try_from(table).unwrap();
+    assert_eq!(value, sample());
+    assert_eq!(keys(&value), ["zeta", "alpha", "nested"]);
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_toml_rejects_unrepresentable() {
+    let table: toml::Table = toml::from_str("[server]\nstarted = 2024-01-02T03:04:05Z").unwrap();
+    let err = Value::try_from(toml::Value::Table(table)).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Unrepresentable));
+    assert_eq!(
+        err.to_string(),
+        "TOML datetime 2024-01-02T03:04:05Z cannot be represented in TOON at /server/started"
+    );
+
+    let err = TryInto::<toml::Value>::try_into(toon!([1, null])).unwrap_err();
+    assert_eq!(err.to_string(), "null cannot be represented in TOML at /1");
+
+    let err = TryInto::<toml::Value>::try_into(toon!(u64::MAX)).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Unrepresentable));
+}
@@ -1,5 +1,5 @@
 use serde::Deserialize;
-use serde_toon2::{Value, from_str};
+use serde_toon2::{Value, from_str, toon};
 
 #[test]
 fn test_primitive_string() {
@@ -153,3 +153,81 @@ v3: "null""#;
     assert_eq!(obj.get("v2").unwrap().as_str().unwrap(), "42");
     assert_eq!(obj.get("v3").unwrap().as_str().unwrap(), "null");
 }
+
+#[test]
+fn test_non_ascii_keys() {
+    let input = "café: au lait\nnaïve[2]{é,b}:\n  1,2\n  3,4";
+    let result: Value = from_str(input).unwrap();
+
+    let obj = result.as_object().unwrap();
+    assert_eq!(obj.get("café").unwrap().as_str().unwrap(), "au lait");
+    let rows = obj.get("naïve").unwrap().as_array().unwrap();
+    assert_eq!(
+        rows[1].as_object().unwrap().get("é").unwrap().as_i64(),
+        Some(3)
+    );
+}
+
+#[test]
+fn test_deeply_nested_objects() {
+    let depth = 200;
+    let mut input = String::new();
+    for level in 0..depth {
+        input.push_str(&"  ".repeat(level));
+        input.push_str("child:\n");
+    }
+    input.push_str(&"  ".repeat(depth));
+    input.push_str("leaf: true\ntail: 1");
+
+    let result: Value = from_str(&input).unwrap();
+
+    let mut current = &result;
+    for _ in 0..depth {
+        current = current.as_object().unwrap().get("child").unwrap();
+    }
+    assert_eq!(
+        current.as_object().unwrap().get("leaf"),
+        Some(&Value::Bool(true))
+    );
+    assert_eq!(
+        result.as_object().unwrap().get("tail").unwrap().as_i64(),
+        Some(1)
+    );
+}
+
+#[test]
+fn test_structural_characters_inside_quotes() {
+    let input = "\"ключ: x\": \"a,б\"\nrows[2]{\"é,ü\",naïve}:\n  \"x: ж\",ünï\n  \"\\\"q\\\"\",ß\nlist[2|]: \"ö|ä\"|\"[1]: ü\"";
+    let result: Value = from_str(input).unwrap();
+
+    let obj = result.as_object().unwrap();
+    assert_eq!(obj.get("ключ: x").unwrap().as_str().unwrap(), "a,б");
+
+    let rows = obj.get("rows").unwrap().as_array().unwrap();
+    let first = rows[0].as_object().unwrap();
+    assert_eq!(first.get("é,ü").unwrap().as_str().unwrap(), "x: ж");
+    assert_eq!(first.get("naïve").unwrap().as_str().unwrap(), "ünï");
+    let second = rows[1].as_object().unwrap();
+    assert_eq!(second.get("é,ü").unwrap().as_str().unwrap(), "\"q\"");
+
+    let list = obj.get("list").unwrap().as_array().unwrap();
+    assert_eq!(list[0].as_str().unwrap(), "ö|ä");
+    assert_eq!(list[1].as_str().unwrap(), "[1]: ü");
+}
+
+#[test]
+fn test_colons_inside_quoted_keys_and_fields() {
+    let result: Value = from_str("[1]{\"a:b\"}:\n  1").unwrap();
+    assert_eq!(result, toon!([{ "a:b": 1 }]));
+
+    let result: Value = from_str("\"k:1\"[2]: x,\"y:z\"").unwrap();
+    assert_eq!(result, toon!({ "k:1": ["x", "y:z"] }));
+
+    let input =
+        "items[4]:\n  - \"a:b\"\n  - \"c:d\": 1\n  - [1]{\"e:f\"}:\n    2\n  - [1]: \"g:h\"";
+    let result: Value = from_str(input).unwrap();
+    assert_eq!(
+        result,
+        toon!({ "items": ["a:b", { "c:d": 1 }, [{ "e:f": 2 }], ["g:h"]] })
+    );
+}
@@ -0,0 +1,205 @@
+use serde_toon2::diff::{Change, ChangeKind, DiffOptions, diff, diff_with_options, to_json_patch};
+use serde_toon2::{Value, from_str, toon};
+
+fn keyed(keys: &[&str]) -> DiffOptions {
+    DiffOptions {
+        keys: keys.iter().map(|key| key.to_string()).collect(),
+    }
+}
+
+fn change(path: &str, kind: ChangeKind) -> Change {
+    Change {
+        path: path.to_string(),
+        kind,
+    }
+}
+
+/// Splits a pointer into its parent and unescaped last token.
+fn split(path: &str) -> (&str, String) {
+    let (parent, last) = path.rsplit_once('/').unwrap();
+    (parent, last.replace("~1", "/").replace("~0", "~"))
+}
+
+fn insert(doc: &mut Value, path: &str, value: Value) {
+    let (parent, last) = split(path);
+    match doc.pointer_mut(parent).unwrap() {
+        Value::Array(arr) => arr.insert(last.parse().unwrap(), value),
+        Value::Object(obj) => {
+            obj.insert(last, value);
+        }
+        other => panic!("cannot add to {other:?}"),
+    }
+}
+
+fn remove(doc: &mut Value, path: &str) -> Value {
+    let (parent, last) = split(path);
+    match doc.pointer_mut(parent).unwrap() {
+        Value::Array(arr) => arr.remove(last.parse().unwrap()),
+        Value::Object(obj) => obj.shift_remove(&last).unwrap(),
+        other => panic!("cannot remove from {other:?}"),
+    }
+}
+
+/// Applies a JSON Patch produced by `to_json_patch`, one operation after another.
+fn apply(mut doc: Value, patch: &Value) -> Value {
+    for op in patch.as_array().unwrap() {
+        let path = op["path"].as_str().unwrap();
+        match op["op"].as_str().unwrap() {
+            "add" => insert(&mut doc, path, op["value"].clone()),
+            "remove" => {
+                remove(&mut doc, path);
+            }
+            "replace" => *doc.pointer_mut(path).unwrap() = op["value"].clone(),
+            "move" => {
+                let value = remove(&mut doc, op["from"].as_str().unwrap());
+                insert(&mut doc, path, value);
+            }
+            other => panic!("unexpected op {other}"),
+        }
+    }
+    doc
+}
+
+fn assert_patch_applies(old: &Value, new: &Value, options: &DiffOptions) -> Vec<Change> {
+    let changes = diff_with_options(old, new, options);
+    let patched = apply(old.clone(), &to_json_patch(&changes));
+    assert_eq!(&patched, new, "{changes:#?}");
+    changes
+}
+
+#[test]
+fn test_equal_values_have_no_changes() {
+    let doc: Value = from_str("a: 1\nb[2]: x,y\nc:\n  d: true").unwrap();
+    assert!(diff(&doc, &doc.clone()).is_empty());
+
+    let reordered: Value = from_str("c:\n  d: true\nb[2]: x,y\na: 1.0").unwrap();
+    assert!(diff(&doc, &reordered).is_empty());
+}
+
+#[test]
+fn test_objects_and_scalars() {
+    let old = toon!({ "name": "Ada", "age": 36, "a/b": { "x": 1 }, "gone": null });
+    let new = toon!({ "name": "Ada L.", "age": 36, "a/b": { "x": [1] }, "new": 1 });
+
+    let changes = assert_patch_applies(&old, &new, &DiffOptions::default());
+    assert_eq!(
+        changes,
+        [
+            change(
+                "/name",
+                ChangeKind::Changed {
+                    from: toon!("Ada"),
+                    to: toon!("Ada L.")
+                }
+            ),
+            change(
+                "/a~1b/x",
+                ChangeKind::Changed {
+                    from: toon!(1),
+                    to: toon!([1])
+                }
+            ),
+            change("/gone", ChangeKind::Removed(Value::Null)),
+            change("/new", ChangeKind::Added(toon!(1))),
+        ]
+    );
+
+    let root = diff(&toon!(1), &toon!("1"));
+    assert_eq!(root[0].path, "");
+    assert_patch_applies(&toon!(1), &toon!("1"), &DiffOptions::default());
+}
+
+#[test]
+fn test_arrays_match_by_index() {
+    let old = toon!({ "tags": ["a", "b", "c", "d"] });
+    let new = toon!({ "tags": ["a", "x"] });
+    let changes = assert_patch_applies(&old, &new, &DiffOptions::default());
+    let paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+    assert_eq!(paths, ["/tags/1", "/tags/3", "/tags/2"]);
+
+    let changes = assert_patch_applies(&new, &old, &DiffOptions::default());
+    let paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+    assert_eq!(paths, ["/tags/1", "/tags/2", "/tags/3"]);
+}
+
+#[test]
+fn test_tabular_rows_match_by_key() {
+    let old: Value =
+        from_str("items[4]{sku,price,qty}:\n  A1,2.5,1\n  B2,12,3\n  C3,40,1\n  D4,10,2").unwrap();
+    let new: Value =
+        from_str("items[4]{sku,price,qty}:\n  B2,12,3\n  E5,7,1\n  C3,45,1\n  D4,10,2").unwrap();
+
+    // By index, the rows that shifted show up as changed fields
+    let by_index = assert_patch_applies(&old, &new, &DiffOptions::default());
+    assert_eq!(by_index.len(), 7);
+
+    let by_key = assert_patch_applies(&old, &new, &keyed(&["id", "sku"]));
+    assert_eq!(
+        by_key,
+        [
+            change(
+                "/items/0",
+                ChangeKind::Removed(toon!({ "sku": "A1", "price": 2.5, "qty": 1 }))
+            ),
+            change(
+                "/items/1",
+                ChangeKind::Added(toon!({ "sku": "E5", "price": 7, "qty": 1 }))
+            ),
+            change(
+                "/items/2/price",
+                ChangeKind::Changed {
+                    from: toon!(40),
+                    to: toon!(45)
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_reordered_rows_move() {
+    let old = toon!([{ "id": 1 }, { "id": 2, "v": "x" }, { "id": 3 }, { "id": 4 }]);
+    let new = toon!([{ "id": 3 }, { "id": 1 }, { "id": 5 }, { "id": 4 }, { "id": 2, "v": "y" }]);
+    let changes = assert_patch_applies(&old, &new, &keyed(&["id"]));
+    assert_eq!(
+        changes[0],
+        change(
+            "/0",
+            ChangeKind::Moved {
+                from: "/2".to_string()
+            }
+        )
+    );
+    assert!(changes.contains(&change(
+        "/4/v",
+        ChangeKind::Changed {
+            from: toon!("x"),
+            to: toon!("y")
+        }
+    )));
+
+    let reversed: Vec<Value> = new.as_array().unwrap().iter().rev().cloned().collect();
+    assert_patch_applies(&old, &Value::Array(reversed), &keyed(&["id"]));
+}
+
+#[test]
+fn test_unkeyable_arrays_fall_back_to_index() {
+    let options = keyed(&["id"]);
+
+    // A duplicate key, a missing key and a non-object element each disable keying
+    for old in [
+        toon!([{ "id": 1 }, { "id": 1 }]),
+        toon!([{ "id": 1 }, { "name": "x" }]),
+        toon!([{ "id": 1 }, 2]),
+    ] {
+        let new = toon!([{ "id": 2 }]);
+        let changes = assert_patch_applies(&old, &new, &options);
+        assert_eq!(changes[0].path, "/0/id");
+    }
+
+    let nested = toon!({ "groups": [{ "id": "g", "members": [{ "id": 1 }, { "id": 2 }] }] });
+    let edited = toon!({ "groups": [{ "id": "g", "members": [{ "id": 2 }] }] });
+    let changes = assert_patch_applies(&nested, &edited, &options);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].path, "/groups/0/members/0");
+}
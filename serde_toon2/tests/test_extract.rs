@@ -0,0 +1,120 @@
+use serde::Deserialize;
+use serde_toon2::extract::{BlockKind, find_blocks, from_str_all, from_str_first};
+use serde_toon2::{DecoderOptions, Value};
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct User {
+    id: u32,
+    name: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Users {
+    users: Vec<User>,
+}
+
+#[test]
+fn test_labelled_fence() {
+    let reply = "Here you go:\n\n```toon\nusers[1]{id,name}:\n  1,Ada\n```\n\nLet me know!";
+    let found = from_str_first::<Users>(reply, DecoderOptions::default()).unwrap();
+    assert_eq!(
+        found.value.users,
+        vec![User {
+            id: 1,
+            name: "Ada".to_string(),
+        }]
+    );
+    assert_eq!(&reply[found.range], "users[1]{id,name}:\n  1,Ada\n");
+}
+
+#[test]
+fn test_unfenced_region() {
+    let reply = "The record is below.\nid: 7\nname: Bob\nThanks for asking.";
+    let blocks = find_blocks(reply);
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].kind, BlockKind::Unfenced);
+    assert_eq!(blocks[0].text, "id: 7\nname: Bob\n");
+
+    let found = from_str_first::<User>(reply, DecoderOptions::default()).unwrap();
+    assert_eq!(found.value.id, 7);
+    assert_eq!(found.range, blocks[0].range);
+}
+
+#[test]
+fn test_unfenced_region_with_nesting_and_blank_lines() {
+    let text = "user:\n  id: 1\n  tags[2]: a,b\n\nactive: true\n\nThat is all.";
+    let blocks = find_blocks(text);
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(
+        blocks[0].text,
+        "user:\n  id: 1\n  tags[2]: a,b\n\nactive: true\n"
+    );
+}
+
+#[test]
+fn test_prose_is_not_toon() {
+    let text = "Summary:\nThe data has 3 rows.\nSee https://example.com for details.";
+    assert!(find_blocks(text).is_empty());
+    assert!(from_str_first::<Value>(text, DecoderOptions::default()).is_err());
+}
+
+#[test]
+fn test_other_languages_are_skipped() {
+    let text = "```json\n{\"id\": 1}\n```\n\n```\nid: 2\n```";
+    let blocks = find_blocks(text);
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].kind, BlockKind::Fenced);
+
+    let found = from_str_first::<User>(
+        &text.replace("id: 2", "id: 2\nname: Cy"),
+        DecoderOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(found.value.name, "Cy");
+}
+
+#[test]
+fn test_all_blocks() {
+    let text = "```toon\nid: 1\nname: A\n```\ntext\n```toon\nid: 2\nname: B\n```\nid: 3\nname: C\n";
+    let found = from_str_all::<User>(text, DecoderOptions::default()).unwrap();
+    let ids: Vec<u32> = found.iter().map(|e| e.value.id).collect();
+    assert_eq!(ids, vec![1, 2, 3]);
+    for extracted in &found {
+        assert!(text[extracted.range.clone()].starts_with("id: "));
+    }
+}
+
+#[test]
+fn test_guessed_blocks_that_fail_are_skipped() {
+    let text = "```\nnot: [toon\n  - broken\n```\n\n```toon\nid: 4\nname: D\n```";
+    let found = from_str_first::<User>(text, DecoderOptions::default()).unwrap();
+    assert_eq!(found.value.id, 4);
+}
+
+#[test]
+fn test_labelled_block_errors_use_original_lines() {
+    let text = "Intro\n\n```toon\nitems[3]: a,b\n```";
+    let err = from_str_first::<Value>(text, DecoderOptions::default()).unwrap_err();
+    assert!(err.to_string().contains("line 4"), "{}", err);
+}
+
+#[test]
+fn test_unclosed_fence() {
+    let text = "```toon\nid: 9\nname: Z";
+    let found = from_str_first::<User>(text, DecoderOptions::default()).unwrap();
+    assert_eq!(found.value.id, 9);
+    assert_eq!(found.range.end, text.len());
+}
+
+#[test]
+fn test_extract_with_repair() {
+    let text = "```toon\nusers[3]{id,name}:\n  1,Ada,\n```";
+    assert!(from_str_first::<Users>(text, DecoderOptions::default()).is_err());
+
+    let opts = DecoderOptions {
+        repair: true,
+        ..Default::default()
+    };
+    let found = from_str_first::<Users>(text, opts).unwrap();
+    assert_eq!(found.value.users.len(), 1);
+}
@@ -2,7 +2,7 @@ use std::{fs::File, io::Read, path::Path};
 
 use datatest_stable::Result;
 use serde::Deserialize;
-use serde_toon2::{DecoderOptions, Delimiter, EncoderOptions, KeyFolding, PathExpansion};
+use serde_toon2::{DecoderOptions, Delimiter, EncoderOptions, Indent, KeyFolding, PathExpansion};
 
 #[derive(Deserialize)]
 struct DecodeTestOptions {
@@ -29,7 +29,7 @@ impl DecodeTestOptions {
         }
 
         if let Some(indent) = self.indent {
-            opts.indent = indent;
+            opts.indent = Indent::Spaces(indent);
         }
 
         opts
@@ -72,7 +72,7 @@ impl EncodeTestOptions {
         }
 
         if let Some(indent) = self.indent {
-            opts.indent = indent;
+            opts.indent = Indent::Spaces(indent);
         }
 
         opts
@@ -157,12 +157,13 @@ fn test_decode_fixture(path: &Path) -> Result<()> {
     let fixture: Fixture<DecodeTest> = serde_json::from_str(&json_string)?;
 
     for test in fixture.tests {
-        let result = if let Some(options) = test.options {
-            let opts = options.to_decoder_options();
-            serde_toon2::from_str_with_options(&test.input, opts)
-        } else {
-            serde_toon2::from_str(&test.input)
-        };
+        let opts = test
+            .options
+            .as_ref()
+            .map(DecodeTestOptions::to_decoder_options)
+            .unwrap_or_default();
+        let result = serde_toon2::from_str_with_options(&test.input, opts.clone());
+        let borrowed = serde_toon2::from_str_borrowed_with_options(&test.input, opts);
 
         if test.should_error {
             assert!(
@@ -171,6 +172,12 @@ fn test_decode_fixture(path: &Path) -> Result<()> {
                 test.name,
                 test.spec_section
             );
+            assert!(
+                borrowed.is_err(),
+                "expected borrowed error but got success: fixture {}, spec: {}",
+                test.name,
+                test.spec_section
+            );
         } else {
             let output: serde_json::Value = result.unwrap_or_else(|_| {
                 panic!(
@@ -183,6 +190,20 @@ fn test_decode_fixture(path: &Path) -> Result<()> {
                 "result does not match expected: {}, spec: {}",
                 test.name, test.spec_section
             );
+
+            let borrowed = borrowed.unwrap_or_else(|_| {
+                panic!(
+                    "borrowed decode failed: fixture {}, spec: {}",
+                    test.name, test.spec_section
+                )
+            });
+            assert_eq!(
+                serde_toon2::from_value::<serde_json::Value>(borrowed.into_owned())?,
+                test.expected,
+                "borrowed result does not match expected: {}, spec: {}",
+                test.name,
+                test.spec_section
+            );
         }
     }
 
@@ -0,0 +1,118 @@
+use serde_toon2::ser::{DefaultFormatter, Formatter, Serializer};
+use serde_toon2::{Delimiter, EncoderOptions, Indent, Number, Value, from_str, to_string};
+use std::io::{self, Write};
+
+fn encode_with<F: Formatter>(value: &Value, formatter: F) -> String {
+    let mut buf = Vec::new();
+    let mut ser = Serializer::with_formatter(&mut buf, EncoderOptions::default(), formatter);
+    ser.serialize_value(value).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+struct Ansi;
+
+impl Formatter for Ansi {
+    fn write_key<W: ?Sized + Write>(&mut self, writer: &mut W, key: &str) -> io::Result<()> {
+        writer.write_all(b"\x1b[34m")?;
+        DefaultFormatter.write_key(writer, key)?;
+        writer.write_all(b"\x1b[0m")
+    }
+
+    fn write_number<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        value: &Number,
+    ) -> io::Result<()> {
+        writer.write_all(b"\x1b[33m")?;
+        DefaultFormatter.write_number(writer, value)?;
+        writer.write_all(b"\x1b[0m")
+    }
+}
+
+struct AlwaysQuote;
+
+impl Formatter for AlwaysQuote {
+    fn write_string<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        value: &str,
+        _delimiter: Delimiter,
+    ) -> io::Result<()> {
+        self.write_quoted_string(writer, value)
+    }
+
+    fn write_key<W: ?Sized + Write>(&mut self, writer: &mut W, key: &str) -> io::Result<()> {
+        self.write_quoted_string(writer, key)
+    }
+}
+
+struct Tabs;
+
+impl Formatter for Tabs {
+    fn write_indent<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        depth: usize,
+        _indent: Indent,
+    ) -> io::Result<()> {
+        (0..depth).try_for_each(|_| writer.write_all(b"\t"))
+    }
+}
+
+const DOC: &str = "user:\n  id: 7\n  tags[2]: a,b\nrows[2]{id,name}:\n  1,Ada\n  2,Bob\nitems[2]:\n  - x: 1\n    y: 2\n  - plain";
+
+#[test]
+fn test_default_formatter_matches_to_string() {
+    let value: Value = from_str(DOC).unwrap();
+
+    assert_eq!(
+        encode_with(&value, DefaultFormatter),
+        to_string(&value).unwrap()
+    );
+    assert_eq!(encode_with(&value, DefaultFormatter), DOC);
+}
+
+#[test]
+fn test_ansi_formatter() {
+    let value: Value = from_str("user:\n  id: 7").unwrap();
+
+    let out = encode_with(&value, Ansi);
+
+    assert_eq!(
+        out,
+        "\x1b[34muser\x1b[0m:\n  \x1b[34mid\x1b[0m: \x1b[33m7\x1b[0m"
+    );
+}
+
+#[test]
+fn test_always_quote_formatter_round_trips() {
+    let value: Value = from_str(DOC).unwrap();
+
+    let out = encode_with(&value, AlwaysQuote);
+
+    assert!(out.starts_with("\"user\":\n  \"id\": 7\n  \"tags\"[2]: \"a\",\"b\""));
+    assert!(out.contains("\"rows\"[2]{\"id\",\"name\"}:\n  1,\"Ada\""));
+    assert_eq!(from_str::<Value>(&out).unwrap(), value);
+}
+
+#[test]
+fn test_tab_formatter() {
+    let value: Value = from_str(DOC).unwrap();
+
+    let out = encode_with(&value, Tabs);
+
+    assert_eq!(
+        out,
+        "user:\n\tid: 7\n\ttags[2]: a,b\nrows[2]{id,name}:\n\t1,Ada\n\t2,Bob\nitems[2]:\n\t- x: 1\n\t\ty: 2\n\t- plain"
+    );
+}
+
+#[test]
+fn test_array_keys_are_quoted() {
+    let value: Value = from_str("\"my list\"[2]: 1,2").unwrap();
+
+    let out = to_string(&value).unwrap();
+
+    assert_eq!(out, "\"my list\"[2]: 1,2");
+    assert_eq!(from_str::<Value>(&out).unwrap(), value);
+}
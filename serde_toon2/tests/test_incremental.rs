@@ -0,0 +1,253 @@
+use serde::Deserialize;
+use serde_toon2::incremental::{Parser, PendingArray};
+use serde_toon2::{DecoderOptions, PathExpansion, Value, toon};
+
+fn field<'a>(value: &'a Value, key: &str) -> &'a Value {
+    &value.as_object().unwrap()[key]
+}
+
+#[test]
+fn test_feed_complete_lines() {
+    let mut parser = Parser::new();
+
+    let partial = parser.feed("name: Ada\n").unwrap();
+    assert_eq!(field(&partial.value, "name"), &Value::from("Ada"));
+    assert_eq!(partial.incomplete, None);
+    assert!(partial.pending.is_empty());
+
+    let partial = parser.feed("age: 42\n").unwrap();
+    assert_eq!(field(&partial.value, "age").as_i64(), Some(42));
+}
+
+#[test]
+fn test_incomplete_scalar() {
+    let mut parser = Parser::new();
+
+    let partial = parser.feed("user:\n  name: Ad").unwrap();
+    assert_eq!(
+        field(field(&partial.value, "user"), "name"),
+        &Value::from("Ad")
+    );
+    assert_eq!(partial.incomplete.as_deref(), Some("/user/name"));
+
+    let partial = parser.feed("a\n").unwrap();
+    assert_eq!(
+        field(field(&partial.value, "user"), "name"),
+        &Value::from("Ada")
+    );
+    assert_eq!(partial.incomplete, None);
+}
+
+#[test]
+fn test_unterminated_quoted_string() {
+    let mut parser = Parser::new();
+
+    let partial = parser.feed("note: \"a, b").unwrap();
+    assert_eq!(field(&partial.value, "note"), &Value::from("a, b"));
+    assert_eq!(partial.incomplete.as_deref(), Some("/note"));
+
+    // A trailing backslash is the start of an escape, not part of the value
+    let partial = parser.feed(" \\").unwrap();
+    assert_eq!(field(&partial.value, "note"), &Value::from("a, b "));
+
+    let partial = parser.feed("\"c\"\n").unwrap();
+    assert_eq!(field(&partial.value, "note"), &Value::from("a, b \"c"));
+    assert_eq!(partial.incomplete, None);
+}
+
+#[test]
+fn test_pending_arrays() {
+    let mut parser = Parser::new();
+
+    let partial = parser.feed("tags[3]:\n  - rust\n").unwrap();
+    assert_eq!(field(&partial.value, "tags").as_array().unwrap().len(), 1);
+    assert_eq!(
+        partial.pending,
+        vec![PendingArray {
+            line: 1,
+            key: Some("tags".to_string()),
+            declared: 3,
+            received: 1,
+        }]
+    );
+
+    let partial = parser.feed("  - serde\n  - toon\n").unwrap();
+    assert!(partial.pending.is_empty());
+}
+
+#[test]
+fn test_pending_tabular_rows() {
+    let mut parser = Parser::new();
+
+    let partial = parser
+        .feed("rows[4]{id,name}:\n  1,a\n  2,b\n  3,")
+        .unwrap();
+    let rows = field(&partial.value, "rows").as_array().unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(field(&rows[2], "name"), &Value::from(""));
+    assert_eq!(partial.incomplete.as_deref(), Some("/rows/2/name"));
+    assert_eq!(partial.pending[0].received, 3);
+    assert_eq!(partial.pending[0].declared, 4);
+}
+
+#[test]
+fn test_unparseable_tail_is_ignored() {
+    let mut parser = Parser::new();
+
+    // A half-written header cannot be read yet
+    let partial = parser.feed("id: 1\nitems[").unwrap();
+    assert_eq!(field(&partial.value, "id").as_i64(), Some(1));
+    assert!(partial.value.as_object().unwrap().get("items").is_none());
+    assert_eq!(partial.incomplete, None);
+}
+
+#[test]
+fn test_errors_in_complete_lines() {
+    let mut parser = Parser::new();
+    let result = parser.feed("tags[1]:\n  - a\n  - b\n");
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_finish_validates() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Doc {
+        tags: Vec<String>,
+    }
+
+    let mut parser = Parser::new();
+    parser.feed("tags[2]: a,").unwrap();
+    parser.feed("b").unwrap();
+    let doc: Doc = parser.finish().unwrap();
+    assert_eq!(doc.tags, vec!["a", "b"]);
+
+    let mut parser = Parser::new();
+    parser.feed("tags[3]: a,b").unwrap();
+    assert!(parser.finish::<Doc>().is_err());
+}
+
+#[test]
+fn test_multi_line_list_items() {
+    let input =
+        "items[2]:\n  - x: 1\n    y: 2\n  - x: 3\n    meta:\n      k: v\n    tags[2]: a,b\n";
+    let expected = toon!({
+        "items": [{ "x": 1, "y": 2 }, { "x": 3, "meta": { "k": "v" }, "tags": ["a", "b"] }]
+    });
+
+    for size in 1..8 {
+        let mut parser = Parser::new();
+        let chars: Vec<char> = input.chars().collect();
+        for chunk in chars.chunks(size) {
+            parser.feed(&chunk.iter().collect::<String>()).unwrap();
+        }
+        let value: Value = parser.finish().unwrap();
+        assert_eq!(value, expected);
+    }
+
+    let mut parser = Parser::new();
+    let partial = parser
+        .feed("items[2]:\n  - x: 1\n    y: 2\n  - x: 3")
+        .unwrap();
+    assert_eq!(
+        partial.value,
+        toon!({ "items": [{ "x": 1, "y": 2 }, { "x": 3 }] })
+    );
+    assert_eq!(partial.incomplete.as_deref(), Some("/items/1/x"));
+
+    let mut parser = Parser::new();
+    let result = parser.feed("items[2]:\n  - x: 1\n    y: 2\n\n  - x: 3\n");
+    assert!(result.unwrap_err().to_string().contains("Blank lines"));
+}
+
+#[test]
+fn test_non_strict_options() {
+    let mut parser = Parser::with_options(DecoderOptions::default());
+    let partial = parser.feed("tags[2]:\n  - a\n").unwrap();
+    assert_eq!(field(&partial.value, "tags").as_array().unwrap().len(), 1);
+    assert_eq!(partial.pending.len(), 1);
+}
+
+#[test]
+fn test_chunk_boundaries_match_whole_input() {
+    let input = "users[2]{id,name}:\n  1,\"Ada, L\"\n  2,Bob\nmeta:\n  count: 2\n";
+    let expected: Value = serde_toon2::from_str(input).unwrap();
+
+    for size in 1..8 {
+        let mut parser = Parser::new();
+        let chars: Vec<char> = input.chars().collect();
+        for chunk in chars.chunks(size) {
+            parser.feed(&chunk.iter().collect::<String>()).unwrap();
+        }
+        let value: Value = parser.finish().unwrap();
+        assert_eq!(value, expected);
+    }
+}
+
+#[test]
+fn test_incomplete_path_follows_the_last_line() {
+    let mut parser = Parser::with_options(DecoderOptions {
+        strict: true,
+        expand_paths: PathExpansion::Safe,
+        ..Default::default()
+    });
+    let partial = parser.feed("a.b: 1\nc: 2\na.d: 12").unwrap();
+    assert_eq!(partial.value, toon!({ "a": { "b": 1, "d": 12 }, "c": 2 }));
+    assert_eq!(partial.incomplete.as_deref(), Some("/a/d"));
+
+    // A repeated key keeps its first place in the object
+    let mut parser = Parser::new();
+    let partial = parser.feed("a: 1\nb: 2\na: 3").unwrap();
+    assert_eq!(partial.value, toon!({ "a": 3, "b": 2 }));
+    assert_eq!(partial.incomplete.as_deref(), Some("/a"));
+}
+
+#[test]
+fn test_pending_lines_across_fields() {
+    let mut parser = Parser::new();
+    for line in [
+        "x: 1\n",
+        "tags[3]:\n",
+        "  - a\n",
+        "y: 2\n",
+        "z[2]:\n",
+        "  - b\n",
+    ] {
+        parser.feed(line).unwrap();
+    }
+    let partial = parser.feed("").unwrap();
+    let lines: Vec<(usize, usize)> = partial
+        .pending
+        .iter()
+        .map(|array| (array.line, array.received))
+        .collect();
+    assert_eq!(lines, [(2, 1), (5, 1)]);
+}
+
+#[test]
+fn test_chunked_feeds_match_a_single_feed() {
+    let inputs = [
+        "users[2]{id,name}:\n  1,Ada\n  2,Bob\nmeta:\n  count: 2\n  tags[3]: a,b\nz: 1\n",
+        "a.b: 1\nc: 2\na.d: 12\n\"x.y\": 3\na.b: 4\n",
+        "[3]:\n  - a\n  - b\nc: 1\n",
+        "a: 1\nb: 2\n  c: 3\nd: 4\n",
+        "a:\n    b: 1\nc:\n    d: 2\ne: 3\n",
+    ];
+    for options in [
+        DecoderOptions::default(),
+        DecoderOptions {
+            strict: true,
+            expand_paths: PathExpansion::Safe,
+            indent: serde_toon2::Indent::Auto,
+            ..Default::default()
+        },
+    ] {
+        for input in inputs {
+            let mut chunked = Parser::with_options(options.clone());
+            for end in 1..=input.len() {
+                let partial = chunked.feed(&input[end - 1..end]);
+                let single = Parser::with_options(options.clone()).feed(&input[..end]);
+                assert_eq!(partial.ok(), single.ok(), "{input:?} up to {end}");
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use serde_toon2::error::ErrorKind;
+use serde_toon2::{
+    DecoderOptions, EncoderOptions, Indent, Value, from_str, from_str_with_options,
+    to_string_with_options,
+};
+
+const NESTED: &str =
+    "user:\n  name: Ada\n  address:\n    city: London\n  tags[2]: a,b\nactive: true";
+
+fn decode(toon: &str, indent: Indent, strict: bool) -> serde_toon2::Result<Value> {
+    from_str_with_options(
+        toon,
+        DecoderOptions {
+            indent,
+            strict,
+            ..Default::default()
+        },
+    )
+}
+
+#[test]
+fn test_encode_tabs() {
+    let value: Value = from_str(NESTED).unwrap();
+    let opts = EncoderOptions {
+        indent: Indent::Tabs,
+        ..Default::default()
+    };
+    let toon = to_string_with_options(&value, opts).unwrap();
+    assert_eq!(toon, NESTED.replace("    ", "\t\t").replace("  ", "\t"));
+}
+
+#[test]
+fn test_encode_auto_uses_two_spaces() {
+    let value: Value = from_str(NESTED).unwrap();
+    let opts = EncoderOptions {
+        indent: Indent::Auto,
+        ..Default::default()
+    };
+    assert_eq!(to_string_with_options(&value, opts).unwrap(), NESTED);
+}
+
+#[test]
+fn test_decode_tabs() {
+    let expected = decode(NESTED, Indent::Spaces(2), true).unwrap();
+    let tabbed = NESTED.replace("    ", "\t\t").replace("  ", "\t");
+
+    assert_eq!(decode(&tabbed, Indent::Tabs, true).unwrap(), expected);
+    assert_eq!(decode(&tabbed, Indent::Tabs, false).unwrap(), expected);
+}
+
+#[test]
+fn test_decode_tabs_rejects_spaces_in_strict_mode() {
+    let err = decode("user:\n  name: Ada", Indent::Tabs, true).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::IndentationError));
+    assert!(err.to_string().contains("line 2"));
+}
+
+#[test]
+fn test_decode_spaces_rejects_tabs_in_strict_mode() {
+    let err = decode("user:\n\tname: Ada", Indent::Spaces(2), true).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::IndentationError));
+}
+
+#[test]
+fn test_auto_detects_spaces() {
+    let expected = decode(NESTED, Indent::Spaces(2), true).unwrap();
+    let four = NESTED.replace("  ", "    ");
+
+    assert!(decode(&four, Indent::Spaces(2), true).is_ok());
+    assert_eq!(decode(&four, Indent::Auto, true).unwrap(), expected);
+    assert_eq!(decode(NESTED, Indent::Auto, true).unwrap(), expected);
+
+    // With the default width, 4-space files nest one level too deep
+    assert_ne!(decode(&four, Indent::Spaces(2), false).unwrap(), expected);
+}
+
+#[test]
+fn test_auto_detects_tabs() {
+    let expected = decode(NESTED, Indent::Spaces(2), true).unwrap();
+    let tabbed = NESTED.replace("    ", "\t\t").replace("  ", "\t");
+    assert_eq!(decode(&tabbed, Indent::Auto, true).unwrap(), expected);
+}
+
+#[test]
+fn test_auto_strict_requires_consistent_unit() {
+    let err = decode("a:\n    b: 1\nc:\n  d: 2", Indent::Auto, true).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::IndentationError));
+    assert!(err.to_string().contains("multiple of 4"));
+}
+
+#[test]
+fn test_auto_without_indentation() {
+    let value = decode("a: 1\nb: 2", Indent::Auto, true).unwrap();
+    assert_eq!(value.as_object().unwrap().len(), 2);
+}
+
+#[test]
+fn test_tabs_round_trip_tabular() {
+    let value: Value = from_str("rows[2]{id,name}:\n  1,Ada\n  2,Bob").unwrap();
+    let opts = EncoderOptions {
+        indent: Indent::Tabs,
+        ..Default::default()
+    };
+    let toon = to_string_with_options(&value, opts).unwrap();
+    assert_eq!(toon, "rows[2]{id,name}:\n\t1,Ada\n\t2,Bob");
+    assert_eq!(decode(&toon, Indent::Auto, true).unwrap(), value);
+}
@@ -0,0 +1,90 @@
+use serde::Deserialize;
+use serde_toon2::ser::Serializer;
+use serde_toon2::{
+    EncoderOptions, LineEnding, Value, from_slice, from_str, to_string_with_options,
+};
+
+const DOC: &str = "user:\n  name: Ada\n  tags[2]: a,b\nrows[2]{id,name}:\n  1,x\n  2,y";
+
+fn encode(value: &Value, line_ending: LineEnding, trailing_newline: bool) -> String {
+    let opts = EncoderOptions {
+        line_ending,
+        trailing_newline,
+        ..Default::default()
+    };
+    to_string_with_options(value, opts).unwrap()
+}
+
+#[test]
+fn test_encode_crlf() {
+    let value: Value = from_str(DOC).unwrap();
+    assert_eq!(
+        encode(&value, LineEnding::CrLf, false),
+        DOC.replace('\n', "\r\n")
+    );
+}
+
+#[test]
+fn test_trailing_newline() {
+    let value: Value = from_str(DOC).unwrap();
+    assert_eq!(encode(&value, LineEnding::Lf, true), format!("{}\n", DOC));
+    assert_eq!(
+        encode(&value, LineEnding::CrLf, true),
+        format!("{}\r\n", DOC.replace('\n', "\r\n"))
+    );
+}
+
+#[test]
+fn test_serializer_end() {
+    let value: Value = from_str("a: 1").unwrap();
+    let opts = EncoderOptions {
+        trailing_newline: true,
+        ..Default::default()
+    };
+
+    let mut buf = Vec::new();
+    let mut ser = Serializer::new(&mut buf, opts);
+    ser.serialize_value(&value).unwrap();
+    ser.end().unwrap();
+    assert_eq!(buf, b"a: 1\n");
+}
+
+#[test]
+fn test_decode_crlf_round_trip() {
+    let value: Value = from_str(DOC).unwrap();
+    let crlf = encode(&value, LineEnding::CrLf, true);
+    assert_eq!(from_str::<Value>(&crlf).unwrap(), value);
+}
+
+#[test]
+fn test_decode_strips_bom() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Doc {
+        name: String,
+    }
+
+    let doc: Doc = from_str("\u{feff}name: Ada\r\n").unwrap();
+    assert_eq!(doc.name, "Ada");
+
+    let doc: Doc = from_slice(b"\xef\xbb\xbfname: Ada").unwrap();
+    assert_eq!(doc.name, "Ada");
+}
+
+#[test]
+fn test_lone_carriage_return() {
+    let err = from_str::<Value>("a: 1\nb: x\ry\nc: 3").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Carriage return must be followed by a line feed at line 2, column 5"
+    );
+
+    // Column counts characters, not bytes
+    let err = from_str::<Value>("naïve: é\r").unwrap_err();
+    assert!(err.to_string().ends_with("line 1, column 9"), "{}", err);
+}
+
+#[test]
+fn test_quoted_carriage_return_escape() {
+    let value: Value = from_str("text: \"a\\rb\"\r\n").unwrap();
+    assert_eq!(value.as_object().unwrap()["text"].as_str(), Some("a\rb"));
+}
@@ -0,0 +1,169 @@
+use serde_toon2::error::ErrorKind;
+use serde_toon2::query::{self, Query};
+use serde_toon2::{Value, from_str, toon};
+
+fn doc() -> Value {
+    from_str(
+        "\
+store:
+  name: Corner Shop
+  items[4]{sku,price,tags}:
+    A1,2.5,null
+    B2,12,sale
+    C3,40,null
+    D4,10,sale
+  staff[2]:
+    - name: Ada
+      active: true
+      address:
+        city: Oslo
+    - name: Bob
+      active: false
+\"first name\": Cy",
+    )
+    .unwrap()
+}
+
+fn select(expr: &str) -> Vec<Value> {
+    query::select_cloned(&doc(), expr).unwrap()
+}
+
+#[test]
+fn test_names_indexes_and_wildcards() {
+    assert_eq!(select("store.name"), [toon!("Corner Shop")]);
+    assert_eq!(select("$.store.name"), select(".store.name"));
+    assert_eq!(select("$['first name']"), [toon!("Cy")]);
+    assert_eq!(select("[\"first name\"]"), [toon!("Cy")]);
+    assert_eq!(select("store.items[0].sku"), [toon!("A1")]);
+    assert_eq!(select("store.items[-1].sku"), [toon!("D4")]);
+    assert_eq!(select("store.staff[*].name"), [toon!("Ada"), toon!("Bob")]);
+    assert_eq!(select("store.staff[0].*").len(), 3);
+    assert_eq!(select("$"), [doc()]);
+    assert_eq!(select("."), [doc()]);
+
+    assert!(select("store.missing").is_empty());
+    assert!(select("store.items[9]").is_empty());
+    assert!(select("store.name[0]").is_empty());
+}
+
+#[test]
+fn test_slices() {
+    let skus = |expr: &str| -> Vec<Value> {
+        let q = format!("store.items[{}].sku", expr);
+        select(&q)
+    };
+    assert_eq!(skus("1:3"), [toon!("B2"), toon!("C3")]);
+    assert_eq!(skus(":2"), [toon!("A1"), toon!("B2")]);
+    assert_eq!(skus("-2:"), [toon!("C3"), toon!("D4")]);
+    assert_eq!(skus("::2"), [toon!("A1"), toon!("C3")]);
+    assert_eq!(
+        skus("::-1"),
+        [toon!("D4"), toon!("C3"), toon!("B2"), toon!("A1")]
+    );
+    assert_eq!(skus("2:0:-1"), [toon!("C3"), toon!("B2")]);
+    assert!(skus("3:1").is_empty());
+    assert_eq!(skus("-100:100").len(), 4);
+
+    let max = i64::MAX;
+    let min = i64::MIN;
+    assert_eq!(skus(&format!("1::{max}")), [toon!("B2")]);
+    assert_eq!(skus(&format!("::{min}")), [toon!("D4")]);
+    assert_eq!(skus(&format!("{min}:{max}:{max}")), [toon!("A1")]);
+    assert_eq!(skus(&format!("{max}:{min}:{min}")), [toon!("D4")]);
+}
+
+#[test]
+fn test_recursive_descent() {
+    assert_eq!(
+        select("..name"),
+        [toon!("Corner Shop"), toon!("Ada"), toon!("Bob")]
+    );
+    assert_eq!(select("..city"), [toon!("Oslo")]);
+    assert_eq!(select("store..[0].sku"), [toon!("A1")]);
+
+    let all = select("..*");
+    assert!(all.contains(&toon!("Oslo")));
+    assert!(all.contains(&toon!("Cy")));
+}
+
+#[test]
+fn test_filters() {
+    let skus = |filter: &str| -> Vec<Value> {
+        let q = format!("store.items[?{}].sku", filter);
+        select(&q)
+    };
+    assert_eq!(skus("price>10"), [toon!("B2"), toon!("C3")]);
+    assert_eq!(
+        skus("@.price >= 10"),
+        [toon!("B2"), toon!("C3"), toon!("D4")]
+    );
+    assert_eq!(skus("price == 2.5"), [toon!("A1")]);
+    assert_eq!(skus("tags == 'sale' && price < 11"), [toon!("D4")]);
+    assert_eq!(
+        skus("tags == null || price > 30"),
+        [toon!("A1"), toon!("C3")]
+    );
+    assert_eq!(skus("!(tags == \"sale\")"), [toon!("A1"), toon!("C3")]);
+    assert_eq!(skus("tags"), [toon!("B2"), toon!("D4")]);
+    assert_eq!(skus("price > 'x'"), Vec::<Value>::new());
+    assert_eq!(skus("sku != 'A1' && price <= 10"), [toon!("D4")]);
+
+    assert_eq!(select("store.staff[?active].name"), [toon!("Ada")]);
+    assert_eq!(select("store.staff[?!active].name"), [toon!("Bob")]);
+    assert_eq!(
+        select("store.staff[?address.city == 'Oslo'].name"),
+        [toon!("Ada")]
+    );
+    assert_eq!(
+        select("store.staff[?@['address']['city']].name"),
+        [toon!("Ada")]
+    );
+    assert!(select("store.staff[?missing == 1]").is_empty());
+    assert_eq!(select("store.staff[?missing != 1]").len(), 2);
+}
+
+#[test]
+fn test_references_and_reuse() {
+    let doc = doc();
+    let query: Query = "store.items[*].price".parse().unwrap();
+    let prices = query.select(&doc);
+    assert_eq!(prices.len(), 4);
+    assert!(std::ptr::eq(prices[0], &doc["store"]["items"][0]["price"]));
+
+    let other = toon!({ "store": { "items": [{ "price": 1 }] } });
+    assert_eq!(query.select_cloned(&other), [toon!(1)]);
+
+    assert!(Query::parse("store.items[0]").unwrap().is_singular());
+    assert!(!query.is_singular());
+}
+
+#[test]
+fn test_parse_errors() {
+    for (expr, message) in [
+        (
+            "store[",
+            "Expected an index, a slice, '*', a name or a filter at position 6",
+        ),
+        ("store.items[*", "Expected ']' at position 13"),
+        ("store.", "Expected a name at position 6"),
+        (
+            "store.items[::0]",
+            "Slice step cannot be zero at position 15",
+        ),
+        (
+            "items[?price >]",
+            "Expected a path or a value at position 14",
+        ),
+        ("items[?'open]", "Unterminated string at position 7"),
+        ("items[?..a]", "Expected a path or a value at position 7"),
+        (
+            "items[?a[*]]",
+            "Paths in filters may only use names and indexes at position 8",
+        ),
+        ("items)", "Unexpected character at position 5"),
+    ] {
+        let err = Query::parse(expr).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidQuery));
+        assert_eq!(err.to_string(), format!("{message} of the query"), "{expr}");
+    }
+}
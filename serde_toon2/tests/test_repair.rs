@@ -0,0 +1,203 @@
+use serde::Deserialize;
+use serde_toon2::error::ErrorKind;
+use serde_toon2::repair::{Repair, RepairKind, from_str_repaired};
+use serde_toon2::{DecoderOptions, Indent, Value, from_str, from_str_with_options};
+
+fn repair_options() -> DecoderOptions {
+    DecoderOptions {
+        repair: true,
+        ..Default::default()
+    }
+}
+
+/// Decodes input that needs no repairs, for comparison with repaired output.
+fn decode(toon: &str) -> Value {
+    let out = from_str_repaired(toon, DecoderOptions::default()).unwrap();
+    assert!(out.repairs.is_empty());
+    out.value
+}
+
+fn kinds(repairs: &[Repair]) -> Vec<RepairKind> {
+    repairs.iter().map(|repair| repair.kind).collect()
+}
+
+#[test]
+fn test_valid_input_needs_no_repairs() {
+    let toon = "users[2]{id,name}:\n  1,Ada\n  2,Bob\nmeta:\n  tags[2]: a,b";
+    let out = from_str_repaired(toon, DecoderOptions::default()).unwrap();
+    assert_eq!(
+        serde_json::to_value(&out.value).unwrap(),
+        from_str::<serde_json::Value>(toon).unwrap()
+    );
+    assert!(out.repairs.is_empty());
+}
+
+#[test]
+fn test_code_fences() {
+    let out = from_str_repaired(
+        "```toon\nname: Ada\nage: 42\n```\n",
+        DecoderOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(out.value, decode("name: Ada\nage: 42"));
+    assert_eq!(
+        out.repairs,
+        vec![
+            Repair {
+                line: 1,
+                kind: RepairKind::CodeFence,
+            },
+            Repair {
+                line: 4,
+                kind: RepairKind::CodeFence,
+            },
+        ]
+    );
+
+    // An unclosed fence, as left by a cut-off reply
+    let out = from_str_repaired("```\nname: Ada", DecoderOptions::default()).unwrap();
+    assert_eq!(kinds(&out.repairs), vec![RepairKind::CodeFence]);
+}
+
+#[test]
+fn test_count_off_by_one() {
+    let toon = "tags[3]: a,b\nitems[1]:\n  - x\n  - y";
+    assert!(matches!(
+        from_str::<Value>(toon).unwrap_err().kind(),
+        ErrorKind::CountMismatch
+    ));
+
+    let out = from_str_repaired(toon, DecoderOptions::default()).unwrap();
+    assert_eq!(out.value, decode("tags[2]: a,b\nitems[2]:\n  - x\n  - y"));
+    assert_eq!(
+        out.repairs,
+        vec![
+            Repair {
+                line: 1,
+                kind: RepairKind::Count {
+                    declared: 3,
+                    received: 2,
+                },
+            },
+            Repair {
+                line: 2,
+                kind: RepairKind::Count {
+                    declared: 1,
+                    received: 2,
+                },
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_trailing_delimiter_in_rows() {
+    let toon = "rows[2]{id,name}:\n  1,Ada,\n  2,Bob";
+    assert!(matches!(
+        from_str::<Value>(toon).unwrap_err().kind(),
+        ErrorKind::WidthMismatch
+    ));
+
+    let out = from_str_repaired(toon, DecoderOptions::default()).unwrap();
+    assert_eq!(out.value, decode("rows[2]{id,name}:\n  1,Ada\n  2,Bob"));
+    assert_eq!(
+        out.repairs,
+        vec![Repair {
+            line: 2,
+            kind: RepairKind::TrailingDelimiter,
+        }]
+    );
+}
+
+#[test]
+fn test_trailing_delimiter_in_inline_array() {
+    let out = from_str_repaired("tags[2|]: a|b|", DecoderOptions::default()).unwrap();
+    assert_eq!(out.value, decode("tags[2|]: a|b"));
+    assert_eq!(kinds(&out.repairs), vec![RepairKind::TrailingDelimiter]);
+
+    // An explicitly empty last value is kept
+    let out = from_str_repaired("tags[3]: a,b,\"\"", DecoderOptions::default()).unwrap();
+    assert_eq!(
+        out.value.as_object().unwrap()["tags"]
+            .as_array()
+            .unwrap()
+            .len(),
+        3
+    );
+    assert!(out.repairs.is_empty());
+}
+
+#[test]
+fn test_mixed_indentation() {
+    let toon = "user:\n    name: Ada\n    address:\n      city: London\n    age: 42\nactive: true";
+    let expected =
+        decode("user:\n  name: Ada\n  address:\n    city: London\n  age: 42\nactive: true");
+
+    let out = from_str_repaired(toon, DecoderOptions::default()).unwrap();
+    assert_eq!(out.value, expected);
+    assert_eq!(
+        out.repairs[0],
+        Repair {
+            line: 2,
+            kind: RepairKind::Indentation {
+                found: 4,
+                expected: 2,
+            },
+        }
+    );
+    assert_eq!(out.repairs.len(), 4);
+}
+
+#[test]
+fn test_mixed_indentation_in_strict_mode() {
+    let toon = "items[2]:\n    - a\n    - b";
+    let strict = DecoderOptions {
+        strict: true,
+        indent: Indent::Spaces(2),
+        ..Default::default()
+    };
+    let opts = DecoderOptions {
+        repair: true,
+        ..strict.clone()
+    };
+
+    assert!(from_str_with_options::<Value>("root:\n   x: 1", strict).is_err());
+    let value: Value = from_str_with_options(toon, opts).unwrap();
+    assert_eq!(value, decode("items[2]:\n  - a\n  - b"));
+}
+
+#[test]
+fn test_repair_option_with_typed_output() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Row {
+        id: u32,
+        name: String,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Doc {
+        rows: Vec<Row>,
+    }
+
+    let toon = "```\nrows[3]{id,name}:\n    1,Ada,\n    2,Bob,\n```";
+    let doc: Doc = from_str_with_options(toon, repair_options()).unwrap();
+    assert_eq!(
+        doc.rows,
+        vec![
+            Row {
+                id: 1,
+                name: "Ada".to_string(),
+            },
+            Row {
+                id: 2,
+                name: "Bob".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_unrepairable_input() {
+    let err = from_str_repaired("rows[1]{a,b}:\n  1,2,3", DecoderOptions::default()).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::WidthMismatch));
+}
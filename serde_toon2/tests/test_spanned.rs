@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+use serde_toon2::{
+    DecoderOptions, PathExpansion, Spanned, Value, from_str, from_str_with_options, from_value,
+    to_string,
+};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Deserialize)]
+struct Config {
+    name: Spanned<String>,
+    server: Spanned<Server>,
+    tags: Spanned<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Server {
+    host: String,
+    port: Spanned<u32>,
+}
+
+fn text<T>(input: &str, spanned: &Spanned<T>) -> String {
+    input[spanned.span()].to_string()
+}
+
+#[test]
+fn test_scalar_locations() {
+    let input = "name: \"demo app\"\nserver:\n  host: localhost\n  port: 99999\ntags[2]: a,b";
+    let config: Config = from_str(input).unwrap();
+
+    assert_eq!(config.name.get_ref(), "demo app");
+    assert_eq!(text(input, &config.name), "\"demo app\"");
+    assert_eq!((config.name.line(), config.name.column()), (1, 7));
+
+    let port = &config.server.get_ref().port;
+    assert_eq!(*port.get_ref(), 99999);
+    assert_eq!(text(input, port), "99999");
+    assert_eq!((port.line(), port.column()), (4, 9));
+    assert_eq!(config.server.get_ref().host, "localhost");
+}
+
+#[test]
+fn test_container_locations() {
+    let input = "name: x\nserver:\n  host: localhost\n  port: 80\ntags[2]: a,b";
+    let config: Config = from_str(input).unwrap();
+
+    assert_eq!(text(input, &config.server), "host: localhost\n  port: 80");
+    assert_eq!((config.server.line(), config.server.column()), (3, 3));
+    assert_eq!(text(input, &config.tags), "a,b");
+    assert_eq!(config.tags.get_ref(), &["a", "b"]);
+    assert_eq!(config.tags.column(), 10);
+}
+
+#[test]
+fn test_array_items_and_rows() {
+    #[derive(Deserialize)]
+    struct Doc {
+        users: Vec<Spanned<User>>,
+        items: Spanned<Vec<Spanned<Value>>>,
+        empty: Spanned<BTreeMap<String, u32>>,
+    }
+
+    #[derive(Deserialize)]
+    struct User {
+        id: Spanned<u64>,
+        name: String,
+    }
+
+    let input = "\
+users[2]{id,name}:
+  1,Ada
+  22,Bob
+items[2]:
+  - sku: A1
+    qty: 2
+  - 7
+empty:";
+    let doc: Doc = from_str(input).unwrap();
+
+    assert_eq!(text(input, &doc.users[1]), "22,Bob");
+    assert_eq!(doc.users[1].get_ref().name, "Bob");
+    let id = &doc.users[1].get_ref().id;
+    assert_eq!((*id.get_ref(), id.line(), id.column()), (22, 3, 3));
+
+    assert_eq!(text(input, &doc.items), "- sku: A1\n    qty: 2\n  - 7");
+    let items = doc.items.get_ref();
+    assert_eq!(text(input, &items[0]), "sku: A1\n    qty: 2");
+    assert_eq!((items[1].line(), items[1].column()), (7, 5));
+
+    assert!(doc.empty.get_ref().is_empty());
+    assert_eq!(doc.empty.span(), input.len()..input.len());
+    assert_eq!((doc.empty.line(), doc.empty.column()), (8, 7));
+}
+
+#[test]
+fn test_optional_enum_and_borrowed_fields() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Mode {
+        Fast,
+        Limit(u32),
+    }
+
+    #[derive(Deserialize)]
+    struct Doc<'a> {
+        mode: Spanned<Mode>,
+        limit: Spanned<Mode>,
+        missing: Option<Spanned<u32>>,
+        present: Option<Spanned<u32>>,
+        #[serde(borrow)]
+        label: Spanned<&'a str>,
+    }
+
+    let input = "mode: Fast\nlimit:\n  Limit: 3\npresent: 5\nlabel: plain";
+    let doc: Doc = from_str(input).unwrap();
+
+    assert_eq!(doc.mode.get_ref(), &Mode::Fast);
+    assert_eq!(doc.limit.get_ref(), &Mode::Limit(3));
+    assert_eq!(doc.limit.line(), 3);
+    assert!(doc.missing.is_none());
+    assert_eq!(doc.present.as_ref().map(Spanned::line), Some(4));
+    assert_eq!(*doc.label.get_ref(), "plain");
+    assert_eq!(text(input, &doc.label), "plain");
+}
+
+#[test]
+fn test_key_order_does_not_matter() {
+    #[derive(Deserialize)]
+    struct Doc<'a> {
+        name: &'a str,
+        port: Spanned<u32>,
+    }
+
+    for input in ["name: ada\nport: 1", "port: 1\nname: ada"] {
+        let doc: Doc = from_str(input).unwrap();
+        assert_eq!(doc.name, "ada");
+        assert_eq!(text(input, &doc.port), "1");
+    }
+}
+
+#[test]
+fn test_buffered_containers() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(untagged)]
+    enum Port {
+        Number(u32),
+        Name(String),
+    }
+
+    #[derive(Deserialize)]
+    struct Inner {
+        port: Spanned<u32>,
+    }
+
+    #[derive(Deserialize)]
+    struct Flattened {
+        #[serde(flatten)]
+        inner: Inner,
+    }
+
+    // An untagged enum works when the `Spanned` wraps it as a whole
+    let input = "a: http\nb: 80";
+    let ports: BTreeMap<String, Spanned<Port>> = from_str(input).unwrap();
+    assert_eq!(ports["a"].get_ref(), &Port::Name("http".to_string()));
+    assert_eq!(ports["b"].get_ref(), &Port::Number(80));
+    assert_eq!(text(input, &ports["b"]), "80");
+
+    // Flattening buffers the fields, which loses their locations
+    let err = from_str::<Flattened>("port: 80")
+        .map(|doc| doc.inner.port.into_inner())
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("expected a spanned value"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_columns_count_characters() {
+    #[derive(Deserialize)]
+    struct Doc {
+        café: String,
+        n: Spanned<i32>,
+        m: Spanned<i32>,
+    }
+
+    let input = "\u{feff}café: é\nn: -1\nm: 2";
+    let doc: Doc = from_str(input).unwrap();
+    assert_eq!(doc.café, "é");
+    assert_eq!((doc.n.line(), doc.n.column()), (2, 4));
+    assert_eq!(text(input, &doc.n), "-1");
+
+    let doc: Doc = from_str("café: é\r\nn: -1\r\nm: 2").unwrap();
+    assert_eq!((doc.m.line(), doc.m.column()), (3, 4));
+}
+
+#[test]
+fn test_expanded_paths_cover_their_values() {
+    #[derive(Deserialize)]
+    struct Doc {
+        a: Spanned<BTreeMap<String, u32>>,
+    }
+
+    let options = DecoderOptions {
+        expand_paths: PathExpansion::Safe,
+        ..DecoderOptions::default()
+    };
+    let input = "a.b: 1\na.c: 22";
+    let doc: Doc = from_str_with_options(input, options).unwrap();
+    assert_eq!(doc.a.get_ref()["c"], 22);
+    assert_eq!(text(input, &doc.a), "1\na.c: 22");
+}
+
+#[test]
+fn test_value_and_serialization() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Doc {
+        port: Spanned<u32>,
+    }
+
+    let first: Doc = from_str("port: 80").unwrap();
+    let second: Doc = from_str("\n\nport:    80").unwrap();
+    assert_ne!(first.port.span(), second.port.span());
+    assert_eq!(first, second);
+    assert_eq!(to_string(&first).unwrap(), "port: 80");
+
+    let err = from_value::<Doc>(from_str("port: 80").unwrap()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Spanned values can only be deserialized from TOON text"
+    );
+
+    let err = from_str::<Doc>("port: -1").unwrap_err();
+    assert!(err.to_string().contains("invalid value"), "{err}");
+}
@@ -0,0 +1,56 @@
+use serde_toon2::stats::{self, ApproxTokenizer, Tokenizer};
+use serde_toon2::{EncoderOptions, Value, from_str};
+
+#[test]
+fn test_approx_tokenizer_counts() {
+    assert_eq!(ApproxTokenizer.count_tokens(""), 0);
+    assert_eq!(ApproxTokenizer.count_tokens("abcdefgh"), 2);
+    assert_eq!(ApproxTokenizer.count_tokens("123456"), 2);
+    assert_eq!(ApproxTokenizer.count_tokens("a\n    b"), 4);
+    assert_eq!(ApproxTokenizer.count_tokens("日本"), 2);
+}
+
+#[test]
+fn test_compare_tabular_saves_tokens() {
+    let toon = "users[3]{id,name,role}:\n  1,Alice,admin\n  2,Bob,user\n  3,Carol,user";
+    let value: Value = from_str(toon).unwrap();
+
+    let report = stats::compare(&value, &EncoderOptions::default()).unwrap();
+
+    assert_eq!(report.toon.bytes, toon.len());
+    assert!(report.toon.tokens < report.json_compact.tokens);
+    assert!(report.json_compact.tokens < report.json_pretty.tokens);
+    assert!(report.toon.token_savings(&report.json_compact) > 0.0);
+}
+
+#[test]
+fn test_compare_json_renderings() {
+    let value: Value = from_str("a: 1\nb[2]: x,\"y\\\"z\"").unwrap();
+    let bytes = |text: &str| text.len();
+
+    let report = stats::compare_with(&value, &EncoderOptions::default(), &bytes).unwrap();
+
+    assert_eq!(
+        report.json_compact.tokens,
+        r#"{"a":1,"b":["x","y\"z"]}"#.len()
+    );
+    assert_eq!(
+        report.json_pretty.tokens,
+        "{\n  \"a\": 1,\n  \"b\": [\n    \"x\",\n    \"y\\\"z\"\n  ]\n}".len()
+    );
+}
+
+#[test]
+fn test_compare_per_key_breakdown() {
+    let value: Value = from_str("name: Ada\ntags[2]: a,b").unwrap();
+
+    let report = stats::compare(&value, &EncoderOptions::default()).unwrap();
+
+    let keys: Vec<&str> = report.keys.iter().map(|k| k.key.as_str()).collect();
+    assert_eq!(keys, vec!["name", "tags"]);
+    assert_eq!(report.keys[0].toon.bytes, "name: Ada".len());
+    assert_eq!(
+        report.keys[1].json_compact.bytes,
+        r#"{"tags":["a","b"]}"#.len()
+    );
+}
@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use serde_toon2::ser::Serializer;
+use serde_toon2::{
+    DecoderOptions, EncoderOptions, Indent, LineEnding, StreamDeserializer, Value, from_str,
+};
+use std::io::{self, Read};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Event {
+    id: u32,
+    kind: String,
+    tags: Vec<String>,
+}
+
+fn events() -> Vec<Event> {
+    (1..=3)
+        .map(|id| Event {
+            id,
+            kind: format!("kind-{}", id),
+            tags: vec!["a".to_string(); id as usize],
+        })
+        .collect()
+}
+
+fn write_stream(options: EncoderOptions) -> String {
+    let mut buf = Vec::new();
+    let mut ser = Serializer::new(&mut buf, options);
+    for event in events() {
+        ser.write_document(&event).unwrap();
+    }
+    ser.end().unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn test_write_documents() {
+    let stream = write_stream(EncoderOptions::default());
+    assert_eq!(
+        stream,
+        "id: 1\nkind: kind-1\ntags[1]: a\n---\nid: 2\nkind: kind-2\ntags[2]: a,a\n---\nid: 3\nkind: kind-3\ntags[3]: a,a,a"
+    );
+}
+
+#[test]
+fn test_round_trip() {
+    let stream = write_stream(EncoderOptions::default());
+    let decoded: Vec<Event> = StreamDeserializer::new(stream.as_bytes())
+        .collect::<serde_toon2::Result<_>>()
+        .unwrap();
+    assert_eq!(decoded, events());
+}
+
+#[test]
+fn test_round_trip_crlf_with_trailing_newline() {
+    let stream = write_stream(EncoderOptions {
+        line_ending: LineEnding::CrLf,
+        trailing_newline: true,
+        ..Default::default()
+    });
+    assert!(stream.contains("\r\n---\r\n"));
+    assert!(stream.ends_with("a,a,a\r\n"));
+
+    let decoded: Vec<Event> = StreamDeserializer::new(stream.as_bytes())
+        .collect::<serde_toon2::Result<_>>()
+        .unwrap();
+    assert_eq!(decoded, events());
+}
+
+#[test]
+fn test_nested_documents_and_root_arrays() {
+    let stream = "user:\n  name: Ada\n---\n[2]: 1,2\n---\nplain";
+    let docs: Vec<Value> = StreamDeserializer::new(stream.as_bytes())
+        .collect::<serde_toon2::Result<_>>()
+        .unwrap();
+    assert_eq!(docs.len(), 3);
+    assert_eq!(docs[0], from_str::<Value>("user:\n  name: Ada").unwrap());
+    assert_eq!(docs[1].as_array().unwrap().len(), 2);
+    assert_eq!(docs[2].as_str(), Some("plain"));
+}
+
+#[test]
+fn test_empty_documents_are_skipped() {
+    let stream = "---\nid: 1\n---\n\n---\n---\nid: 2\n---\n";
+    let docs: Vec<Value> = StreamDeserializer::new(stream.as_bytes())
+        .collect::<serde_toon2::Result<_>>()
+        .unwrap();
+    assert_eq!(docs.len(), 2);
+
+    assert_eq!(
+        StreamDeserializer::<_, Value>::new("".as_bytes()).count(),
+        0
+    );
+}
+
+#[test]
+fn test_indented_separator_is_content() {
+    let stream = "note: \"---\"\nitems[1]:\n  - ---";
+    let docs: Vec<Value> = StreamDeserializer::new(stream.as_bytes())
+        .collect::<serde_toon2::Result<_>>()
+        .unwrap();
+    assert_eq!(docs.len(), 1);
+}
+
+#[test]
+fn test_errors_use_absolute_lines() {
+    let stream = "id: 1\n---\nid: 2\ntags[2]: a\n---\nid: 3";
+    let results: Vec<serde_toon2::Result<Value>> =
+        StreamDeserializer::new(stream.as_bytes()).collect();
+
+    assert_eq!(results.len(), 3);
+    assert!(results[0].is_ok());
+    let err = results[1].as_ref().unwrap_err();
+    assert_eq!(err.line(), Some(4));
+    assert!(err.to_string().contains("line 4"));
+    // A bad document does not stop the stream
+    assert!(results[2].is_ok());
+}
+
+#[test]
+fn test_options_apply_to_every_document() {
+    let stream = "a:\n\tb: 1\n---\nc:\n\td: 2";
+    let opts = DecoderOptions {
+        indent: Indent::Tabs,
+        strict: true,
+        ..Default::default()
+    };
+    let docs: Vec<Value> = StreamDeserializer::with_options(stream.as_bytes(), opts)
+        .collect::<serde_toon2::Result<_>>()
+        .unwrap();
+    assert_eq!(docs[1], from_str::<Value>("c:\n  d: 2").unwrap());
+}
+
+#[test]
+fn test_io_error_ends_stream() {
+    struct Failing(bool);
+
+    impl Read for Failing {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0 {
+                return Err(io::Error::other("disk on fire"));
+            }
+            self.0 = true;
+            let data = b"id: 1\n---\n";
+            buf[..data.len()].copy_from_slice(data);
+            Ok(data.len())
+        }
+    }
+
+    let mut stream = StreamDeserializer::<_, Value>::new(Failing(false));
+    assert!(stream.next().unwrap().is_ok());
+    assert!(stream.next().unwrap().is_err());
+    assert!(stream.next().is_none());
+}
@@ -0,0 +1,294 @@
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Serialize};
+use serde_toon2::error::ErrorKind;
+use serde_toon2::ser::Serializer;
+use serde_toon2::{
+    EncoderOptions, KeyFolding, Value, from_str, to_string, to_string_with_options, toon,
+};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+#[derive(Serialize)]
+struct Address {
+    city: String,
+    zip: Option<String>,
+}
+
+#[derive(Serialize)]
+enum Status {
+    Active,
+    Suspended { reason: String, days: u32 },
+    Renamed(String),
+}
+
+#[derive(Serialize)]
+struct Account {
+    id: u64,
+    name: String,
+    address: Address,
+    empty: BTreeMap<String, i32>,
+    tags: Vec<&'static str>,
+    status: Status,
+    previous: Status,
+    legacy: Status,
+    scores: BTreeMap<String, f64>,
+}
+
+fn account() -> Account {
+    Account {
+        id: 7,
+        name: "Ada Lovelace".to_string(),
+        address: Address {
+            city: "London".to_string(),
+            zip: None,
+        },
+        empty: BTreeMap::new(),
+        tags: vec!["admin", "ops"],
+        status: Status::Suspended {
+            reason: "review".to_string(),
+            days: 3,
+        },
+        previous: Status::Active,
+        legacy: Status::Renamed("ada".to_string()),
+        scores: BTreeMap::from([("math".to_string(), 9.5), ("logic".to_string(), 10.0)]),
+    }
+}
+
+/// Encodes through the buffered path, via a fully built `Value`.
+fn buffered<T: Serialize>(value: &T) -> String {
+    let json = serde_json::to_value(value).unwrap();
+    let value: Value = serde_json::from_value(json).unwrap();
+    let mut buf = Vec::new();
+    Serializer::new(&mut buf, EncoderOptions::default())
+        .serialize_value(&value)
+        .unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn test_streaming_matches_buffered_output() {
+    let account = account();
+
+    let streamed = to_string(&account).unwrap();
+
+    assert_eq!(streamed, buffered(&account));
+    assert_eq!(
+        streamed,
+        "id: 7\nname: Ada Lovelace\naddress:\n  city: London\n  zip: null\nempty:\ntags[2]: admin,ops\nstatus:\n  Suspended:\n    reason: review\n    days: 3\nprevious: Active\nlegacy:\n  Renamed: ada\nscores:\n  logic: 10\n  math: 9.5"
+    );
+}
+
+#[test]
+fn test_streaming_nested_in_list_items() {
+    let accounts = vec![account(), account()];
+
+    assert_eq!(to_string(&accounts).unwrap(), buffered(&accounts));
+}
+
+#[test]
+fn test_streaming_root_variants() {
+    let status = Status::Suspended {
+        reason: "x".to_string(),
+        days: 1,
+    };
+
+    assert_eq!(
+        to_string(&status).unwrap(),
+        "Suspended:\n  reason: x\n  days: 1"
+    );
+    assert_eq!(
+        to_string(&Status::Renamed("y".to_string())).unwrap(),
+        "Renamed: y"
+    );
+    assert_eq!(to_string(&Status::Active).unwrap(), "Active");
+}
+
+#[test]
+fn test_key_folding_buffers_document() {
+    #[derive(Serialize)]
+    struct Outer {
+        a: Inner,
+        b: i32,
+    }
+
+    #[derive(Serialize)]
+    struct Inner {
+        c: i32,
+    }
+
+    let opts = EncoderOptions {
+        key_folding: KeyFolding::Safe,
+        ..Default::default()
+    };
+
+    let toon = to_string_with_options(
+        &Outer {
+            a: Inner { c: 1 },
+            b: 2,
+        },
+        opts,
+    )
+    .unwrap();
+
+    assert_eq!(toon, "a.c: 1\nb: 2");
+}
+
+#[derive(Serialize)]
+struct Row {
+    id: u32,
+    name: &'static str,
+}
+
+#[test]
+fn test_streaming_arrays_match_buffered_output() {
+    #[derive(Serialize)]
+    struct Doc {
+        rows: Vec<Row>,
+        accounts: Vec<Account>,
+        statuses: Vec<Status>,
+        maps: Vec<BTreeMap<&'static str, i32>>,
+        values: Vec<Value>,
+        numbers: Vec<i32>,
+        pairs: Vec<Vec<i32>>,
+        empty: Vec<Row>,
+    }
+
+    let doc = Doc {
+        rows: vec![Row { id: 1, name: "a" }, Row { id: 2, name: "b,c" }],
+        accounts: vec![account(), account()],
+        statuses: vec![
+            Status::Active,
+            Status::Renamed("x".to_string()),
+            Status::Suspended {
+                reason: "y".to_string(),
+                days: 2,
+            },
+        ],
+        maps: vec![BTreeMap::from([("a", 1)]), BTreeMap::from([("b", 2)])],
+        values: vec![toon!({ "a": 1 }), toon!({ "a": 2, "b": 3 }), toon!([1])],
+        numbers: vec![1, 2, 3],
+        pairs: vec![vec![1, 2], vec![3]],
+        empty: vec![],
+    };
+
+    assert_eq!(to_string(&doc).unwrap(), buffered(&doc));
+    assert_eq!(
+        to_string(&doc.rows).unwrap(),
+        "[2]{id,name}:\n  1,a\n  2,\"b,c\""
+    );
+    assert_eq!(to_string(&doc.statuses).unwrap(), buffered(&doc.statuses));
+}
+
+#[test]
+fn test_streaming_writes_list_items_as_they_are_serialized() {
+    #[derive(Clone)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl std::io::Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[derive(Serialize)]
+    struct Item {
+        id: u32,
+        tags: Vec<&'static str>,
+    }
+
+    /// Items that check the output already holds every earlier item.
+    struct Items(Shared);
+
+    impl Serialize for Items {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut seq = serializer.serialize_seq(Some(3))?;
+            for id in 0..3 {
+                if id > 0 {
+                    let written = String::from_utf8(self.0.0.borrow().clone()).unwrap();
+                    assert!(written.ends_with(&format!("id: {}\n    tags[1]: x", id - 1)));
+                }
+                seq.serialize_element(&Item {
+                    id,
+                    tags: vec!["x"],
+                })?;
+            }
+            seq.end()
+        }
+    }
+
+    let out = Shared(Rc::default());
+    let mut ser = Serializer::new(out.clone(), EncoderOptions::default());
+    Items(out.clone()).serialize(&mut ser).unwrap();
+
+    assert_eq!(
+        String::from_utf8(out.0.take()).unwrap(),
+        "[3]:\n  - id: 0\n    tags[1]: x\n  - id: 1\n    tags[1]: x\n  - id: 2\n    tags[1]: x"
+    );
+}
+
+#[test]
+fn test_streaming_rows_that_do_not_fit_a_table() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Row {
+        id: u32,
+        tags: Option<Vec<String>>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Item {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        note: Option<String>,
+    }
+
+    let rows = vec![
+        Row { id: 1, tags: None },
+        Row {
+            id: 2,
+            tags: Some(vec!["a".to_string()]),
+        },
+    ];
+    let toon = to_string(&rows).unwrap();
+    assert_eq!(toon, buffered(&rows));
+    assert_eq!(from_str::<Vec<Row>>(&toon).unwrap(), rows);
+
+    let items = vec![
+        Item {
+            name: "a".to_string(),
+            note: Some("x".to_string()),
+        },
+        Item {
+            name: "b".to_string(),
+            note: None,
+        },
+    ];
+    let toon = to_string(&items).unwrap();
+    assert_eq!(toon, buffered(&items));
+    assert_eq!(from_str::<Vec<Item>>(&toon).unwrap(), items);
+}
+
+#[test]
+fn test_streaming_checks_declared_length() {
+    struct Short;
+
+    impl Serialize for Short {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut seq = serializer.serialize_seq(Some(3))?;
+            seq.serialize_element(&account())?;
+            seq.end()
+        }
+    }
+
+    let err = to_string(&Short).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::CountMismatch), "{err}");
+    assert!(
+        err.to_string().contains("declared 3 elements, got 1"),
+        "{err}"
+    );
+}
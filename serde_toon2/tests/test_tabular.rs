@@ -0,0 +1,90 @@
+use serde_toon2::error::ErrorKind;
+use serde_toon2::{DecoderOptions, Value, from_str, from_str_with_options};
+
+/// Builds a tabular array with `rows` rows, replacing selected rows verbatim.
+fn table(header_len: usize, rows: usize, overrides: &[(usize, &str)]) -> String {
+    let mut doc = format!("rows[{}]{{id,name,score}}:\n", header_len);
+    for i in 0..rows {
+        match overrides.iter().find(|(row, _)| *row == i) {
+            Some((_, line)) => doc.push_str(&format!("  {}\n", line)),
+            None => doc.push_str(&format!("  {},user {},{}.5\n", i, i, i)),
+        }
+    }
+    doc
+}
+
+#[test]
+fn test_large_table_preserves_row_order() {
+    let value: Value = from_str(&table(5000, 5000, &[])).unwrap();
+
+    let rows = value.as_object().unwrap()["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), 5000);
+    for (i, row) in rows.iter().enumerate() {
+        let row = row.as_object().unwrap();
+        assert_eq!(row["id"].as_u64(), Some(i as u64));
+        assert_eq!(row["name"].as_str(), Some(format!("user {}", i).as_str()));
+    }
+}
+
+#[test]
+fn test_large_table_reports_first_error_in_row_order() {
+    let doc = table(
+        5000,
+        5000,
+        &[
+            (4000, "1,2"),
+            (1500, "1,\"bad \\x escape\",3"),
+            (3000, "1,2,3,4"),
+        ],
+    );
+
+    let err = from_str::<Value>(&doc).unwrap_err();
+
+    assert!(matches!(err.kind(), ErrorKind::InvalidEscape));
+    assert!(err.to_string().contains("line 1502"), "{}", err);
+}
+
+#[test]
+fn test_large_table_width_mismatch() {
+    let doc = table(5000, 5000, &[(4999, "1,2"), (2500, "1,2,3,4")]);
+
+    let err = from_str::<Value>(&doc).unwrap_err();
+
+    assert!(matches!(err.kind(), ErrorKind::WidthMismatch));
+    assert!(err.to_string().contains("line 2502"), "{}", err);
+}
+
+#[test]
+fn test_large_table_count_mismatch() {
+    let err = from_str::<Value>(&table(5001, 5000, &[])).unwrap_err();
+
+    assert!(matches!(err.kind(), ErrorKind::CountMismatch));
+}
+
+#[test]
+fn test_row_error_before_blank_line_wins() {
+    let mut doc = table(3000, 3000, &[(10, "1,2")]);
+    doc = doc.replacen("  100,", "\n  100,", 1);
+
+    let opts = DecoderOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let err = from_str_with_options::<Value>(&doc, opts).unwrap_err();
+
+    assert!(matches!(err.kind(), ErrorKind::WidthMismatch));
+}
+
+#[test]
+fn test_blank_line_in_large_table() {
+    let doc = table(3000, 3000, &[]).replacen("  2000,", "\n  2000,", 1);
+
+    let opts = DecoderOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let err = from_str_with_options::<Value>(&doc, opts).unwrap_err();
+
+    assert!(matches!(err.kind(), ErrorKind::InvalidSyntax));
+    assert!(err.to_string().contains("line 2003"), "{}", err);
+}
@@ -0,0 +1,101 @@
+use serde_toon2::truncate::{ElisionKind, TruncateOptions, to_string_truncated, truncate};
+use serde_toon2::{EncoderOptions, Value, from_str};
+
+fn table(rows: usize) -> Value {
+    let body: Vec<String> = (0..rows)
+        .map(|i| {
+            format!(
+                "  {},user{},\"a fairly long description for row {}\"",
+                i, i, i
+            )
+        })
+        .collect();
+    from_str(&format!(
+        "users[{}]{{id,name,bio}}:\n{}",
+        rows,
+        body.join("\n")
+    ))
+    .unwrap()
+}
+
+#[test]
+fn test_max_rows_fixes_header() {
+    let opts = TruncateOptions {
+        max_rows: Some(2),
+        ..Default::default()
+    };
+
+    let out = to_string_truncated(&table(5), &EncoderOptions::default(), &opts).unwrap();
+
+    assert!(out.toon.starts_with("users[2]{id,name,bio}:"));
+    let decoded: Value = from_str(&out.toon).unwrap();
+    assert_eq!(
+        decoded.as_object().unwrap()["users"]
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(
+        out.elisions[0].kind,
+        ElisionKind::Rows { kept: 2, total: 5 }
+    );
+    assert_eq!(out.elisions[0].path, "/users");
+}
+
+#[test]
+fn test_max_string_len_adds_marker() {
+    let value: Value = from_str("a/b: abcdefghijkl").unwrap();
+
+    let (short, elisions) = truncate(&value, None, Some(3), "[cut]");
+
+    assert_eq!(short.as_object().unwrap()["a/b"], Value::from("abc[cut]"));
+    assert_eq!(elisions[0].path, "/a~1b");
+    assert_eq!(elisions[0].kind, ElisionKind::String { kept: 3, total: 12 });
+}
+
+#[test]
+fn test_no_limits_is_identity() {
+    let value = table(3);
+
+    let out = to_string_truncated(
+        &value,
+        &EncoderOptions::default(),
+        &TruncateOptions::default(),
+    )
+    .unwrap();
+
+    assert!(out.elisions.is_empty());
+    assert!(out.fits_budget);
+    assert_eq!(out.toon, serde_toon2::to_string(&value).unwrap());
+}
+
+#[test]
+fn test_token_budget_shrinks_until_it_fits() {
+    let value = table(50);
+    let opts = TruncateOptions {
+        token_budget: Some(60),
+        ..Default::default()
+    };
+
+    let out = to_string_truncated(&value, &EncoderOptions::default(), &opts).unwrap();
+
+    assert!(out.fits_budget);
+    assert!(out.tokens <= 60);
+    assert!(!out.elisions.is_empty());
+    let _: Value = from_str(&out.toon).unwrap();
+}
+
+#[test]
+fn test_token_budget_reports_when_unreachable() {
+    let value: Value = from_str("a: 1\nb: 2\nc: 3").unwrap();
+    let opts = TruncateOptions {
+        token_budget: Some(1),
+        ..Default::default()
+    };
+
+    let out = to_string_truncated(&value, &EncoderOptions::default(), &opts).unwrap();
+
+    assert!(!out.fits_budget);
+    assert!(out.elisions.is_empty());
+}
@@ -0,0 +1,623 @@
+use serde::{Deserialize, Serialize};
+use serde_toon2::{Map, Number, Value, from_str, from_value, to_string, to_value, toon};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::hash::{Hash, Hasher};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct User {
+    id: u32,
+    name: String,
+    roles: Vec<String>,
+    manager: Option<String>,
+}
+
+fn ada() -> User {
+    User {
+        id: 1,
+        name: "Ada".to_string(),
+        roles: vec!["admin".to_string(), "ops".to_string()],
+        manager: None,
+    }
+}
+
+#[test]
+fn test_to_value_struct() {
+    let value = to_value(&ada()).unwrap();
+
+    let mut expected = Map::new();
+    expected.insert("id".to_string(), Value::Number(Number::U64(1)));
+    expected.insert("name".to_string(), Value::from("Ada"));
+    expected.insert(
+        "roles".to_string(),
+        Value::Array(vec![Value::from("admin"), Value::from("ops")]),
+    );
+    expected.insert("manager".to_string(), Value::Null);
+    assert_eq!(value, Value::Object(expected));
+}
+
+#[test]
+fn test_to_value_map_keys() {
+    let mut map = BTreeMap::new();
+    map.insert(2, "b");
+    map.insert(1, "a");
+    let value = to_value(&map).unwrap();
+    let keys: Vec<_> = value.as_object().unwrap().keys().cloned().collect();
+    assert_eq!(keys, ["1", "2"]);
+
+    let mut map = BTreeMap::new();
+    map.insert(vec![1], "a");
+    assert!(to_value(&map).is_err());
+}
+
+#[test]
+fn test_from_value_round_trip() {
+    let user: User = from_value(to_value(&ada()).unwrap()).unwrap();
+    assert_eq!(user, ada());
+
+    let value: Value = from_str("id: 1\nname: Ada\nroles[2]: admin,ops\nmanager: null").unwrap();
+    let user: User = from_value(value).unwrap();
+    assert_eq!(user, ada());
+}
+
+#[test]
+fn test_from_value_type_error() {
+    assert!(from_value::<User>(toon!({ "id": "one" })).is_err());
+    assert!(from_value::<u8>(toon!(300)).is_err());
+}
+
+#[test]
+fn test_toon_macro_literals() {
+    assert_eq!(toon!(null), Value::Null);
+    assert_eq!(toon!(true), Value::Bool(true));
+    assert_eq!(toon!(false), Value::Bool(false));
+    assert_eq!(toon!(42), Value::Number(Number::I64(42)));
+    assert_eq!(toon!(-1.5), Value::Number(Number::F64(-1.5)));
+    assert_eq!(toon!("hi"), Value::from("hi"));
+    assert_eq!(toon!([]), Value::Array(vec![]));
+    assert_eq!(toon!({}), Value::Object(Map::new()));
+}
+
+#[test]
+fn test_toon_macro_matches_hand_built() {
+    let value = toon!({
+        "users": [
+            { "id": 1, "name": "Ada" },
+            { "id": 2, "name": "Bob" },
+        ],
+        "meta": { "page": 1, "next": null },
+        "tags": ["a", true, null, [1, 2]],
+    });
+
+    let user = |id: i64, name: &str| {
+        let mut map = Map::new();
+        map.insert("id".to_string(), Value::Number(Number::I64(id)));
+        map.insert("name".to_string(), Value::from(name));
+        Value::Object(map)
+    };
+    let mut meta = Map::new();
+    meta.insert("page".to_string(), Value::Number(Number::I64(1)));
+    meta.insert("next".to_string(), Value::Null);
+    let mut expected = Map::new();
+    expected.insert(
+        "users".to_string(),
+        Value::Array(vec![user(1, "Ada"), user(2, "Bob")]),
+    );
+    expected.insert("meta".to_string(), Value::Object(meta));
+    expected.insert(
+        "tags".to_string(),
+        Value::Array(vec![
+            Value::from("a"),
+            Value::Bool(true),
+            Value::Null,
+            Value::Array(vec![
+                Value::Number(Number::I64(1)),
+                Value::Number(Number::I64(2)),
+            ]),
+        ]),
+    );
+    assert_eq!(value, Value::Object(expected));
+}
+
+#[test]
+fn test_toon_macro_interpolation() {
+    let user = ada();
+    let key = String::from("dynamic");
+    let limit = 10;
+
+    let value = toon!({
+        "user": user,
+        (key): limit * 2,
+        "over": limit > 5,
+        "roles": [user.roles[0], user.roles.len()],
+    });
+
+    assert_eq!(
+        to_string(&value).unwrap(),
+        "user:\n  id: 1\n  name: Ada\n  roles[2]: admin,ops\n  manager: null\ndynamic: 20\nover: true\nroles[2]: admin,2"
+    );
+}
+
+#[test]
+fn test_toon_macro_preserves_key_order() {
+    let value = toon!({ "z": 1, "a": 2, "m": 3 });
+    let keys: Vec<_> = value.as_object().unwrap().keys().cloned().collect();
+    assert_eq!(keys, ["z", "a", "m"]);
+}
+
+fn sample() -> Value {
+    toon!({
+        "users": [
+            { "id": 1, "name": "Ada", "tags": ["admin"] },
+            { "id": 2, "name": "Bob", "tags": [] },
+        ],
+        "a/b": { "m~n": true },
+        "": "empty key",
+        "count": 2,
+    })
+}
+
+#[test]
+fn test_index() {
+    let value = sample();
+    assert_eq!(value["users"][0]["name"], "Ada");
+    assert_eq!(value["users"][1]["id"], 2);
+    assert_eq!(value[""], "empty key");
+    assert_eq!(value[String::from("count")], 2);
+
+    assert!(value["missing"].is_null());
+    assert!(value["users"][9].is_null());
+    assert!(value["count"]["nested"].is_null());
+    assert!(value[0].is_null());
+}
+
+#[test]
+fn test_get() {
+    let mut value = sample();
+    assert_eq!(value.get("count"), Some(&toon!(2)));
+    assert_eq!(value.get("missing"), None);
+    assert_eq!(value.get("users").and_then(|users| users.get(2)), None);
+    assert_eq!(value.get(0), None);
+
+    *value.get_mut("count").unwrap() = toon!(3);
+    assert_eq!(value["count"], 3);
+    assert!(value.get_mut("missing").is_none());
+}
+
+#[test]
+fn test_index_mut() {
+    let mut value = Value::Null;
+    value["server"]["port"] = toon!(8080);
+    value["server"]["hosts"] = toon!(["a", "b"]);
+    value["server"]["hosts"][1] = toon!("c");
+    value["enabled"] = toon!(true);
+
+    assert_eq!(
+        value.to_string(),
+        "server:\n  port: 8080\n  hosts[2]: a,c\nenabled: true"
+    );
+}
+
+#[test]
+#[should_panic(expected = "cannot access index 2 of array of length 2")]
+fn test_index_mut_out_of_bounds() {
+    let mut value = toon!([1, 2]);
+    value[2] = toon!(3);
+}
+
+#[test]
+#[should_panic(expected = "cannot access key \"name\" of an array")]
+fn test_index_mut_wrong_type() {
+    let mut value = toon!([1, 2]);
+    value["name"] = toon!(3);
+}
+
+#[test]
+fn test_pointer() {
+    let value = sample();
+    assert_eq!(value.pointer(""), Some(&value));
+    assert_eq!(value.pointer("/users/0/tags/0"), Some(&toon!("admin")));
+    assert_eq!(value.pointer("/a~1b/m~0n"), Some(&toon!(true)));
+    assert_eq!(value.pointer("/"), Some(&toon!("empty key")));
+
+    assert_eq!(value.pointer("users"), None);
+    assert_eq!(value.pointer("/users/00"), None);
+    assert_eq!(value.pointer("/users/+1"), None);
+    assert_eq!(value.pointer("/users/-"), None);
+    assert_eq!(value.pointer("/users/2"), None);
+    assert_eq!(value.pointer("/count/x"), None);
+}
+
+#[test]
+fn test_pointer_mut() {
+    let mut value = sample();
+    value
+        .pointer_mut("/users/1/tags")
+        .and_then(Value::as_array_mut)
+        .unwrap()
+        .push(toon!("new"));
+    assert_eq!(value["users"][1]["tags"], toon!(["new"]));
+
+    *value.pointer_mut("").unwrap() = toon!(null);
+    assert!(value.is_null());
+    assert!(value.pointer_mut("/x").is_none());
+}
+
+#[test]
+fn test_take() {
+    let mut value = sample();
+    let users = value["users"].take();
+    assert_eq!(users.as_array().map(Vec::len), Some(2));
+    assert!(value["users"].is_null());
+    assert!(value.get("users").is_some());
+}
+
+#[test]
+fn test_as_mut() {
+    let mut value = toon!({ "a": 1 });
+    value
+        .as_object_mut()
+        .unwrap()
+        .insert("b".to_string(), toon!(2));
+    assert_eq!(value, toon!({ "a": 1, "b": 2 }));
+    assert!(value.as_array_mut().is_none());
+    assert!(toon!("x").as_object_mut().is_none());
+}
+
+#[test]
+fn test_entry() {
+    let mut value = Value::Null;
+    for word in ["a", "b", "a"] {
+        let count = value.entry(word).unwrap().or_insert(toon!(0));
+        *count = toon!(count.as_i64().unwrap() + 1);
+    }
+    assert_eq!(value, toon!({ "a": 2, "b": 1 }));
+
+    assert!(toon!([]).entry("a").is_none());
+    assert!(toon!(1).entry("a").is_none());
+}
+
+#[test]
+fn test_map_api() {
+    let mut map: Map<String, Value> = [("b", 1), ("a", 2)]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), toon!(v)))
+        .collect();
+    map.extend([("c".to_string(), toon!(3))]);
+    map.shift_remove("b");
+
+    let keys: Vec<&String> = map.keys().collect();
+    assert_eq!(keys, ["a", "c"]);
+    assert_eq!(format!("{:?}", Map::from_iter([("a", 1)])), r#"{"a": 1}"#);
+    assert_eq!(Value::Object(map.clone()), toon!({ "a": 2, "c": 3 }));
+
+    let decoded: Map<String, u32> = from_str("a: 2\nc: 3").unwrap();
+    let pairs: Vec<(String, u32)> = decoded.into_iter().collect();
+    assert_eq!(pairs, [("a".to_string(), 2), ("c".to_string(), 3)]);
+    assert_eq!(Map::<String, u32>::with_capacity(4).len(), 0);
+}
+
+#[test]
+fn test_partial_eq_primitives() {
+    assert_eq!(toon!(42), 42);
+    assert_eq!(toon!(42), 42u8);
+    assert_eq!(toon!(42), 42.0);
+    assert_eq!(42usize, toon!(42));
+    assert_eq!(toon!(-1), -1i8);
+    assert_ne!(toon!(-1), 1u64);
+    assert_eq!(toon!(1.5), 1.5f32);
+    assert_eq!(toon!(true), true);
+    assert_eq!(toon!("x"), "x");
+    assert_eq!("x", toon!("x"));
+    assert_eq!(toon!("x"), String::from("x"));
+
+    assert_ne!(toon!("1"), 1);
+    assert_ne!(toon!(1), "1");
+    assert_ne!(toon!(null), false);
+    assert_ne!(toon!(1.5), 1);
+}
+
+#[test]
+fn test_display_and_from_str() {
+    let value = sample();
+    let text = value.to_string();
+    assert_eq!(text, to_string(&value).unwrap());
+    assert_eq!(format!("{}", toon!([1, 2])), "[2]: 1,2");
+
+    let parsed: Value = text.parse().unwrap();
+    assert_eq!(parsed.to_string(), text);
+    assert_eq!(parsed["users"][1]["name"], "Bob");
+
+    let err = "items[3]: a,b".parse::<Value>().unwrap_err();
+    assert!(err.to_string().contains('3'));
+}
+
+fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn assert_same(a: Number, b: Number) {
+    assert_eq!(a, b);
+    assert_eq!(a.cmp(&b), std::cmp::Ordering::Equal);
+    assert_eq!(
+        hash_of(&a),
+        hash_of(&b),
+        "{:?} and {:?} hash differently",
+        a,
+        b
+    );
+}
+
+#[test]
+fn test_number_equality_across_variants() {
+    assert_same(Number::I64(1), Number::U64(1));
+    assert_same(Number::I64(1), Number::F64(1.0));
+    assert_same(
+        Number::U64(1 << 63),
+        Number::F64(9_223_372_036_854_775_808.0),
+    );
+    assert_same(Number::I64(i64::MIN), Number::F64(i64::MIN as f64));
+    assert_same(Number::F64(-0.0), Number::I64(0));
+    assert_same(Number::F64(-0.0), Number::F64(0.0));
+    assert_same(Number::F64(f64::NAN), Number::F64(-f64::NAN));
+    assert_same(Number::F64(1e300), Number::F64(1e300));
+
+    assert_ne!(Number::I64(-1), Number::U64(u64::MAX));
+    assert_ne!(Number::I64(1), Number::F64(1.5));
+    // 2^53 + 1 is not representable as a float, so no float equals it
+    assert_ne!(Number::I64((1 << 53) + 1), Number::F64((1u64 << 53) as f64));
+}
+
+#[test]
+fn test_number_total_order() {
+    let mut numbers = vec![
+        Number::F64(f64::NAN),
+        Number::U64(u64::MAX),
+        Number::F64(f64::INFINITY),
+        Number::F64(0.5),
+        Number::I64(-3),
+        Number::F64(f64::NEG_INFINITY),
+        Number::I64((1 << 53) + 1),
+        Number::F64((1u64 << 53) as f64),
+        Number::F64(-2.5),
+        Number::U64(0),
+        Number::F64(1e30),
+    ];
+    numbers.sort();
+    assert_eq!(
+        numbers,
+        vec![
+            Number::F64(f64::NEG_INFINITY),
+            Number::I64(-3),
+            Number::F64(-2.5),
+            Number::U64(0),
+            Number::F64(0.5),
+            Number::F64((1u64 << 53) as f64),
+            Number::I64((1 << 53) + 1),
+            Number::U64(u64::MAX),
+            Number::F64(1e30),
+            Number::F64(f64::INFINITY),
+            Number::F64(f64::NAN),
+        ]
+    );
+    assert!(Number::F64(-0.5) < Number::I64(0));
+    assert!(Number::F64(-1.5) > Number::I64(-2));
+}
+
+#[test]
+fn test_value_order_between_kinds() {
+    let mut values = vec![
+        toon!({ "a": 1 }),
+        toon!([1]),
+        toon!("a"),
+        toon!(1),
+        toon!(true),
+        toon!(null),
+    ];
+    values.sort();
+    assert_eq!(
+        values,
+        vec![
+            toon!(null),
+            toon!(true),
+            toon!(1),
+            toon!("a"),
+            toon!([1]),
+            toon!({ "a": 1 }),
+        ]
+    );
+    assert!(toon!(false) < toon!(true));
+    assert!(toon!("abc") < toon!("abd"));
+    assert!(toon!([1, 2]) < toon!([1, 2, 0]));
+    assert!(toon!([1, 3]) > toon!([1, 2, 0]));
+    assert!(toon!({ "a": 1, "b": 2 }) < toon!({ "a": 1, "b": 3 }));
+    assert!(toon!({ "a": 2 }) > toon!({ "a": 1, "b": 1 }));
+}
+
+#[test]
+fn test_value_key_order_does_not_matter() {
+    let a = toon!({ "id": 1, "name": "Ada" });
+    let b = toon!({ "name": "Ada", "id": 1.0 });
+    assert_eq!(a, b);
+    assert_eq!(a.cmp(&b), std::cmp::Ordering::Equal);
+    assert_eq!(hash_of(&a), hash_of(&b));
+}
+
+#[test]
+fn test_value_sets_deduplicate() {
+    let decoded: Value = from_str("[4]: 1,1.0,-0,0").unwrap();
+    let mut items = decoded.as_array().unwrap().clone();
+    items.push(toon!(1u64));
+    items.push(toon!("1"));
+
+    let hashed: HashSet<&Value> = items.iter().collect();
+    let ordered: BTreeSet<&Value> = items.iter().collect();
+    assert_eq!(hashed.len(), 3);
+    assert_eq!(
+        ordered.into_iter().cloned().collect::<Vec<_>>(),
+        vec![toon!(0), toon!(1), toon!("1")]
+    );
+
+    let mut counts: BTreeMap<Value, usize> = BTreeMap::new();
+    for row in [toon!({ "a": 1 }), toon!({ "a": 1.0 }), toon!({ "a": 2 })] {
+        *counts.entry(row).or_default() += 1;
+    }
+    assert_eq!(counts[&toon!({ "a": 1 })], 2);
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+enum Shape {
+    Empty,
+    Circle(f64),
+    Point(i32, i32),
+    Rect { width: u32, height: u32 },
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Drawing {
+    title: Option<String>,
+    layer: Option<u32>,
+    shapes: Vec<Shape>,
+}
+
+fn drawing() -> Drawing {
+    Drawing {
+        title: Some("plan".to_string()),
+        layer: Some(5),
+        shapes: vec![
+            Shape::Empty,
+            Shape::Circle(1.5),
+            Shape::Point(-1, 2),
+            Shape::Rect {
+                width: 3,
+                height: 4,
+            },
+        ],
+    }
+}
+
+#[test]
+fn test_options_and_enums_round_trip() {
+    let value = to_value(&drawing()).unwrap();
+    assert_eq!(from_value::<Drawing>(value.clone()).unwrap(), drawing());
+    assert_eq!(Drawing::deserialize(&value).unwrap(), drawing());
+
+    let mut text = drawing();
+    let rect = text.shapes.pop().unwrap();
+    assert_eq!(
+        from_str::<Drawing>(&to_string(&text).unwrap()).unwrap(),
+        text
+    );
+    assert_eq!(from_str::<Shape>(&to_string(&rect).unwrap()).unwrap(), rect);
+
+    let none: Drawing = from_str("title: null\nshapes[0]:").unwrap();
+    assert_eq!(none.title, None);
+    assert_eq!(none.layer, None);
+}
+
+#[test]
+fn test_enum_shapes() {
+    assert_eq!(from_value::<Shape>(toon!("Empty")).unwrap(), Shape::Empty);
+    assert_eq!(
+        from_value::<Shape>(toon!({ "Empty": null })).unwrap(),
+        Shape::Empty
+    );
+    assert_eq!(
+        from_value::<Shape>(toon!(["Point", 1, 2])).unwrap(),
+        Shape::Point(1, 2)
+    );
+    assert_eq!(
+        Shape::deserialize(&toon!({ "Point": [3, 4] })).unwrap(),
+        Shape::Point(3, 4)
+    );
+
+    assert!(from_value::<Shape>(toon!(5)).is_err());
+    assert!(from_value::<Shape>(toon!({ "Circle": 1, "Empty": null })).is_err());
+    assert!(Shape::deserialize(&toon!({ "Empty": 1 })).is_err());
+    assert!(Shape::deserialize(&toon!("Triangle")).is_err());
+}
+
+#[test]
+fn test_enum_shapes_owned_and_borrowed() {
+    let accepted = [
+        (toon!("Empty"), Shape::Empty),
+        (toon!(["Empty"]), Shape::Empty),
+        (toon!({ "Circle": 1.5 }), Shape::Circle(1.5)),
+        (toon!(["Circle", 1.5]), Shape::Circle(1.5)),
+        (toon!(["Point", 1, 2]), Shape::Point(1, 2)),
+        (toon!({ "Point": [1, 2] }), Shape::Point(1, 2)),
+        (
+            toon!(["Rect", 3, 4]),
+            Shape::Rect {
+                width: 3,
+                height: 4,
+            },
+        ),
+    ];
+    for (value, expected) in accepted {
+        assert_eq!(Shape::deserialize(&value).unwrap(), expected, "{value:?}");
+        assert_eq!(from_value::<Shape>(value).unwrap(), expected);
+    }
+
+    let rejected = [
+        toon!([]),
+        toon!([1, 2]),
+        toon!(["Empty", 1]),
+        toon!(["Circle"]),
+        toon!(["Circle", 1, 2]),
+        toon!({}),
+        toon!(null),
+    ];
+    for value in rejected {
+        let borrowed = Shape::deserialize(&value).unwrap_err().to_string();
+        let owned = from_value::<Shape>(value).unwrap_err().to_string();
+        assert_eq!(borrowed, owned);
+    }
+
+    let err = from_value::<Shape>(toon!(5)).unwrap_err().to_string();
+    assert!(
+        err.contains("an array starting with the variant name"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_deserialize_borrowed() {
+    #[derive(Deserialize)]
+    struct Borrowed<'a> {
+        name: &'a str,
+        roles: Vec<&'a str>,
+        #[serde(borrow)]
+        manager: Option<&'a str>,
+    }
+
+    let value = to_value(&ada()).unwrap();
+    let user = Borrowed::deserialize(&value).unwrap();
+    assert_eq!(user.name, "Ada");
+    assert_eq!(user.roles, ["admin", "ops"]);
+    assert_eq!(user.manager, None);
+
+    let map = BTreeMap::<&str, Value>::deserialize(&value).unwrap();
+    assert_eq!(map["id"], Value::Number(Number::U64(1)));
+    assert_eq!(Value::deserialize(&value).unwrap(), value);
+}
+
+#[test]
+fn test_value_from_json() {
+    let json = r#"{"z": 1, "a": [true, null, -2, 1.5], "big": 18446744073709551615, "s": "x"}"#;
+    let value: Value = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        value.as_object().unwrap().keys().collect::<Vec<_>>(),
+        ["z", "a", "big", "s"]
+    );
+    assert_eq!(value["a"][0], true);
+    assert!(value["a"][1].is_null());
+    assert_eq!(value["a"][2], -2);
+    assert_eq!(value["a"][3], 1.5);
+    assert_eq!(value["big"].as_u64(), Some(u64::MAX));
+    assert_eq!(value["s"], "x");
+
+    assert!(serde_json::from_str::<Value>("[1, {").is_err());
+}